@@ -1,15 +1,34 @@
 //! Action Executor — pluggable trait + factory.
 
+pub mod print;
 pub mod type_text;
 
 use crate::config::ActionConfig;
 use crate::router::Intent;
 
+/// Command action that reverts the last action ("scratch that").
+pub const UNDO_COMMAND: &str = "undo";
+
 /// Trait for action execution backends.
 pub trait ActionExecutor: Send + Sync {
     /// Execute an intent (type text, run command, etc.).
     fn execute(&self, intent: &Intent) -> anyhow::Result<()>;
     fn name(&self) -> &str;
+
+    /// Revert the most recent undoable action.
+    ///
+    /// Returns `false` if there is nothing to undo. Backends without undo
+    /// support keep this default.
+    fn undo(&self) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    /// Whether a streaming recording may be executed piece by piece as it is
+    /// transcribed. Backends that treat each execution as one complete request
+    /// (e.g. a computer-use goal) return `false` to get the whole utterance.
+    fn streams(&self) -> bool {
+        true
+    }
 }
 
 /// Function signature for an external factory that can create action backends.
@@ -29,7 +48,8 @@ pub fn create_action(
     extra_factory: Option<&ActionFactory>,
 ) -> anyhow::Result<Box<dyn ActionExecutor>> {
     match cfg.backend.as_str() {
-        "type-text" => Ok(Box::new(type_text::TypeTextAction)),
+        "type-text" => Ok(Box::new(type_text::TypeTextAction::from_config(cfg))),
+        "print" => Ok(Box::new(print::PrintAction)),
         other => {
             if let Some(factory) = extra_factory {
                 if let Some(result) = factory(cfg) {
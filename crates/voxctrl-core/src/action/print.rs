@@ -0,0 +1,45 @@
+//! Print action — writes intents to stdout instead of acting on them.
+//!
+//! Meant for headless runs on recorded audio (regression tests, piping
+//! transcripts into other tools): dictation is printed one line per
+//! utterance, commands as `[action] args`.
+
+use std::io::Write;
+
+use crate::router::Intent;
+use super::ActionExecutor;
+
+pub struct PrintAction;
+
+/// Render an intent as the line printed for it.
+fn render(intent: &Intent) -> String {
+    match intent {
+        Intent::Dictate(text) => text.clone(),
+        Intent::Command { action, args } => format!("[{action}] {args}"),
+    }
+}
+
+impl ActionExecutor for PrintAction {
+    fn execute(&self, intent: &Intent) -> anyhow::Result<()> {
+        let mut out = std::io::stdout().lock();
+        writeln!(out, "{}", render(intent))?;
+        out.flush()?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "print"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_dictation_and_commands() {
+        assert_eq!(render(&Intent::Dictate("hello world".into())), "hello world");
+        let cmd = Intent::Command { action: "key".into(), args: serde_json::json!({"key": "enter"}) };
+        assert_eq!(render(&cmd), r#"[key] {"key":"enter"}"#);
+    }
+}
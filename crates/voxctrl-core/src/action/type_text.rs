@@ -1,11 +1,82 @@
-//! Type-text action — types dictated text at the cursor using enigo.
+//! Type-text action — types dictated text at the cursor using enigo, or
+//! pastes it through the clipboard.
 
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::{ActionConfig, InjectionMode};
 use crate::router::Intent;
 use super::ActionExecutor;
-use enigo::{Enigo, Keyboard, Settings};
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Number of dictations that can be undone in a row.
+const UNDO_DEPTH: usize = 10;
+/// Pause after filling the clipboard, before sending the paste shortcut.
+const CLIPBOARD_SETTLE: Duration = Duration::from_millis(50);
+/// Time the target app gets to read the clipboard before it is restored.
+const CLIPBOARD_RESTORE_DELAY: Duration = Duration::from_millis(300);
+/// Most key presses a single spoken command may send.
+const MAX_KEY_REPEAT: u64 = 50;
 
 /// Types dictated text at the current cursor position.
-pub struct TypeTextAction;
+///
+/// Also handles the `key` (`{"key": "enter", "repeat": 2}`) and
+/// `select_words` (`{"n": 3}`) commands emitted by the rules router, so simple
+/// keyboard commands work without another backend. Repeat counts are capped
+/// at [`MAX_KEY_REPEAT`].
+///
+/// Undo deletes the last dictation with one backspace per grapheme typed.
+/// Key commands clear the undo stack, since the cursor may have moved.
+pub struct TypeTextAction {
+    injection: InjectionMode,
+    paste_min_chars: usize,
+    /// Grapheme counts of recent dictations, oldest first.
+    undo_stack: Mutex<VecDeque<usize>>,
+}
+
+impl TypeTextAction {
+    /// Keystroke-only action.
+    pub fn new() -> Self {
+        Self {
+            injection: InjectionMode::Keystrokes,
+            paste_min_chars: 0,
+            undo_stack: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Action using the configured injection mode.
+    pub fn from_config(cfg: &ActionConfig) -> Self {
+        Self { injection: cfg.injection, paste_min_chars: cfg.paste_min_chars, ..Self::new() }
+    }
+
+    fn uses_clipboard(&self, text: &str) -> bool {
+        match self.injection {
+            InjectionMode::Keystrokes => false,
+            InjectionMode::ClipboardPaste => true,
+            InjectionMode::Auto => text.chars().count() >= self.paste_min_chars,
+        }
+    }
+
+    fn push_undo(&self, text: &str) {
+        let mut stack = self.undo_stack.lock().unwrap();
+        if stack.len() == UNDO_DEPTH {
+            stack.pop_front();
+        }
+        stack.push_back(text.graphemes(true).count());
+    }
+
+    fn pop_undo(&self) -> Option<usize> {
+        self.undo_stack.lock().unwrap().pop_back()
+    }
+}
+
+impl Default for TypeTextAction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl ActionExecutor for TypeTextAction {
     fn execute(&self, intent: &Intent) -> anyhow::Result<()> {
@@ -13,10 +84,42 @@ impl ActionExecutor for TypeTextAction {
             Intent::Dictate(text) => {
                 let mut enigo = Enigo::new(&Settings::default())
                     .map_err(|e| anyhow::anyhow!("failed to init enigo: {e}"))?;
-                enigo
-                    .text(text)
-                    .map_err(|e| anyhow::anyhow!("failed to type text: {e}"))?;
-                log::debug!("TypeTextAction: typed {} chars", text.len());
+                if self.uses_clipboard(text) && paste(&mut enigo, text)? {
+                    log::debug!("TypeTextAction: pasted {} chars", text.len());
+                } else {
+                    enigo
+                        .text(text)
+                        .map_err(|e| anyhow::anyhow!("failed to type text: {e}"))?;
+                    log::debug!("TypeTextAction: typed {} chars", text.len());
+                }
+                self.push_undo(text);
+                Ok(())
+            }
+            Intent::Command { action, args } if action == "key" => {
+                let name = args["key"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("key command needs a \"key\" argument"))?;
+                let key = key_from_name(name)
+                    .ok_or_else(|| anyhow::anyhow!("unknown key {name:?}"))?;
+                let repeat = clamp_repeat(args["repeat"].as_u64().unwrap_or(1));
+                let mut enigo = Enigo::new(&Settings::default())
+                    .map_err(|e| anyhow::anyhow!("failed to init enigo: {e}"))?;
+                self.undo_stack.lock().unwrap().clear();
+                for _ in 0..repeat {
+                    enigo
+                        .key(key, Direction::Click)
+                        .map_err(|e| anyhow::anyhow!("failed to press {name:?}: {e}"))?;
+                }
+                log::debug!("TypeTextAction: pressed {name:?} ×{repeat}");
+                Ok(())
+            }
+            Intent::Command { action, args } if action == "select_words" => {
+                let n = clamp_repeat(args["n"].as_u64().unwrap_or(1));
+                let mut enigo = Enigo::new(&Settings::default())
+                    .map_err(|e| anyhow::anyhow!("failed to init enigo: {e}"))?;
+                self.undo_stack.lock().unwrap().clear();
+                select_words_left(&mut enigo, n)?;
+                log::debug!("TypeTextAction: selected {n} words");
                 Ok(())
             }
             Intent::Command { action, .. } => {
@@ -31,4 +134,175 @@ impl ActionExecutor for TypeTextAction {
     fn name(&self) -> &str {
         "type-text"
     }
+
+    fn undo(&self) -> anyhow::Result<bool> {
+        let Some(count) = self.pop_undo() else {
+            return Ok(false);
+        };
+        let mut enigo = Enigo::new(&Settings::default())
+            .map_err(|e| anyhow::anyhow!("failed to init enigo: {e}"))?;
+        for _ in 0..count {
+            enigo
+                .key(Key::Backspace, Direction::Click)
+                .map_err(|e| anyhow::anyhow!("failed to press backspace: {e}"))?;
+        }
+        log::debug!("TypeTextAction: undid {count} graphemes");
+        Ok(true)
+    }
+}
+
+/// Paste `text` through the clipboard, then put the previous clipboard text back.
+///
+/// Only text can be restored, so if the clipboard holds anything else (an
+/// image, files) or nothing, it is left untouched and `false` is returned for
+/// the caller to type `text` instead.
+fn paste(enigo: &mut Enigo, text: &str) -> anyhow::Result<bool> {
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| anyhow::anyhow!("failed to open clipboard: {e}"))?;
+    let Ok(saved) = clipboard.get_text() else {
+        log::debug!("TypeTextAction: clipboard doesn't hold text — typing instead of pasting");
+        return Ok(false);
+    };
+    clipboard
+        .set_text(text)
+        .map_err(|e| anyhow::anyhow!("failed to set clipboard: {e}"))?;
+    std::thread::sleep(CLIPBOARD_SETTLE);
+
+    let result = send_paste_shortcut(enigo);
+
+    std::thread::sleep(CLIPBOARD_RESTORE_DELAY);
+    if let Err(e) = clipboard.set_text(saved) {
+        log::warn!("TypeTextAction: failed to restore clipboard: {e}");
+    }
+    result.map(|()| true)
+}
+
+/// Cap a spoken repeat count at [`MAX_KEY_REPEAT`].
+fn clamp_repeat(count: u64) -> u64 {
+    if count > MAX_KEY_REPEAT {
+        log::warn!("TypeTextAction: repeat count {count} capped at {MAX_KEY_REPEAT}");
+    }
+    count.min(MAX_KEY_REPEAT)
+}
+
+/// Extend the selection `n` words to the left of the cursor with
+/// Ctrl+Shift+Left (Option+Shift+Left on macOS), always releasing the modifiers.
+fn select_words_left(enigo: &mut Enigo, n: u64) -> anyhow::Result<()> {
+    let word = if cfg!(target_os = "macos") { Key::Option } else { Key::Control };
+    enigo
+        .key(Key::Shift, Direction::Press)
+        .map_err(|e| anyhow::anyhow!("failed to press shift: {e}"))?;
+    let mut result = enigo.key(word, Direction::Press);
+    if result.is_ok() {
+        for _ in 0..n {
+            result = enigo.key(Key::LeftArrow, Direction::Click);
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+    let released = enigo.key(word, Direction::Release).and(enigo.key(Key::Shift, Direction::Release));
+    result.map_err(|e| anyhow::anyhow!("failed to select words: {e}"))?;
+    released.map_err(|e| anyhow::anyhow!("failed to release selection modifiers: {e}"))?;
+    Ok(())
+}
+
+/// Press Ctrl+V (Cmd+V on macOS), always releasing the modifier.
+fn send_paste_shortcut(enigo: &mut Enigo) -> anyhow::Result<()> {
+    let modifier = if cfg!(target_os = "macos") { Key::Meta } else { Key::Control };
+    enigo
+        .key(modifier, Direction::Press)
+        .map_err(|e| anyhow::anyhow!("failed to press paste modifier: {e}"))?;
+    let clicked = enigo.key(Key::Unicode('v'), Direction::Click);
+    let released = enigo.key(modifier, Direction::Release);
+    clicked.map_err(|e| anyhow::anyhow!("failed to send paste shortcut: {e}"))?;
+    released.map_err(|e| anyhow::anyhow!("failed to release paste modifier: {e}"))?;
+    Ok(())
+}
+
+/// Map a spoken / configured key name to an enigo `Key`.
+fn key_from_name(name: &str) -> Option<Key> {
+    let key = match name.to_lowercase().as_str() {
+        "enter" | "return" => Key::Return,
+        "tab" => Key::Tab,
+        "space" => Key::Space,
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        "escape" | "esc" => Key::Escape,
+        "up" => Key::UpArrow,
+        "down" => Key::DownArrow,
+        "left" => Key::LeftArrow,
+        "right" => Key::RightArrow,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "page up" | "pageup" => Key::PageUp,
+        "page down" | "pagedown" => Key::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_names_are_case_insensitive() {
+        assert_eq!(key_from_name("Enter"), Some(Key::Return));
+        assert_eq!(key_from_name("page up"), Some(Key::PageUp));
+        assert_eq!(key_from_name("ESC"), Some(Key::Escape));
+    }
+
+    #[test]
+    fn repeat_counts_are_capped() {
+        assert_eq!(clamp_repeat(3), 3);
+        assert_eq!(clamp_repeat(1_000_000), MAX_KEY_REPEAT);
+    }
+
+    #[test]
+    fn unknown_key_name() {
+        assert_eq!(key_from_name("hyper"), None);
+    }
+
+    #[test]
+    fn injection_mode_selects_clipboard() {
+        let mut cfg = ActionConfig { paste_min_chars: 10, ..Default::default() };
+        assert!(!TypeTextAction::from_config(&cfg).uses_clipboard(&"x".repeat(500)));
+
+        cfg.injection = InjectionMode::ClipboardPaste;
+        assert!(TypeTextAction::from_config(&cfg).uses_clipboard("hi"));
+
+        cfg.injection = InjectionMode::Auto;
+        let action = TypeTextAction::from_config(&cfg);
+        assert!(!action.uses_clipboard("short"));
+        assert!(action.uses_clipboard("ten chars!"));
+        assert!(!action.uses_clipboard("ünïcödé"));
+    }
+
+    #[test]
+    fn undo_counts_graphemes() {
+        let action = TypeTextAction::new();
+        action.push_undo("héllo");
+        action.push_undo("e\u{301}👍🏽\r\n");
+        assert_eq!(action.pop_undo(), Some(3));
+        assert_eq!(action.pop_undo(), Some(5));
+        assert_eq!(action.pop_undo(), None);
+    }
+
+    #[test]
+    fn undo_stack_keeps_most_recent() {
+        let action = TypeTextAction::new();
+        for i in 1..=UNDO_DEPTH + 2 {
+            action.push_undo(&"x".repeat(i));
+        }
+        let undone: Vec<usize> = std::iter::from_fn(|| action.pop_undo()).collect();
+        assert_eq!(undone.len(), UNDO_DEPTH);
+        assert_eq!(undone.first(), Some(&(UNDO_DEPTH + 2)));
+        assert_eq!(undone.last(), Some(&3));
+    }
+
+    #[test]
+    fn undo_with_empty_stack_is_noop() {
+        assert!(!TypeTextAction::new().undo().unwrap());
+    }
 }
@@ -0,0 +1,252 @@
+//! Speaker-bleed rejection — gates mic audio that is only the system's own
+//! playback (YouTube, music) picked up by the microphone.
+//!
+//! A loopback capture of the output device is the reference. Each 20 ms mic
+//! block is correlated against the recent reference over a range of lags,
+//! covering the acoustic path and the skew between the two streams; the best
+//! match gives the share of mic energy explained by playback. A block passes
+//! only when the rest of the mic energy — the talker — exceeds that bleed by
+//! the configured margin. Gated blocks fade to silence.
+//!
+//! The correlation at every lag comes from one FFT cross-correlation per
+//! block, and the reference energy under each lag from running sums.
+
+use super::fft::Fft;
+use super::filter::db_to_gain;
+
+const BLOCK_MS: u32 = 20;
+/// Largest offset searched between mic and reference, either way.
+const MAX_LAG_MS: u32 = 80;
+/// Mean reference power below this (-60 dBFS) can't bleed into the mic.
+const REFERENCE_SILENCE: f32 = 1e-6;
+/// Mic blocks quieter than this are passed without analysis.
+const MIC_SILENCE: f32 = 1e-8;
+
+pub struct BleedGate {
+    block: usize,
+    max_lag: usize,
+    /// Largest share of mic energy explained by the reference for a block
+    /// to still count as speech.
+    max_bleed_share: f32,
+    /// Recent reference audio at the mic rate, newest last.
+    history: Vec<f32>,
+    gain: f32,
+    /// Covers the searched reference span plus one block without wrapping.
+    fft: Fft,
+    re: Vec<f32>,
+    im: Vec<f32>,
+    mic_re: Vec<f32>,
+    mic_im: Vec<f32>,
+    /// Running sum of squared reference samples over the searched span.
+    energy: Vec<f64>,
+}
+
+impl BleedGate {
+    /// `margin_db`: how far the talker must exceed the playback bleed.
+    pub fn new(sample_rate: u32, margin_db: f32) -> Self {
+        let margin = db_to_gain(margin_db.max(0.0)).powi(2);
+        let block = (sample_rate * BLOCK_MS / 1000).max(1) as usize;
+        let max_lag = (sample_rate * MAX_LAG_MS / 1000) as usize;
+        let n = (2 * max_lag + 2 * block).next_power_of_two();
+        Self {
+            block,
+            max_lag,
+            max_bleed_share: 1.0 / (1.0 + margin),
+            history: Vec::new(),
+            gain: 1.0,
+            fft: Fft::new(n),
+            re: vec![0.0; n],
+            im: vec![0.0; n],
+            mic_re: vec![0.0; n],
+            mic_im: vec![0.0; n],
+            energy: Vec::with_capacity(n + 1),
+        }
+    }
+
+    /// Gate `mic` in place against `reference`, the loopback audio captured
+    /// over the same period and resampled to the mic rate. Both are assumed
+    /// to end at roughly the same moment.
+    pub fn process(&mut self, mic: &mut [f32], reference: &[f32]) {
+        self.history.extend_from_slice(reference);
+        // History position of the first mic sample
+        let mut at = self.history.len() as isize - mic.len() as isize;
+        for block in mic.chunks_mut(self.block) {
+            // Too short to judge: keep the previous decision
+            let target = if block.len() * 4 < self.block {
+                self.gain
+            } else if self.is_bleed(block, at) {
+                0.0
+            } else {
+                1.0
+            };
+            at += block.len() as isize;
+            let step = (target - self.gain) / block.len() as f32;
+            for s in block.iter_mut() {
+                self.gain += step;
+                *s *= self.gain;
+            }
+            self.gain = target;
+        }
+
+        let keep = self.max_lag + self.block;
+        if self.history.len() > keep {
+            self.history.drain(..self.history.len() - keep);
+        }
+    }
+
+    /// Whether the reference around history position `at` explains enough of
+    /// `mic` to call it playback.
+    fn is_bleed(&mut self, mic: &[f32], at: isize) -> bool {
+        let mic_energy: f32 = mic.iter().map(|s| s * s).sum();
+        if mic_energy < MIC_SILENCE * mic.len() as f32 {
+            return false;
+        }
+        let len = mic.len() as isize;
+        let lag = self.max_lag as isize;
+        let first = (at - lag).max(0);
+        let last = (at + lag).min(self.history.len() as isize - len);
+        if last < first {
+            return false;
+        }
+        let span = &self.history[first as usize..(last + len) as usize];
+
+        // Cross-correlation of the span with the block: spectrum of the span
+        // times the conjugate spectrum of the block, transformed back
+        self.re.fill(0.0);
+        self.im.fill(0.0);
+        self.re[..span.len()].copy_from_slice(span);
+        self.fft.forward(&mut self.re, &mut self.im);
+        self.mic_re.fill(0.0);
+        self.mic_im.fill(0.0);
+        self.mic_re[..mic.len()].copy_from_slice(mic);
+        self.fft.forward(&mut self.mic_re, &mut self.mic_im);
+        for k in 0..self.re.len() {
+            let (a, b) = (self.re[k], self.im[k]);
+            let (c, d) = (self.mic_re[k], -self.mic_im[k]);
+            self.re[k] = a * c - b * d;
+            self.im[k] = a * d + b * c;
+        }
+        self.fft.inverse(&mut self.re, &mut self.im);
+
+        self.energy.clear();
+        self.energy.push(0.0);
+        let mut sum = 0.0f64;
+        for &s in span {
+            sum += (s * s) as f64;
+            self.energy.push(sum);
+        }
+
+        let len = mic.len();
+        (0..=(last - first) as usize).any(|offset| {
+            let ref_energy = (self.energy[offset + len] - self.energy[offset]) as f32;
+            if ref_energy < REFERENCE_SILENCE * len as f32 {
+                return false;
+            }
+            let cross = self.re[offset];
+            cross * cross > self.max_bleed_share * mic_energy * ref_energy
+        })
+    }
+
+    /// Forget the reference history, e.g. at the start of a new recording.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.gain = 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::test_util::{noise, rms};
+
+    const RATE: u32 = 16_000;
+    const CHUNK: usize = 320;
+
+    /// Chords plus a little noise, standing in for music.
+    fn music(len: usize) -> Vec<f32> {
+        let hiss = noise(len, 0.05, 7);
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                [220.0, 277.0, 330.0, 880.0]
+                    .iter()
+                    .map(|f| 0.15 * (t * f * std::f32::consts::TAU).sin())
+                    .sum::<f32>()
+                    + hiss[i]
+            })
+            .collect()
+    }
+
+    /// `signal` as heard by the mic `delay` samples later, scaled by `gain`.
+    fn delayed(signal: &[f32], delay: usize, gain: f32) -> Vec<f32> {
+        (0..signal.len())
+            .map(|i| if i >= delay { gain * signal[i - delay] } else { 0.0 })
+            .collect()
+    }
+
+    fn run(gate: &mut BleedGate, mic: &[f32], reference: &[f32]) -> Vec<f32> {
+        let mut out = mic.to_vec();
+        for (m, r) in out.chunks_mut(CHUNK).zip(reference.chunks(CHUNK)) {
+            gate.process(m, r);
+        }
+        out
+    }
+
+    #[test]
+    fn playback_picked_up_by_mic_is_gated() {
+        let mut gate = BleedGate::new(RATE, 6.0);
+        let reference = music(RATE as usize);
+        // 30 ms acoustic path, quieter than the source
+        let mic: Vec<f32> = delayed(&reference, 480, 0.3)
+            .iter()
+            .zip(noise(reference.len(), 0.002, 3))
+            .map(|(m, n)| m + n)
+            .collect();
+        let out = run(&mut gate, &mic, &reference);
+        assert!(rms(&out[3_200..]) < 0.05 * rms(&mic[3_200..]));
+    }
+
+    #[test]
+    fn bleed_near_the_lag_limit_is_gated() {
+        let mut gate = BleedGate::new(RATE, 6.0);
+        let reference = music(RATE as usize);
+        // 75 ms: close to the 80 ms search range
+        let mic = delayed(&reference, 1_200, 0.3);
+        let out = run(&mut gate, &mic, &reference);
+        assert!(rms(&out[3_200..]) < 0.05 * rms(&mic[3_200..]));
+    }
+
+    #[test]
+    fn mic_passes_untouched_when_nothing_plays() {
+        let mut gate = BleedGate::new(RATE, 6.0);
+        let voice = noise(RATE as usize, 0.3, 11);
+        let out = run(&mut gate, &voice, &vec![0.0; voice.len()]);
+        assert_eq!(out, voice);
+    }
+
+    #[test]
+    fn talker_over_quiet_playback_passes() {
+        let mut gate = BleedGate::new(RATE, 6.0);
+        let reference = music(RATE as usize);
+        let voice = noise(reference.len(), 0.3, 11);
+        let mic: Vec<f32> = voice
+            .iter()
+            .zip(delayed(&reference, 480, 0.05))
+            .map(|(v, b)| v + b)
+            .collect();
+        let out = run(&mut gate, &mic, &reference);
+        assert_eq!(out, mic);
+    }
+
+    #[test]
+    fn missing_reference_passes_mic() {
+        // Loopback delivers nothing while the output is idle (e.g. WASAPI)
+        let mut gate = BleedGate::new(RATE, 6.0);
+        let voice = noise(RATE as usize, 0.3, 11);
+        let mut out = voice.clone();
+        for chunk in out.chunks_mut(CHUNK) {
+            gate.process(chunk, &[]);
+        }
+        assert_eq!(out, voice);
+    }
+}
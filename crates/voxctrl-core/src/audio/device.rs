@@ -0,0 +1,332 @@
+//! Input device preferences and recovery from device loss.
+//!
+//! cpal streams can't move between threads, so the owner of the capture
+//! stream (the GUI event loop or the TUI loop) polls a [`CaptureSupervisor`],
+//! which notices a failed or stalled stream and reopens capture on the most
+//! preferred device still present. While capture runs on a fallback device
+//! the supervisor also checks now and then whether a more preferred one has
+//! reappeared, and moves back to it.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use super::CaptureStream;
+use crate::AppStatus;
+
+/// A stream whose callback hasn't run for this long is treated as lost —
+/// some backends just stop calling back when the device is unplugged.
+pub const STALL_TIMEOUT: Duration = Duration::from_secs(3);
+/// Delay between attempts to reopen capture while no device is available.
+pub const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+/// Delay between checks for a more preferred device while on a fallback.
+pub const PREFERENCE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Pick the device to open: the first of `names` matching the earliest
+/// pattern in `preferences` (case-insensitive substring), or `None` if no
+/// preferred device is present.
+pub fn pick_device(names: &[String], preferences: &[&str]) -> Option<usize> {
+    let names: Vec<String> = names.iter().map(|n| n.to_lowercase()).collect();
+    preferences.iter().find_map(|pattern| {
+        let pattern = pattern.to_lowercase();
+        names.iter().position(|n| n.contains(&pattern))
+    })
+}
+
+/// Index of the earliest pattern in `preferences` that `name` matches.
+pub fn preference_rank(name: &str, preferences: &[&str]) -> Option<usize> {
+    let name = name.to_lowercase();
+    preferences.iter().position(|pattern| name.contains(&pattern.to_lowercase()))
+}
+
+/// Preferences a device was opened under when it wasn't the first choice.
+#[derive(Debug, Clone)]
+pub struct Fallback {
+    pub preferences: Vec<String>,
+    /// Rank of the open device in `preferences`; `None` for the system default.
+    pub rank: Option<usize>,
+}
+
+impl Fallback {
+    /// `None` when the device opened is the most preferred one.
+    pub fn new(name: &str, preferences: &[&str]) -> Option<Self> {
+        let rank = preference_rank(name, preferences);
+        (rank != Some(0)).then(|| Self { preferences: preferences.iter().map(|p| p.to_string()).collect(), rank })
+    }
+
+    /// Whether any of `names` matches an earlier preference than the open device.
+    pub fn is_outranked_by(&self, names: &[String]) -> bool {
+        let preferences: Vec<&str> = self.preferences.iter().map(String::as_str).collect();
+        names
+            .iter()
+            .filter_map(|name| preference_rank(name, &preferences))
+            .any(|rank| self.rank.is_none_or(|current| rank < current))
+    }
+}
+
+/// Liveness of a capture stream, updated from its callbacks.
+pub struct StreamHealth {
+    started: Instant,
+    /// Time of the last data callback, in ms since `started`.
+    last_callback_ms: AtomicU64,
+    failed: AtomicBool,
+}
+
+impl Default for StreamHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamHealth {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            last_callback_ms: AtomicU64::new(0),
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    /// Record a data callback.
+    pub fn beat(&self) {
+        self.last_callback_ms.store(self.started.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Record that the backend reported the device gone.
+    pub fn fail(&self) {
+        self.failed.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the stream has failed, or has stalled for [`STALL_TIMEOUT`] as of `now`.
+    pub fn is_lost(&self, now: Instant) -> bool {
+        let last = Duration::from_millis(self.last_callback_ms.load(Ordering::Relaxed));
+        self.failed.load(Ordering::Relaxed)
+            || now.saturating_duration_since(self.started) > last + STALL_TIMEOUT
+    }
+}
+
+/// A stream the supervisor can watch.
+pub trait SupervisedStream {
+    fn is_lost(&self, now: Instant) -> bool;
+    fn device_name(&self) -> &str;
+    /// Whether capture should move to a more preferred device that has
+    /// appeared since this stream was opened.
+    fn should_upgrade(&self) -> bool {
+        false
+    }
+}
+
+impl SupervisedStream for CaptureStream {
+    fn is_lost(&self, now: Instant) -> bool {
+        self.source.is_lost(now)
+    }
+
+    /// Never in the middle of a recording or utterance.
+    fn should_upgrade(&self) -> bool {
+        self.state.status.load() == AppStatus::Idle && self.source.is_outranked()
+    }
+
+    fn device_name(&self) -> &str {
+        &self.device_name
+    }
+}
+
+/// Device changes reported by [`CaptureSupervisor::poll`], for the UI.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeviceEvent {
+    /// The named device failed or stopped delivering audio.
+    Lost(String),
+    /// Capture was reopened on the named device.
+    Reconnected(String),
+}
+
+/// Owns the capture stream and reopens it after the device is lost.
+pub struct CaptureSupervisor<S = CaptureStream> {
+    stream: Option<S>,
+    next_attempt: Instant,
+    next_preference_check: Instant,
+    failures: u32,
+}
+
+impl<S: SupervisedStream> CaptureSupervisor<S> {
+    pub fn new(stream: Option<S>) -> Self {
+        let now = Instant::now();
+        Self { stream, next_attempt: now, next_preference_check: now + PREFERENCE_CHECK_INTERVAL, failures: 0 }
+    }
+
+    /// Name of the device currently captured from, if any.
+    pub fn device_name(&self) -> Option<&str> {
+        self.stream.as_ref().map(|s| s.device_name())
+    }
+
+    /// Install a stream opened elsewhere (e.g. after a config change). Pass
+    /// `None` first to release the old device before opening a new one; if
+    /// opening fails, polling keeps retrying.
+    pub fn replace(&mut self, stream: Option<S>) {
+        let now = Instant::now();
+        self.stream = stream;
+        self.next_attempt = now + RETRY_INTERVAL;
+        self.next_preference_check = now + PREFERENCE_CHECK_INTERVAL;
+        self.failures = 0;
+    }
+
+    /// Check the stream, and try `open` when there is none and a retry is
+    /// due, or when a more preferred device has appeared. Call periodically
+    /// from the thread that owns the stream.
+    pub fn poll(&mut self, now: Instant, open: impl FnOnce() -> Result<S>) -> Option<DeviceEvent> {
+        if let Some(stream) = &self.stream {
+            if stream.is_lost(now) {
+                let name = stream.device_name().to_string();
+                log::warn!("Audio device '{name}' lost — reconnecting");
+                // Release the device before reopening on the next poll
+                self.stream = None;
+                self.next_attempt = now;
+                self.failures = 0;
+                return Some(DeviceEvent::Lost(name));
+            }
+            if now < self.next_preference_check {
+                return None;
+            }
+            self.next_preference_check = now + PREFERENCE_CHECK_INTERVAL;
+            if !stream.should_upgrade() {
+                return None;
+            }
+            log::info!("Preferred audio device available — leaving '{}'", stream.device_name());
+            // Release the fallback device, then reopen at once
+            self.stream = None;
+            self.failures = 0;
+        } else if now < self.next_attempt {
+            return None;
+        }
+        match open() {
+            Ok(stream) => {
+                let name = stream.device_name().to_string();
+                log::info!("Audio capture reopened on '{name}'");
+                self.stream = Some(stream);
+                self.next_preference_check = now + PREFERENCE_CHECK_INTERVAL;
+                Some(DeviceEvent::Reconnected(name))
+            }
+            Err(e) => {
+                if self.failures == 0 {
+                    log::warn!("No audio device available, retrying: {e:#}");
+                } else {
+                    log::debug!("Audio device retry {} failed: {e:#}", self.failures);
+                }
+                self.failures += 1;
+                self.next_attempt = now + RETRY_INTERVAL;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn earliest_preference_wins() {
+        let names = vec!["Realtek Mic".to_string(), "DJI Mic Mini".into(), "USB Headset".into()];
+        assert_eq!(pick_device(&names, &["dji", "headset"]), Some(1));
+        assert_eq!(pick_device(&names, &["wireless", "HEADSET"]), Some(2));
+        assert_eq!(pick_device(&names, &["wireless"]), None);
+    }
+
+    #[test]
+    fn stream_is_lost_on_failure_or_stall() {
+        let health = StreamHealth::new();
+        health.beat();
+        let now = Instant::now();
+        assert!(!health.is_lost(now));
+        assert!(health.is_lost(now + STALL_TIMEOUT + Duration::from_secs(1)));
+        health.fail();
+        assert!(health.is_lost(now));
+    }
+
+    #[test]
+    fn fallback_is_outranked_by_earlier_preferences_only() {
+        let preferences = ["dji", "headset"];
+        assert!(Fallback::new("DJI Mic Mini", &preferences).is_none());
+
+        let headset = Fallback::new("USB Headset", &preferences).unwrap();
+        assert!(headset.is_outranked_by(&["Realtek Mic".into(), "DJI Mic Mini".into()]));
+        assert!(!headset.is_outranked_by(&["Realtek Mic".into(), "USB Headset".into()]));
+
+        // The system default is outranked by any preferred device
+        let default = Fallback::new("Realtek Mic", &preferences).unwrap();
+        assert!(default.is_outranked_by(&["USB Headset".into()]));
+        assert!(!default.is_outranked_by(&["Realtek Mic".into()]));
+    }
+
+    struct FakeStream {
+        name: String,
+        lost: Rc<Cell<bool>>,
+        outranked: Rc<Cell<bool>>,
+    }
+
+    impl SupervisedStream for FakeStream {
+        fn is_lost(&self, _: Instant) -> bool {
+            self.lost.get()
+        }
+        fn device_name(&self) -> &str {
+            &self.name
+        }
+        fn should_upgrade(&self) -> bool {
+            self.outranked.get()
+        }
+    }
+
+    fn fake(name: &str, lost: &Rc<Cell<bool>>) -> FakeStream {
+        FakeStream { name: name.into(), lost: lost.clone(), outranked: Rc::default() }
+    }
+
+    #[test]
+    fn lost_device_is_reopened_on_next_available() {
+        let lost = Rc::new(Cell::new(false));
+        let mut sup = CaptureSupervisor::new(Some(fake("DJI Mic", &lost)));
+        let now = Instant::now();
+        assert_eq!(sup.poll(now, || unreachable!()), None);
+
+        lost.set(true);
+        assert_eq!(sup.poll(now, || unreachable!()), Some(DeviceEvent::Lost("DJI Mic".into())));
+        assert_eq!(sup.device_name(), None);
+
+        let fresh = Rc::new(Cell::new(false));
+        let event = sup.poll(now, || Ok(fake("Realtek Mic", &fresh)));
+        assert_eq!(event, Some(DeviceEvent::Reconnected("Realtek Mic".into())));
+        assert_eq!(sup.device_name(), Some("Realtek Mic"));
+    }
+
+    #[test]
+    fn failed_reopen_retries_after_interval() {
+        let mut sup = CaptureSupervisor::<FakeStream>::new(None);
+        let now = Instant::now();
+        assert_eq!(sup.poll(now, || anyhow::bail!("no devices")), None);
+        // Not retried before the interval elapses
+        assert_eq!(sup.poll(now + RETRY_INTERVAL / 2, || unreachable!()), None);
+
+        let lost = Rc::new(Cell::new(false));
+        let event = sup.poll(now + RETRY_INTERVAL, || Ok(fake("USB Mic", &lost)));
+        assert_eq!(event, Some(DeviceEvent::Reconnected("USB Mic".into())));
+    }
+
+    #[test]
+    fn fallback_moves_back_to_preferred_device() {
+        let lost = Rc::new(Cell::new(false));
+        let outranked = Rc::new(Cell::new(false));
+        let realtek = FakeStream { name: "Realtek Mic".into(), lost: lost.clone(), outranked: outranked.clone() };
+        let mut sup = CaptureSupervisor::new(Some(realtek));
+        let now = Instant::now();
+
+        outranked.set(true);
+        // Preferences are only re-checked every interval
+        assert_eq!(sup.poll(now, || unreachable!()), None);
+        let later = now + PREFERENCE_CHECK_INTERVAL;
+        let event = sup.poll(later, || Ok(fake("DJI Mic", &lost)));
+        assert_eq!(event, Some(DeviceEvent::Reconnected("DJI Mic".into())));
+        assert_eq!(sup.device_name(), Some("DJI Mic"));
+    }
+}
@@ -0,0 +1,120 @@
+//! Small in-place radix-2 FFT for the spectral audio stages.
+//!
+//! Frames here are a few hundred samples, so a plain iterative
+//! Cooley–Tukey with precomputed twiddles is plenty.
+
+/// FFT plan for one power-of-two size.
+pub struct Fft {
+    n: usize,
+    /// `e^{-2πik/n}` for `k < n/2`, as (cos, sin).
+    twiddles: Vec<(f32, f32)>,
+    bit_reverse: Vec<usize>,
+}
+
+impl Fft {
+    /// Plan an FFT of `n` points; `n` must be a power of two.
+    pub fn new(n: usize) -> Self {
+        assert!(n.is_power_of_two(), "FFT size must be a power of two, got {n}");
+        let bits = n.trailing_zeros();
+        let twiddles = (0..n / 2)
+            .map(|k| {
+                let angle = -2.0 * std::f64::consts::PI * k as f64 / n as f64;
+                (angle.cos() as f32, angle.sin() as f32)
+            })
+            .collect();
+        let bit_reverse = (0..n)
+            .map(|i| if bits == 0 { 0 } else { i.reverse_bits() >> (usize::BITS - bits) })
+            .collect();
+        Self { n, twiddles, bit_reverse }
+    }
+
+    /// Number of points.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Forward transform of (`re`, `im`) in place.
+    pub fn forward(&self, re: &mut [f32], im: &mut [f32]) {
+        self.transform(re, im, false);
+    }
+
+    /// Inverse transform in place, scaled by `1/n` so it undoes [`forward`](Self::forward).
+    pub fn inverse(&self, re: &mut [f32], im: &mut [f32]) {
+        self.transform(re, im, true);
+        let scale = 1.0 / self.n as f32;
+        re.iter_mut().chain(im.iter_mut()).for_each(|v| *v *= scale);
+    }
+
+    fn transform(&self, re: &mut [f32], im: &mut [f32], inverse: bool) {
+        assert!(re.len() == self.n && im.len() == self.n, "buffer size must match FFT size");
+        for (i, &j) in self.bit_reverse.iter().enumerate() {
+            if i < j {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+        let mut size = 2;
+        while size <= self.n {
+            let half = size / 2;
+            let stride = self.n / size;
+            for start in (0..self.n).step_by(size) {
+                for k in 0..half {
+                    let (wr, wi) = self.twiddles[k * stride];
+                    let wi = if inverse { -wi } else { wi };
+                    let (a, b) = (start + k, start + k + half);
+                    let tr = re[b] * wr - im[b] * wi;
+                    let ti = re[b] * wi + im[b] * wr;
+                    re[b] = re[a] - tr;
+                    im[b] = im[a] - ti;
+                    re[a] += tr;
+                    im[a] += ti;
+                }
+            }
+            size *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impulse_has_flat_spectrum() {
+        let fft = Fft::new(8);
+        let mut re = vec![1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let mut im = vec![0.0; 8];
+        fft.forward(&mut re, &mut im);
+        assert!(re.iter().all(|&v| (v - 1.0).abs() < 1e-6));
+        assert!(im.iter().all(|&v| v.abs() < 1e-6));
+    }
+
+    #[test]
+    fn cosine_lands_in_its_bin() {
+        let n = 64;
+        let fft = Fft::new(n);
+        let mut re: Vec<f32> = (0..n)
+            .map(|i| (2.0 * std::f32::consts::PI * 5.0 * i as f32 / n as f32).cos())
+            .collect();
+        let mut im = vec![0.0; n];
+        fft.forward(&mut re, &mut im);
+        let mag = |k: usize| (re[k] * re[k] + im[k] * im[k]).sqrt();
+        assert!((mag(5) - n as f32 / 2.0).abs() < 1e-3);
+        assert!((mag(n - 5) - n as f32 / 2.0).abs() < 1e-3);
+        assert!(mag(4) < 1e-3 && mag(6) < 1e-3);
+    }
+
+    #[test]
+    fn inverse_round_trips() {
+        let fft = Fft::new(16);
+        let original: Vec<f32> = (0..16).map(|i| (i as f32 * 0.37).sin()).collect();
+        let mut re = original.clone();
+        let mut im = vec![0.0; 16];
+        fft.forward(&mut re, &mut im);
+        fft.inverse(&mut re, &mut im);
+        for (got, want) in re.iter().zip(&original) {
+            assert!((got - want).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Automatic gain control — normalizes speech level towards a target RMS.
+//!
+//! Tracks the signal's RMS envelope over 10 ms blocks (fast attack, slow
+//! release) and steers the gain so the envelope sits at the target. Gain is
+//! held through silence so the noise floor isn't pumped up between words,
+//! and a soft limiter keeps boosted peaks below full scale.
+
+use super::{db_to_gain, AudioFilter};
+
+const BLOCK_MS: u32 = 10;
+/// Envelope smoothing per block when the level rises / falls.
+const ENVELOPE_ATTACK: f32 = 0.3;
+const ENVELOPE_RELEASE: f32 = 0.02;
+/// Gain smoothing per block when turning down / up.
+const GAIN_DOWN: f32 = 0.3;
+const GAIN_UP: f32 = 0.05;
+/// Envelope below this (-60 dBFS) is treated as silence: gain is held.
+const SILENCE_RMS: f32 = 0.001;
+/// Soft limiter knee.
+const LIMIT: f32 = 0.9;
+
+pub struct Agc {
+    target_rms: f32,
+    max_gain: f32,
+    block_len: usize,
+    block_energy: f32,
+    block_count: usize,
+    envelope: f32,
+    gain: f32,
+}
+
+impl Agc {
+    /// `target_dbfs`: desired RMS level; `max_gain_db`: largest boost (and cut) applied.
+    pub fn new(target_dbfs: f32, max_gain_db: f32, sample_rate: u32) -> Self {
+        Self {
+            target_rms: db_to_gain(target_dbfs),
+            max_gain: db_to_gain(max_gain_db.max(0.0)),
+            block_len: (sample_rate * BLOCK_MS / 1000).max(1) as usize,
+            block_energy: 0.0,
+            block_count: 0,
+            envelope: 0.0,
+            gain: 1.0,
+        }
+    }
+
+    fn end_block(&mut self) {
+        let rms = (self.block_energy / self.block_count as f32).sqrt();
+        self.block_energy = 0.0;
+        self.block_count = 0;
+
+        let rate = if rms > self.envelope { ENVELOPE_ATTACK } else { ENVELOPE_RELEASE };
+        self.envelope += rate * (rms - self.envelope);
+        if self.envelope < SILENCE_RMS {
+            return;
+        }
+        let desired = (self.target_rms / self.envelope).clamp(1.0 / self.max_gain, self.max_gain);
+        let rate = if desired < self.gain { GAIN_DOWN } else { GAIN_UP };
+        self.gain += rate * (desired - self.gain);
+    }
+}
+
+fn soft_limit(x: f32) -> f32 {
+    let mag = x.abs();
+    if mag <= LIMIT {
+        return x;
+    }
+    let headroom = 1.0 - LIMIT;
+    x.signum() * (LIMIT + headroom * ((mag - LIMIT) / headroom).tanh())
+}
+
+impl AudioFilter for Agc {
+    fn process(&mut self, samples: &mut [f32]) {
+        for s in samples {
+            self.block_energy += *s * *s;
+            self.block_count += 1;
+            *s = soft_limit(*s * self.gain);
+            if self.block_count == self.block_len {
+                self.end_block();
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.block_energy = 0.0;
+        self.block_count = 0;
+        self.envelope = 0.0;
+        self.gain = 1.0;
+    }
+
+    fn name(&self) -> &str {
+        "agc"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::test_util::rms;
+
+    fn sine(amplitude: f32, secs: f32) -> Vec<f32> {
+        (0..(16_000.0 * secs) as usize)
+            .map(|i| amplitude * (i as f32 * 440.0 * std::f32::consts::TAU / 16_000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn quiet_speech_is_raised_to_target() {
+        let mut agc = Agc::new(-20.0, 30.0, 16_000);
+        let mut samples = sine(0.01, 4.0);
+        for chunk in samples.chunks_mut(1_600) {
+            agc.process(chunk);
+        }
+        let level = 20.0 * rms(&samples[48_000..]).log10();
+        assert!((level + 20.0).abs() < 1.5, "settled at {level:.1} dBFS");
+    }
+
+    #[test]
+    fn loud_input_is_turned_down_without_clipping() {
+        let mut agc = Agc::new(-20.0, 30.0, 16_000);
+        let mut samples = sine(0.99, 2.0);
+        agc.process(&mut samples);
+        assert!(samples.iter().all(|s| s.abs() < 1.0));
+        assert!(rms(&samples[16_000..]) < 0.15);
+    }
+
+    #[test]
+    fn silence_is_not_amplified() {
+        let mut agc = Agc::new(-20.0, 30.0, 16_000);
+        let mut samples = sine(0.0001, 2.0);
+        agc.process(&mut samples);
+        assert!(samples.iter().all(|s| s.abs() <= 0.0001));
+    }
+}
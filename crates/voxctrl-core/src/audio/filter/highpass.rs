@@ -0,0 +1,80 @@
+//! High-pass filter — removes DC offset and low-frequency rumble.
+//!
+//! Second-order Butterworth biquad (RBJ cookbook coefficients).
+
+use super::AudioFilter;
+
+pub struct HighPass {
+    b: [f32; 3],
+    a: [f32; 2],
+    /// Previous two inputs and outputs.
+    x: [f32; 2],
+    y: [f32; 2],
+}
+
+impl HighPass {
+    pub fn new(cutoff_hz: f32, sample_rate: u32) -> Self {
+        let w0 = 2.0 * std::f32::consts::PI * cutoff_hz / sample_rate as f32;
+        let alpha = w0.sin() / std::f32::consts::SQRT_2; // Q = 1/√2
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha;
+        Self {
+            b: [(1.0 + cos) / 2.0 / a0, -(1.0 + cos) / a0, (1.0 + cos) / 2.0 / a0],
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+}
+
+impl AudioFilter for HighPass {
+    fn process(&mut self, samples: &mut [f32]) {
+        for s in samples {
+            let x0 = *s;
+            let y0 = self.b[0] * x0 + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+                - self.a[0] * self.y[0]
+                - self.a[1] * self.y[1];
+            self.x = [x0, self.x[0]];
+            self.y = [y0, self.y[0]];
+            *s = y0;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.x = [0.0; 2];
+        self.y = [0.0; 2];
+    }
+
+    fn name(&self) -> &str {
+        "high-pass"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::test_util::rms;
+
+    #[test]
+    fn removes_dc_offset() {
+        let mut hp = HighPass::new(80.0, 16_000);
+        let mut samples = vec![0.3; 16_000];
+        hp.process(&mut samples);
+        assert!(rms(&samples[8_000..]) < 1e-3);
+    }
+
+    #[test]
+    fn passes_speech_band_in_chunks() {
+        let mut hp = HighPass::new(80.0, 16_000);
+        let input: Vec<f32> = (0..16_000)
+            .map(|i| 0.5 * (i as f32 * 1_000.0 * std::f32::consts::TAU / 16_000.0).sin())
+            .collect();
+        let mut output = input.clone();
+        // Processed in 10 ms chunks, as from capture
+        for chunk in output.chunks_mut(160) {
+            hp.process(chunk);
+        }
+        let ratio = rms(&output[8_000..]) / rms(&input[8_000..]);
+        assert!((ratio - 1.0).abs() < 0.01, "gain at 1 kHz: {ratio}");
+    }
+}
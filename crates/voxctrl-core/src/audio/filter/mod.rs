@@ -0,0 +1,146 @@
+//! Audio preprocessing — filters applied to captured audio before VAD and STT.
+//!
+//! Each enabled filter rewrites the mono stream in place, in turn. Filters are
+//! stateful and see consecutive chunks of one stream; the chain is built from
+//! `audio.filters` whenever the capture stream is (re)created.
+
+pub mod agc;
+pub mod highpass;
+pub mod noise;
+
+use crate::config::AudioFilterConfig;
+
+/// Trait for streaming audio preprocessing stages.
+pub trait AudioFilter: Send {
+    /// Filter the next chunk of mono samples in place.
+    fn process(&mut self, samples: &mut [f32]);
+    /// Forget stream history, e.g. at the start of a new recording.
+    fn reset(&mut self);
+    /// End of stream: return the output still held back by the filter's
+    /// latency. Filters without latency return nothing.
+    fn flush(&mut self) -> Vec<f32> {
+        Vec::new()
+    }
+    fn name(&self) -> &str;
+}
+
+/// Filters run in order over each chunk.
+#[derive(Default)]
+pub struct FilterChain {
+    filters: Vec<Box<dyn AudioFilter>>,
+}
+
+impl FilterChain {
+    pub fn new(filters: Vec<Box<dyn AudioFilter>>) -> Self {
+        Self { filters }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for filter in &mut self.filters {
+            filter.process(samples);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for filter in &mut self.filters {
+            filter.reset();
+        }
+    }
+
+    /// Flush each filter in turn; a filter's tail still passes through the
+    /// filters after it.
+    pub fn flush(&mut self) -> Vec<f32> {
+        let mut tail = Vec::new();
+        for filter in &mut self.filters {
+            filter.process(&mut tail);
+            tail.extend(filter.flush());
+        }
+        tail
+    }
+
+    /// Filter names in order, for logging.
+    pub fn names(&self) -> Vec<&str> {
+        self.filters.iter().map(|f| f.name()).collect()
+    }
+}
+
+/// Build the enabled filters for a stream at `sample_rate`: high-pass first,
+/// then noise suppression, then AGC (so it doesn't boost the noise floor
+/// before it is gated).
+pub fn create_filters(cfg: &AudioFilterConfig, sample_rate: u32) -> FilterChain {
+    let mut filters: Vec<Box<dyn AudioFilter>> = Vec::new();
+    if let Some(cutoff) = cfg.highpass_hz {
+        if cutoff > 0.0 && cutoff < sample_rate as f32 / 2.0 {
+            filters.push(Box::new(highpass::HighPass::new(cutoff, sample_rate)));
+        } else {
+            log::warn!("Ignoring audio.filters.highpass_hz={cutoff}: must be between 0 and {}Hz", sample_rate / 2);
+        }
+    }
+    if cfg.noise_suppression {
+        filters.push(Box::new(noise::NoiseSuppressor::new(cfg.noise_reduction_db, sample_rate)));
+    }
+    if cfg.agc {
+        filters.push(Box::new(agc::Agc::new(cfg.agc_target_dbfs, cfg.agc_max_gain_db, sample_rate)));
+    }
+    FilterChain::new(filters)
+}
+
+/// Convert decibels to a linear amplitude factor.
+pub(crate) fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_by_default() {
+        assert!(create_filters(&AudioFilterConfig::default(), 16_000).is_empty());
+    }
+
+    #[test]
+    fn filters_run_in_fixed_order() {
+        let cfg = AudioFilterConfig {
+            highpass_hz: Some(80.0),
+            noise_suppression: true,
+            agc: true,
+            ..Default::default()
+        };
+        let chain = create_filters(&cfg, 16_000);
+        assert_eq!(chain.names(), vec!["high-pass", "noise-suppression", "agc"]);
+    }
+
+    #[test]
+    fn flushed_tail_passes_through_later_filters() {
+        struct Delay(f32);
+        impl AudioFilter for Delay {
+            fn process(&mut self, samples: &mut [f32]) {
+                for s in samples {
+                    self.0 = std::mem::replace(s, self.0);
+                }
+            }
+            fn reset(&mut self) {}
+            fn flush(&mut self) -> Vec<f32> {
+                vec![std::mem::take(&mut self.0)]
+            }
+            fn name(&self) -> &str { "delay" }
+        }
+
+        let mut chain = FilterChain::new(vec![Box::new(Delay(0.0)), Box::new(Delay(0.0))]);
+        let mut samples = [1.0, 2.0, 3.0];
+        chain.process(&mut samples);
+        assert_eq!(samples, [0.0, 0.0, 1.0]);
+        assert_eq!(chain.flush(), vec![2.0, 3.0]);
+    }
+
+    #[test]
+    fn out_of_range_highpass_is_skipped() {
+        let cfg = AudioFilterConfig { highpass_hz: Some(9_000.0), ..Default::default() };
+        assert!(create_filters(&cfg, 16_000).is_empty());
+    }
+}
@@ -0,0 +1,432 @@
+//! Audio capture — cpal always-on input stream with optional VAD gating.
+
+pub mod bleed;
+pub mod device;
+pub mod fft;
+pub mod filter;
+pub mod resample;
+pub mod ring;
+pub mod source;
+#[cfg(test)]
+pub(crate) mod test_util;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use anyhow::{Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{Device, SampleRate, StreamConfig};
+
+use self::bleed::BleedGate;
+use self::filter::FilterChain;
+use self::source::{AudioSource, SourceHandle, SourceSink};
+use crate::config::Config;
+use crate::pipeline::SharedPipeline;
+use crate::vad::segmenter::UtteranceSegmenter;
+use crate::{AppStatus, SharedState};
+
+/// Find the first available input device in `preferences` order (falling
+/// back to the system default) and build a `StreamConfig` at the requested
+/// sample rate (mono if supported), falling back to the device default when
+/// the exact rate isn't supported.
+/// Returns `(device, config, actual_sample_rate, channels)`.
+fn resolve_device_and_config(preferences: &[&str], sample_rate: u32) -> Result<(Device, StreamConfig, u32, u16)> {
+    let host = cpal::default_host();
+    let mut devices: Vec<Device> = host
+        .input_devices()
+        .context("Failed to enumerate input devices")?
+        .collect();
+    let names: Vec<String> = devices
+        .iter()
+        .map(|d| d.name().unwrap_or_default())
+        .collect();
+    let device = match device::pick_device(&names, preferences) {
+        Some(i) => Some(devices.swap_remove(i)),
+        None => {
+            log::warn!("No input device matches {preferences:?}; using the system default");
+            host.default_input_device()
+        }
+    }
+    .context("No input audio device found")?;
+
+    let desired_rate = SampleRate(sample_rate);
+    let stream_config: StreamConfig = match device
+        .supported_input_configs()
+        .context("Cannot query device input configs")?
+        .find(|c| {
+            c.channels() >= 1
+                && c.min_sample_rate() <= desired_rate
+                && desired_rate <= c.max_sample_rate()
+        }) {
+        Some(range) => {
+            let mut sc: StreamConfig = range.with_sample_rate(desired_rate).into();
+            sc.channels = 1;
+            sc
+        }
+        None => {
+            let default = device
+                .default_input_config()
+                .context("No default input config")?;
+            let device_name = device.name().unwrap_or_else(|_| "<unknown>".into());
+            log::warn!(
+                "{}Hz not supported by '{}'; falling back to {}Hz, {}ch",
+                sample_rate,
+                device_name,
+                default.sample_rate().0,
+                default.channels(),
+            );
+            default.into()
+        }
+    };
+
+    let actual_rate = stream_config.sample_rate.0;
+    let channels = stream_config.channels;
+    Ok((device, stream_config, actual_rate, channels))
+}
+
+/// Downmix interleaved multi-channel audio to mono by averaging channels per frame.
+#[inline]
+pub(crate) fn downmix_to_mono(data: &[f32], channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return data.to_vec();
+    }
+    let ch = channels as usize;
+    data.chunks_exact(ch)
+        .map(|frame| frame.iter().sum::<f32>() / ch as f32)
+        .collect()
+}
+
+/// Seconds of audio the capture ring buffer holds before dropping samples.
+const CAPTURE_RING_SECS: usize = 2;
+
+/// Live capture streams; audio stops when this is dropped.
+pub struct CaptureStream {
+    source: SourceHandle,
+    _loopback: Option<cpal::Stream>,
+    device_name: String,
+    /// Hands-free listener thread, if any.
+    listener: Option<JoinHandle<()>>,
+    state: Arc<SharedState>,
+}
+
+impl CaptureStream {
+    /// Whether a finite source (e.g. a file) has delivered all its audio.
+    pub fn is_finished(&self) -> bool {
+        self.source.is_finished()
+    }
+
+    /// Block until a finite source has delivered everything and, in
+    /// hands-free mode, the listener has processed the last utterance.
+    /// Never returns for a live device.
+    pub fn wait(mut self) {
+        if matches!(self.source, SourceHandle::Device { .. }) {
+            loop {
+                std::thread::park();
+            }
+        }
+        self.source.wait();
+        if let Some(listener) = self.listener.take() {
+            if listener.join().is_err() {
+                log::error!("Hands-free listener panicked");
+            }
+        }
+    }
+}
+
+/// Reading side of the capture stream: the ring buffer plus the
+/// preprocessing its audio passes through before VAD and STT.
+pub struct Capture {
+    pub consumer: ring::Consumer,
+    pub filters: FilterChain,
+    /// Playback reference for speaker-bleed rejection (`audio.loopback_device`).
+    pub loopback: Option<Loopback>,
+}
+
+impl Capture {
+    pub fn new(consumer: ring::Consumer, filters: FilterChain) -> Self {
+        Self { consumer, filters, loopback: None }
+    }
+
+    /// Append newly captured mic audio to `out`, with speaker bleed gated
+    /// out and the filters applied.
+    pub fn read_into(&mut self, out: &mut Vec<f32>) {
+        let before = out.len();
+        self.consumer.pop_into(out);
+        // Drained even while the mic is idle, so the reference stays current
+        if let Some(loopback) = &mut self.loopback {
+            loopback.gate(&mut out[before..]);
+        }
+        self.filters.process(&mut out[before..]);
+    }
+
+    /// Append the audio the filters still hold back, when a recording stops.
+    pub fn flush_into(&mut self, out: &mut Vec<f32>) {
+        out.extend(self.filters.flush());
+    }
+
+    /// Drop buffered audio and stream history before a new recording.
+    pub fn reset(&mut self) {
+        self.consumer.clear();
+        self.filters.reset();
+        if let Some(loopback) = &mut self.loopback {
+            loopback.consumer.clear();
+            loopback.resampler.reset();
+            loopback.gate.reset();
+        }
+    }
+}
+
+/// Loopback capture of the output device, used as the bleed reference.
+pub struct Loopback {
+    pub consumer: ring::Consumer,
+    /// Rate of the loopback stream.
+    pub sample_rate: u32,
+    /// Rate of the mic audio being gated.
+    pub mic_rate: u32,
+    pub gate: BleedGate,
+    /// Converts the loopback stream to the mic rate across reads.
+    resampler: resample::Resampler,
+    scratch: Vec<f32>,
+    reference: Vec<f32>,
+}
+
+impl Loopback {
+    pub fn new(consumer: ring::Consumer, sample_rate: u32, mic_rate: u32, margin_db: f32) -> Self {
+        Self {
+            consumer,
+            sample_rate,
+            mic_rate,
+            gate: BleedGate::new(mic_rate, margin_db),
+            resampler: resample::Resampler::new(sample_rate, mic_rate),
+            scratch: Vec::new(),
+            reference: Vec::new(),
+        }
+    }
+
+    fn gate(&mut self, mic: &mut [f32]) {
+        self.scratch.clear();
+        self.consumer.pop_into(&mut self.scratch);
+        self.consumer.take_dropped();
+        self.reference.clear();
+        self.resampler.process(&self.scratch, &mut self.reference);
+        self.gate.process(mic, &self.reference);
+    }
+}
+
+/// Start the always-on capture stream from the source selected by
+/// `audio.source` — by default the first available device in
+/// `audio.device_pattern`, `audio.fallback_devices` order.
+pub fn start_capture(
+    state: Arc<SharedState>,
+    cfg: &Config,
+    pipeline: Arc<SharedPipeline>,
+) -> Result<CaptureStream> {
+    let source = source::create_source(&cfg.audio)?;
+    start_capture_from(state, cfg, pipeline, source)
+}
+
+/// Start capturing from `source`.
+///
+/// The source never locks: it pushes mono samples into a lock-free ring
+/// buffer, gated on the atomic status. In hands-free mode
+/// (`vad.hands_free`) every buffer goes to a listener thread that segments
+/// utterances with the configured VAD and drives `pipeline` itself;
+/// otherwise a recorder thread assembles `state.chunks` while the hotkey has
+/// recording toggled on. The source's actual rate, which may differ from
+/// `audio.sample_rate` when the device doesn't support it, is recorded in
+/// `state.sample_rate`. Filters from `audio.filters` run on the consumer
+/// threads, never in the realtime callback. With `audio.loopback_device` set,
+/// the playback is captured too and mic audio that only echoes it is gated.
+pub fn start_capture_from(
+    state: Arc<SharedState>,
+    cfg: &Config,
+    pipeline: Arc<SharedPipeline>,
+    source: Box<dyn AudioSource>,
+) -> Result<CaptureStream> {
+    let actual_rate = source.sample_rate();
+    let device_name = source.name().to_string();
+    log::info!("Audio source: {device_name}");
+    state.sample_rate.store(actual_rate, Ordering::Relaxed);
+    state.max_chunks.store(
+        actual_rate as usize * cfg.audio.max_recording_secs as usize,
+        Ordering::Relaxed,
+    );
+
+    let filters = filter::create_filters(&cfg.audio.filters, actual_rate);
+    if !filters.is_empty() {
+        log::info!("Audio filters: {}", filters.names().join(" → "));
+    }
+
+    let (producer, consumer) = ring::channel(actual_rate as usize * CAPTURE_RING_SECS);
+    let mut capture = Capture::new(consumer, filters);
+    let loopback_stream = match &cfg.audio.loopback_device {
+        Some(pattern) => match start_loopback(pattern) {
+            Ok((stream, consumer, rate)) => {
+                capture.loopback = Some(Loopback::new(consumer, rate, actual_rate, cfg.audio.bleed_margin_db));
+                Some(stream)
+            }
+            Err(e) => {
+                log::error!("Speaker-bleed rejection unavailable: {e:#}");
+                None
+            }
+        },
+        None => None,
+    };
+    let segmenter = if cfg.vad.hands_free { hands_free_segmenter(cfg, actual_rate) } else { None };
+    let (hands_free, listener) = match segmenter {
+        Some(segmenter) => {
+            *state.capture.lock().unwrap() = None;
+            let listener = crate::recording::spawn_hands_free(state.clone(), pipeline, segmenter, capture, actual_rate)
+                .context("Failed to spawn hands-free listener")?;
+            (true, Some(listener))
+        }
+        None => {
+            let liveness = capture.consumer.liveness();
+            *state.capture.lock().unwrap() = Some(capture);
+            crate::recording::spawn_recorder(state.clone(), liveness)
+                .context("Failed to spawn recorder thread")?;
+            (false, None)
+        }
+    };
+
+    let state_gate = Arc::clone(&state);
+    let sink = SourceSink::new(producer, move || {
+        hands_free || state_gate.status.load() == AppStatus::Recording
+    });
+    let source = source.start(sink)?;
+    Ok(CaptureStream { source, _loopback: loopback_stream, device_name, listener, state })
+}
+
+/// Open a capture of the playback device matching `pattern` (`"default"` for
+/// the default output): either a monitor source listed among the inputs
+/// (PulseAudio/PipeWire) or an output device, which WASAPI captures in
+/// loopback mode. Returns `(stream, consumer, sample_rate)`.
+fn start_loopback(pattern: &str) -> Result<(cpal::Stream, ring::Consumer, u32)> {
+    let host = cpal::default_host();
+    let pat = pattern.to_lowercase();
+    let matches = |d: &Device| d.name().map(|n| n.to_lowercase().contains(&pat)).unwrap_or(false);
+    let device = if pat == "default" {
+        host.default_output_device()
+    } else {
+        host.input_devices()
+            .ok()
+            .and_then(|mut devices| devices.find(&matches))
+            .or_else(|| host.output_devices().ok().and_then(|mut devices| devices.find(&matches)))
+    }
+    .with_context(|| format!("No playback device matching '{pattern}'"))?;
+
+    let config = device
+        .default_input_config()
+        .or_else(|_| device.default_output_config())
+        .context("No loopback stream config")?;
+    let rate = config.sample_rate().0;
+    let ch = config.channels().max(1) as usize;
+    log::info!(
+        "Loopback reference: {} ({}Hz, {}ch)",
+        device.name().unwrap_or_else(|_| "<unknown>".into()),
+        rate,
+        ch,
+    );
+
+    let (mut producer, consumer) = ring::channel(rate as usize * CAPTURE_RING_SECS);
+    let stream = device
+        .build_input_stream(
+            &config.into(),
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                if ch == 1 {
+                    producer.push(data);
+                } else {
+                    producer.push_iter(
+                        data.chunks_exact(ch).map(|frame| frame.iter().sum::<f32>() / ch as f32),
+                    );
+                }
+            },
+            |err| log::error!("Loopback capture error: {err}"),
+            None,
+        )
+        .context("Failed to build loopback stream")?;
+    stream.play().context("Failed to start loopback stream")?;
+    Ok((stream, consumer, rate))
+}
+
+/// Create the VAD segmenter for hands-free mode, or `None` (falling back to
+/// hotkey recording) if the VAD backend can't be created.
+fn hands_free_segmenter(cfg: &Config, sample_rate: u32) -> Option<UtteranceSegmenter> {
+    let model_dir = crate::vad::vad_model_dir(cfg);
+    let vad = match crate::vad::create_vad(&cfg.vad, model_dir.as_deref()) {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("Hands-free mode unavailable: {e}");
+            return None;
+        }
+    };
+    log::info!(
+        "Hands-free mode: vad={}, pre-roll={}ms, trailing silence={}ms",
+        vad.name(),
+        cfg.vad.pre_roll_ms,
+        cfg.vad.trailing_silence_ms,
+    );
+    Some(UtteranceSegmenter::new(vad, &cfg.vad, sample_rate, cfg.audio.chunk_duration_ms))
+}
+
+/// List all available audio input device names.
+pub fn list_input_devices() -> Vec<String> {
+    let host = cpal::default_host();
+    match host.input_devices() {
+        Ok(devices) => devices.filter_map(|d| d.name().ok()).collect(),
+        Err(e) => {
+            log::warn!("Failed to enumerate input devices: {e}");
+            vec![]
+        }
+    }
+}
+
+/// Start a test audio capture stream that sends RMS levels and raw samples.
+///
+/// `test_chunks` receives mono f32 samples when `recording` is true.
+/// Returns `(stream, actual_sample_rate)`.
+pub fn start_test_capture(
+    device_pattern: &str,
+    sample_rate: u32,
+    level_tx: std::sync::mpsc::Sender<f32>,
+    test_chunks: Arc<std::sync::Mutex<Vec<f32>>>,
+    recording: Arc<AtomicBool>,
+) -> Result<(cpal::Stream, u32)> {
+    let (device, stream_config, actual_rate, channels) =
+        resolve_device_and_config(&[device_pattern], sample_rate)?;
+
+    log::info!(
+        "Test capture: device={:?}, actual_rate={}Hz, channels={}",
+        device.name(), actual_rate, channels
+    );
+
+    let stream = device
+        .build_input_stream(
+            &stream_config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                // Compute RMS for level meter (use raw data, all channels)
+                if !data.is_empty() {
+                    let sum: f32 = data.iter().map(|s| s * s).sum();
+                    let rms = (sum / data.len() as f32).sqrt();
+                    let _ = level_tx.send(rms);
+                }
+                // Record mono samples if recording flag is set
+                if recording.load(Ordering::Relaxed) {
+                    let mut chunks = test_chunks.lock().unwrap();
+                    if channels <= 1 {
+                        chunks.extend_from_slice(data);
+                    } else {
+                        let mono = downmix_to_mono(data, channels);
+                        chunks.extend_from_slice(&mono);
+                    }
+                }
+            },
+            |err| log::error!("Test audio capture error: {err}"),
+            None,
+        )
+        .context("Failed to build test audio input stream")?;
+
+    stream.play().context("Failed to start test audio stream")?;
+    Ok((stream, actual_rate))
+}
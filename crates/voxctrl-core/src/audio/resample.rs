@@ -0,0 +1,268 @@
+//! Band-limited sample-rate conversion — the one path every STT backend's
+//! audio goes through on its way to 16 kHz.
+//!
+//! Kaiser-windowed sinc interpolation. The kernel is stretched to the lower of
+//! the two Nyquist frequencies, so downsampling filters out content the target
+//! rate can't represent instead of folding it back into the speech band.
+//!
+//! [`resample`] converts a whole buffer; [`Resampler`] converts a stream
+//! arriving in chunks, carrying the kernel's context across chunk edges.
+
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// Sample rate every STT backend consumes.
+pub const STT_SAMPLE_RATE: u32 = 16_000;
+
+/// Sinc zero crossings on each side of the kernel centre.
+const ZERO_CROSSINGS: usize = 24;
+/// Kernel table entries per zero crossing (linearly interpolated between).
+const OVERSAMPLE: usize = 512;
+/// Kaiser window shape — ~85 dB stopband attenuation.
+const KAISER_BETA: f64 = 8.6;
+/// Cutoff as a fraction of the lower Nyquist frequency, leaving room for the
+/// transition band below it.
+const CUTOFF: f64 = 0.9;
+
+/// Resample mono `samples` from `from_rate` to `to_rate`.
+pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || from_rate == 0 || to_rate == 0 || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = Ratio::new(from_rate, to_rate);
+    let out_len = ((samples.len() as u64 * to_rate as u64 + from_rate as u64 / 2) / from_rate as u64) as usize;
+    (0..out_len).map(|n| ratio.sample_at(samples, n as f64 * ratio.step)).collect()
+}
+
+/// Conversion constants shared by [`resample`] and [`Resampler`].
+#[derive(Debug, Clone, Copy)]
+struct Ratio {
+    /// Input samples per output sample.
+    step: f64,
+    cutoff: f64,
+    /// Kernel half-width in input samples.
+    half_width: f64,
+}
+
+impl Ratio {
+    fn new(from_rate: u32, to_rate: u32) -> Self {
+        let cutoff = CUTOFF * (to_rate as f64 / from_rate as f64).min(1.0);
+        Self {
+            step: from_rate as f64 / to_rate as f64,
+            cutoff,
+            half_width: ZERO_CROSSINGS as f64 / cutoff,
+        }
+    }
+
+    /// Interpolated value at input position `t`; samples outside `samples` count as silence.
+    fn sample_at(&self, samples: &[f32], t: f64) -> f32 {
+        let table = kernel_table();
+        let first = (t - self.half_width).ceil().max(0.0) as usize;
+        let last = ((t + self.half_width).floor() as usize).min(samples.len() - 1);
+        let acc: f64 = (first..=last)
+            .map(|i| samples[i] as f64 * kernel(table, (i as f64 - t).abs() * self.cutoff))
+            .sum();
+        (acc * self.cutoff) as f32
+    }
+}
+
+/// Streaming resampler for audio delivered in chunks.
+///
+/// Resampling each chunk on its own treats its edges as silence, leaving
+/// clicks at every boundary. This keeps the input the kernel still needs
+/// between calls instead, so the output matches [`resample`] on the whole
+/// stream, delayed by the kernel half-width.
+pub struct Resampler {
+    from_rate: u32,
+    to_rate: u32,
+    ratio: Ratio,
+    /// Input the kernel may still reach; `input[0]` is stream sample `consumed`.
+    input: Vec<f32>,
+    consumed: u64,
+    /// Output samples produced so far.
+    produced: u64,
+}
+
+impl Resampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        Self {
+            from_rate,
+            to_rate,
+            ratio: Ratio::new(from_rate.max(1), to_rate.max(1)),
+            input: Vec::new(),
+            consumed: 0,
+            produced: 0,
+        }
+    }
+
+    /// Append the conversion of `samples` to `out`. Output is produced once
+    /// the input covers the kernel around it.
+    pub fn process(&mut self, samples: &[f32], out: &mut Vec<f32>) {
+        if self.from_rate == self.to_rate || self.from_rate == 0 || self.to_rate == 0 {
+            out.extend_from_slice(samples);
+            return;
+        }
+        self.input.extend_from_slice(samples);
+        let available = self.input.len() as f64 - 1.0;
+        loop {
+            let t = self.produced as f64 * self.ratio.step - self.consumed as f64;
+            if self.input.is_empty() || t + self.ratio.half_width > available {
+                break;
+            }
+            out.push(self.ratio.sample_at(&self.input, t));
+            self.produced += 1;
+        }
+
+        let t = self.produced as f64 * self.ratio.step - self.consumed as f64;
+        let unused = ((t - self.ratio.half_width).ceil().max(0.0) as usize).min(self.input.len());
+        self.input.drain(..unused);
+        self.consumed += unused as u64;
+    }
+
+    /// Start a new stream.
+    pub fn reset(&mut self) {
+        self.input.clear();
+        self.consumed = 0;
+        self.produced = 0;
+    }
+}
+
+/// `samples` at [`STT_SAMPLE_RATE`], borrowing when no conversion is needed.
+pub fn to_16k(samples: &[f32], sample_rate: u32) -> Cow<'_, [f32]> {
+    if sample_rate == STT_SAMPLE_RATE {
+        Cow::Borrowed(samples)
+    } else {
+        Cow::Owned(resample(samples, sample_rate, STT_SAMPLE_RATE))
+    }
+}
+
+/// Windowed sinc at `x` zero crossings from the centre (`x >= 0`).
+fn kernel(table: &[f64], x: f64) -> f64 {
+    let pos = x * OVERSAMPLE as f64;
+    let idx = pos as usize;
+    if idx + 1 >= table.len() {
+        return 0.0;
+    }
+    let frac = pos - idx as f64;
+    table[idx] + (table[idx + 1] - table[idx]) * frac
+}
+
+fn kernel_table() -> &'static [f64] {
+    static TABLE: OnceLock<Vec<f64>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let len = ZERO_CROSSINGS * OVERSAMPLE;
+        let norm = bessel_i0(KAISER_BETA);
+        (0..=len)
+            .map(|j| {
+                let x = j as f64 / OVERSAMPLE as f64;
+                let sinc = if j == 0 {
+                    1.0
+                } else {
+                    let px = std::f64::consts::PI * x;
+                    px.sin() / px
+                };
+                let r = j as f64 / len as f64;
+                let window = bessel_i0(KAISER_BETA * (1.0 - r * r).sqrt()) / norm;
+                sinc * window
+            })
+            .collect()
+    })
+}
+
+/// Zeroth-order modified Bessel function of the first kind (power series).
+fn bessel_i0(x: f64) -> f64 {
+    let q = x * x / 4.0;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..64 {
+        term *= q / (k * k) as f64;
+        sum += term;
+        if term < sum * 1e-15 {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, rate: u32, secs: f32) -> Vec<f32> {
+        let n = (rate as f32 * secs) as usize;
+        (0..n)
+            .map(|i| 0.5 * (i as f32 * freq * std::f32::consts::TAU / rate as f32).sin())
+            .collect()
+    }
+
+    /// RMS over the middle half, away from the zero-padded edges.
+    fn mid_rms(samples: &[f32]) -> f32 {
+        let mid = &samples[samples.len() / 4..samples.len() * 3 / 4];
+        (mid.iter().map(|s| s * s).sum::<f32>() / mid.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn identity_and_empty() {
+        assert_eq!(resample(&[0.1, 0.2, 0.3], 16_000, 16_000), vec![0.1, 0.2, 0.3]);
+        assert!(resample(&[], 44_100, 16_000).is_empty());
+        assert!(matches!(to_16k(&[0.1], 16_000), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn output_length_follows_rate_ratio() {
+        assert_eq!(resample(&[0.0; 48_000], 48_000, 16_000).len(), 16_000);
+        assert_eq!(resample(&[0.0; 44_100], 44_100, 16_000).len(), 16_000);
+        assert_eq!(resample(&[0.0; 8_000], 8_000, 16_000).len(), 16_000);
+    }
+
+    #[test]
+    fn passband_tone_is_preserved() {
+        for from in [8_000, 22_050, 44_100, 48_000] {
+            let out = resample(&sine(1_000.0, from, 1.0), from, 16_000);
+            let expected = sine(1_000.0, 16_000, 1.0);
+            let (a, b) = (out.len() / 4, out.len() * 3 / 4);
+            let max_err = out[a..b]
+                .iter()
+                .zip(&expected[a..b])
+                .map(|(x, y)| (x - y).abs())
+                .fold(0.0f32, f32::max);
+            assert!(max_err < 1e-3, "{from} Hz: max error {max_err}");
+        }
+    }
+
+    #[test]
+    fn streaming_matches_whole_buffer() {
+        let input = sine(1_000.0, 44_100, 0.5);
+        let whole = resample(&input, 44_100, 16_000);
+        let mut resampler = Resampler::new(44_100, 16_000);
+        let mut streamed = Vec::new();
+        for chunk in input.chunks(441) {
+            resampler.process(chunk, &mut streamed);
+        }
+        // Only the kernel half-width at the end is still pending
+        assert!(whole.len() - streamed.len() < 40, "{} of {}", streamed.len(), whole.len());
+        let max_err = streamed
+            .iter()
+            .zip(&whole)
+            .map(|(x, y)| (x - y).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_err < 1e-5, "max error {max_err}");
+    }
+
+    #[test]
+    fn streaming_passes_through_at_equal_rates() {
+        let mut resampler = Resampler::new(16_000, 16_000);
+        let mut out = Vec::new();
+        resampler.process(&[0.1, 0.2], &mut out);
+        assert_eq!(out, vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn tone_above_target_nyquist_is_rejected() {
+        // 10 kHz can't exist at 16 kHz; naive decimation would alias it to 6 kHz
+        let input = sine(10_000.0, 48_000, 1.0);
+        let out = resample(&input, 48_000, 16_000);
+        let ratio = mid_rms(&out) / mid_rms(&input);
+        assert!(ratio < 1e-3, "alias leaked through: {ratio}");
+    }
+}
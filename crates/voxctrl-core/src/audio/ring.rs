@@ -0,0 +1,221 @@
+//! Lock-free single-producer/single-consumer ring buffer of f32 samples.
+//!
+//! Hands audio from the real-time capture callback to a worker thread
+//! without locking or allocating. Samples are stored as `f32` bits in
+//! atomics, so no `unsafe` is needed. When the consumer falls behind, new
+//! samples are dropped (and counted) instead of overwriting unread ones.
+
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+
+struct Shared {
+    slots: Box<[AtomicU32]>,
+    mask: usize,
+    /// Total samples written; only the producer stores it.
+    head: AtomicUsize,
+    /// Total samples read; only the consumer stores it.
+    tail: AtomicUsize,
+    /// Samples dropped because the buffer was full.
+    dropped: AtomicUsize,
+}
+
+/// Create a ring holding at least `capacity` samples (rounded up to a power of two).
+pub fn channel(capacity: usize) -> (Producer, Consumer) {
+    let capacity = capacity.max(1).next_power_of_two();
+    let shared = Arc::new(Shared {
+        slots: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+        mask: capacity - 1,
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+        dropped: AtomicUsize::new(0),
+    });
+    (Producer { shared: shared.clone() }, Consumer { shared })
+}
+
+/// Writing end — owned by the audio callback.
+pub struct Producer {
+    shared: Arc<Shared>,
+}
+
+impl Producer {
+    /// Append `samples`, returning how many fit. The rest are dropped.
+    pub fn push(&mut self, samples: &[f32]) -> usize {
+        self.push_iter(samples.iter().copied())
+    }
+
+    /// Append samples from an iterator (e.g. a downmix) without buffering it first.
+    pub fn push_iter(&mut self, samples: impl IntoIterator<Item = f32>) -> usize {
+        let s = &*self.shared;
+        let head = s.head.load(Ordering::Relaxed);
+        let free = s.slots.len() - head.wrapping_sub(s.tail.load(Ordering::Acquire));
+        let mut written = 0;
+        let mut dropped = 0;
+        for sample in samples {
+            if written < free {
+                s.slots[head.wrapping_add(written) & s.mask].store(sample.to_bits(), Ordering::Relaxed);
+                written += 1;
+            } else {
+                dropped += 1;
+            }
+        }
+        s.head.store(head.wrapping_add(written), Ordering::Release);
+        if dropped > 0 {
+            s.dropped.fetch_add(dropped, Ordering::Relaxed);
+        }
+        written
+    }
+
+    /// Room left before samples start being dropped.
+    pub fn free(&self) -> usize {
+        let s = &*self.shared;
+        s.slots.len() - s.head.load(Ordering::Relaxed).wrapping_sub(s.tail.load(Ordering::Acquire))
+    }
+
+    /// Whether the consumer has been dropped, i.e. nobody will read further samples.
+    pub fn is_abandoned(&self) -> bool {
+        Arc::strong_count(&self.shared) < 2
+    }
+}
+
+/// Reading end — owned by the thread that assembles utterances.
+pub struct Consumer {
+    shared: Arc<Shared>,
+}
+
+impl Consumer {
+    /// Move every available sample onto the end of `out`, returning the count.
+    pub fn pop_into(&mut self, out: &mut Vec<f32>) -> usize {
+        let s = &*self.shared;
+        let tail = s.tail.load(Ordering::Relaxed);
+        let available = s.head.load(Ordering::Acquire).wrapping_sub(tail);
+        out.extend((0..available).map(|i| {
+            f32::from_bits(s.slots[tail.wrapping_add(i) & s.mask].load(Ordering::Relaxed))
+        }));
+        s.tail.store(tail.wrapping_add(available), Ordering::Release);
+        available
+    }
+
+    /// Discard every available sample.
+    pub fn clear(&mut self) {
+        let head = self.shared.head.load(Ordering::Acquire);
+        self.shared.tail.store(head, Ordering::Release);
+    }
+
+    /// Samples dropped on overflow since the last call.
+    pub fn take_dropped(&self) -> usize {
+        self.shared.dropped.swap(0, Ordering::Relaxed)
+    }
+
+    /// Whether the producer has been dropped, i.e. no more samples will arrive.
+    pub fn is_abandoned(&self) -> bool {
+        Arc::strong_count(&self.shared) < 2
+    }
+
+    /// Handle for checking, from another thread, whether both ends are alive.
+    pub fn liveness(&self) -> Liveness {
+        Liveness(Arc::downgrade(&self.shared))
+    }
+}
+
+/// Observes a ring without keeping it alive.
+pub struct Liveness(Weak<Shared>);
+
+impl Liveness {
+    /// `true` while both the producer and the consumer exist.
+    pub fn is_connected(&self) -> bool {
+        self.0.strong_count() == 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_come_out_in_order_across_wraparound() {
+        let (mut tx, mut rx) = channel(4);
+        let mut out = Vec::new();
+        for round in 0..5 {
+            let base = round as f32 * 3.0;
+            assert_eq!(tx.push(&[base, base + 1.0, base + 2.0]), 3);
+            assert_eq!(rx.pop_into(&mut out), 3);
+        }
+        let expected: Vec<f32> = (0..15).map(|i| i as f32).collect();
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn overflow_drops_and_counts_new_samples() {
+        let (mut tx, mut rx) = channel(4);
+        assert_eq!(tx.push(&[1.0, 2.0, 3.0]), 3);
+        assert_eq!(tx.push_iter([4.0, 5.0, 6.0]), 1);
+        assert_eq!(rx.take_dropped(), 2);
+        assert_eq!(rx.take_dropped(), 0);
+
+        let mut out = Vec::new();
+        rx.pop_into(&mut out);
+        assert_eq!(out, vec![1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn clear_discards_pending_samples() {
+        let (mut tx, mut rx) = channel(8);
+        tx.push(&[1.0, 2.0]);
+        rx.clear();
+        tx.push(&[3.0]);
+        let mut out = Vec::new();
+        rx.pop_into(&mut out);
+        assert_eq!(out, vec![3.0]);
+    }
+
+    #[test]
+    fn free_tracks_unread_samples() {
+        let (mut tx, mut rx) = channel(8);
+        tx.push(&[1.0; 5]);
+        assert_eq!(tx.free(), 3);
+        rx.pop_into(&mut Vec::new());
+        assert_eq!(tx.free(), 8);
+        drop(rx);
+        assert!(tx.is_abandoned());
+    }
+
+    #[test]
+    fn dropping_producer_abandons_ring() {
+        let (tx, rx) = channel(8);
+        let liveness = rx.liveness();
+        assert!(!rx.is_abandoned());
+        assert!(liveness.is_connected());
+        drop(tx);
+        assert!(rx.is_abandoned());
+        assert!(!liveness.is_connected());
+    }
+
+    #[test]
+    fn concurrent_producer_and_consumer() {
+        const TOTAL: usize = 200_000;
+        let (mut tx, mut rx) = channel(1024);
+        let producer = std::thread::spawn(move || {
+            let mut next = 0usize;
+            while next < TOTAL {
+                let end = (next + 97).min(TOTAL);
+                let batch: Vec<f32> = (next..end).map(|i| i as f32).collect();
+                let written = tx.push(&batch);
+                // Samples that didn't fit are retried on the next pass
+                next += written;
+                if written < batch.len() {
+                    std::thread::yield_now();
+                }
+            }
+        });
+
+        let mut out = Vec::with_capacity(TOTAL);
+        while out.len() < TOTAL {
+            if rx.pop_into(&mut out) == 0 {
+                std::thread::yield_now();
+            }
+        }
+        producer.join().unwrap();
+
+        assert!(out.iter().enumerate().all(|(i, &v)| v == i as f32));
+    }
+}
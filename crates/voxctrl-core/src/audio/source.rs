@@ -0,0 +1,585 @@
+//! Audio sources — where captured audio comes from.
+//!
+//! A source delivers mono f32 samples into the capture ring buffer; the rest
+//! of capture (filters, bleed rejection, VAD, recording, STT) doesn't care
+//! which one is in use. Besides the live cpal device, audio can be read from
+//! a WAV/FLAC file, raw PCM on stdin or a named FIFO, so the whole pipeline
+//! can run headless on recorded audio.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{Device, StreamConfig};
+
+use super::device::{Fallback, StreamHealth};
+use super::ring;
+use crate::config::AudioConfig;
+
+/// Samples a raw PCM reader decodes at a time.
+const READ_CHUNK: usize = 1024;
+/// How long a reader waits for the consumer to make room.
+const BACKPRESSURE_WAIT: Duration = Duration::from_millis(5);
+/// Reads queued between a raw PCM input and its reader thread.
+const READ_QUEUE: usize = 8;
+/// How often a raw PCM reader waiting for input checks whether to stop.
+const READ_POLL: Duration = Duration::from_millis(50);
+
+/// Trait for audio inputs.
+pub trait AudioSource {
+    fn name(&self) -> &str;
+    /// Rate of the delivered samples.
+    fn sample_rate(&self) -> u32;
+    /// Start delivering audio into `sink`. Audio flows until the returned
+    /// handle is dropped or the source runs out; the sink is dropped then,
+    /// so consumers see the end of the stream.
+    fn start(self: Box<Self>, sink: SourceSink) -> Result<SourceHandle>;
+}
+
+/// Create the source selected by `audio.source`: `"device"` (default),
+/// `"file"`, `"stdin"` or `"fifo"`.
+pub fn create_source(cfg: &AudioConfig) -> Result<Box<dyn AudioSource>> {
+    let path = || {
+        cfg.source_path
+            .clone()
+            .with_context(|| format!("audio.source {:?} needs audio.source_path", cfg.source))
+    };
+    match cfg.source.as_str() {
+        "device" => Ok(Box::new(CpalSource::open(&cfg.device_preferences(), cfg.sample_rate)?)),
+        "file" => Ok(Box::new(FileSource::open(&path()?)?)),
+        "stdin" => Ok(Box::new(RawPcmSource::stdin(cfg.sample_rate))),
+        "fifo" => Ok(Box::new(RawPcmSource::fifo(path()?, cfg.sample_rate))),
+        other => anyhow::bail!("Unknown audio source: {other}"),
+    }
+}
+
+/// Where a source delivers its audio: the capture ring buffer, behind a gate
+/// that is closed while hotkey capture isn't recording.
+pub struct SourceSink {
+    producer: ring::Producer,
+    gate: Box<dyn Fn() -> bool + Send>,
+}
+
+impl SourceSink {
+    pub fn new(producer: ring::Producer, gate: impl Fn() -> bool + Send + 'static) -> Self {
+        Self { producer, gate: Box::new(gate) }
+    }
+
+    /// Deliver from a real-time callback: never blocks, and samples that
+    /// don't fit are dropped.
+    pub fn push(&mut self, samples: impl IntoIterator<Item = f32>) {
+        if (self.gate)() {
+            self.producer.push_iter(samples);
+        }
+    }
+
+    /// Deliver from a reader thread, waiting for room instead of dropping.
+    /// Returns `false` once `stop` is set or the consumer is gone.
+    pub fn push_blocking(&mut self, samples: &[f32], stop: &AtomicBool) -> bool {
+        if !(self.gate)() {
+            return self.is_running(stop);
+        }
+        self.push_open(samples, stop)
+    }
+
+    /// Like [`push_blocking`](Self::push_blocking), but also waits while the
+    /// gate is closed instead of discarding — recorded audio pauses between
+    /// recordings.
+    pub fn push_paused(&mut self, samples: &[f32], stop: &AtomicBool) -> bool {
+        for chunk in samples.chunks(READ_CHUNK) {
+            while !(self.gate)() {
+                if !self.is_running(stop) {
+                    return false;
+                }
+                std::thread::sleep(BACKPRESSURE_WAIT);
+            }
+            if !self.push_open(chunk, stop) {
+                return false;
+            }
+        }
+        self.is_running(stop)
+    }
+
+    fn is_running(&self, stop: &AtomicBool) -> bool {
+        !stop.load(Ordering::Relaxed) && !self.producer.is_abandoned()
+    }
+
+    /// Push all of `samples`, ignoring the gate.
+    fn push_open(&mut self, samples: &[f32], stop: &AtomicBool) -> bool {
+        let mut rest = samples;
+        while !rest.is_empty() {
+            let free = self.producer.free();
+            if free == 0 {
+                if !self.is_running(stop) {
+                    return false;
+                }
+                std::thread::sleep(BACKPRESSURE_WAIT);
+                continue;
+            }
+            let n = free.min(rest.len());
+            self.producer.push(&rest[..n]);
+            rest = &rest[n..];
+        }
+        self.is_running(stop)
+    }
+}
+
+/// Keeps a started source running.
+pub enum SourceHandle {
+    /// Live device stream; its health is updated from the callbacks.
+    /// `fallback` is set when the device isn't the first preference.
+    Device { _stream: cpal::Stream, health: Arc<StreamHealth>, fallback: Option<Fallback> },
+    /// Thread reading a file, stdin or a FIFO.
+    Reader(ReaderHandle),
+}
+
+impl SourceHandle {
+    /// Whether the device failed or stalled. Readers are never lost.
+    pub fn is_lost(&self, now: Instant) -> bool {
+        match self {
+            Self::Device { health, .. } => health.is_lost(now),
+            Self::Reader(_) => false,
+        }
+    }
+
+    /// Whether a fallback device is now outranked by a more preferred
+    /// device that is present. Enumerates the input devices.
+    pub fn is_outranked(&self) -> bool {
+        match self {
+            Self::Device { fallback: Some(fallback), .. } => fallback.is_outranked_by(&super::list_input_devices()),
+            _ => false,
+        }
+    }
+
+    /// Whether a finite source has delivered everything.
+    pub fn is_finished(&self) -> bool {
+        match self {
+            Self::Device { .. } => false,
+            Self::Reader(r) => r.thread.as_ref().is_none_or(|t| t.is_finished()),
+        }
+    }
+
+    /// Block until a finite source has delivered everything. Returns at once
+    /// for devices.
+    pub fn wait(&mut self) {
+        if let Self::Reader(r) = self {
+            if let Some(thread) = r.thread.take() {
+                if thread.join().is_err() {
+                    log::error!("Audio reader thread panicked");
+                }
+            }
+        }
+    }
+}
+
+/// Reader thread of a file, stdin or FIFO source; asks it to stop on drop.
+pub struct ReaderHandle {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for ReaderHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn spawn_reader(
+    name: &str,
+    sink: SourceSink,
+    read: impl FnOnce(SourceSink, &AtomicBool) + Send + 'static,
+) -> Result<SourceHandle> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_thread = stop.clone();
+    let thread = std::thread::Builder::new()
+        .name(format!("audio-{name}"))
+        .spawn(move || read(sink, &stop_thread))
+        .context("Failed to spawn audio reader thread")?;
+    Ok(SourceHandle::Reader(ReaderHandle { stop, thread: Some(thread) }))
+}
+
+// ── cpal device ────────────────────────────────────────────────────────────
+
+/// Live input device, opened through cpal.
+pub struct CpalSource {
+    device: Device,
+    config: StreamConfig,
+    name: String,
+    sample_rate: u32,
+    channels: u16,
+    fallback: Option<Fallback>,
+}
+
+impl CpalSource {
+    /// Resolve the first available device in `preferences` order at (or
+    /// near) `sample_rate`.
+    pub fn open(preferences: &[&str], sample_rate: u32) -> Result<Self> {
+        let (device, config, sample_rate, channels) = super::resolve_device_and_config(preferences, sample_rate)?;
+        let name = device.name().unwrap_or_else(|_| "<unknown>".into());
+        let fallback = Fallback::new(&name, preferences);
+        Ok(Self { device, config, name, sample_rate, channels, fallback })
+    }
+}
+
+impl AudioSource for CpalSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn start(self: Box<Self>, mut sink: SourceSink) -> Result<SourceHandle> {
+        log::info!(
+            "Stream: {}Hz, {}ch{}",
+            self.sample_rate,
+            self.channels,
+            if self.channels > 1 { " (downmixing to mono)" } else { "" },
+        );
+        let health = Arc::new(StreamHealth::new());
+        let (health_cb, health_err) = (Arc::clone(&health), Arc::clone(&health));
+        let ch = self.channels.max(1) as usize;
+        let stream = self
+            .device
+            .build_input_stream(
+                &self.config,
+                move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                    health_cb.beat();
+                    if ch == 1 {
+                        sink.push(data.iter().copied());
+                    } else {
+                        sink.push(data.chunks_exact(ch).map(|frame| frame.iter().sum::<f32>() / ch as f32));
+                    }
+                },
+                move |err| {
+                    log::error!("Audio capture error: {err}");
+                    if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+                        health_err.fail();
+                    }
+                },
+                None,
+            )
+            .context("Failed to build audio input stream")?;
+        stream.play().context("Failed to start audio stream")?;
+        Ok(SourceHandle::Device { _stream: stream, health, fallback: self.fallback })
+    }
+}
+
+// ── WAV / FLAC file ────────────────────────────────────────────────────────
+
+/// Decoded audio file, delivered as fast as capture consumes it. Between
+/// hotkey recordings the file pauses rather than playing on unheard.
+pub struct FileSource {
+    name: String,
+    samples: Vec<f32>,
+    sample_rate: u32,
+}
+
+impl FileSource {
+    /// Decode a WAV or FLAC file (by extension) to mono.
+    pub fn open(path: &Path) -> Result<Self> {
+        let is_flac = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("flac"));
+        let (samples, sample_rate) = if is_flac {
+            crate::history::flac::read(path)?
+        } else {
+            crate::stt::load_wav_pcm(path).with_context(|| format!("read {}", path.display()))?
+        };
+        let name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+        log::info!(
+            "Audio file: {name} ({:.1}s at {sample_rate}Hz)",
+            samples.len() as f64 / sample_rate.max(1) as f64,
+        );
+        Ok(Self { name, samples, sample_rate })
+    }
+}
+
+impl AudioSource for FileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn start(self: Box<Self>, sink: SourceSink) -> Result<SourceHandle> {
+        let samples = self.samples;
+        spawn_reader(&self.name, sink, move |mut sink, stop| {
+            sink.push_paused(&samples, stop);
+            log::info!("Audio file finished");
+        })
+    }
+}
+
+// ── Raw PCM on stdin / FIFO ────────────────────────────────────────────────
+
+type OpenReader = Box<dyn FnMut() -> std::io::Result<Box<dyn Read + Send>> + Send>;
+
+/// Headerless 16-bit little-endian mono PCM, as produced by e.g.
+/// `ffmpeg -f s16le -ac 1` or `arecord -f S16_LE -c 1`.
+///
+/// A live input like a mic: audio arriving between hotkey recordings is
+/// discarded.
+pub struct RawPcmSource {
+    name: String,
+    sample_rate: u32,
+    open: OpenReader,
+    /// Reopen after end of file — a FIFO gets a new writer, stdin doesn't.
+    reopen: bool,
+}
+
+impl RawPcmSource {
+    pub fn stdin(sample_rate: u32) -> Self {
+        Self::new("stdin", sample_rate, false, || Ok(Box::new(std::io::stdin())))
+    }
+
+    /// Read from the FIFO at `path`, reopening it whenever a writer finishes.
+    pub fn fifo(path: PathBuf, sample_rate: u32) -> Self {
+        let name = path.display().to_string();
+        Self::new(&name, sample_rate, true, move || {
+            Ok(Box::new(std::fs::File::open(&path)?))
+        })
+    }
+
+    pub fn new(
+        name: &str,
+        sample_rate: u32,
+        reopen: bool,
+        open: impl FnMut() -> std::io::Result<Box<dyn Read + Send>> + Send + 'static,
+    ) -> Self {
+        Self { name: name.into(), sample_rate, open: Box::new(open), reopen }
+    }
+}
+
+impl AudioSource for RawPcmSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Blocking opens and reads run on a detached thread: a FIFO waiting for
+    /// a writer or a quiet stdin can't be interrupted, so the reader thread
+    /// only waits on their queue and notices `stop` in time.
+    fn start(self: Box<Self>, sink: SourceSink) -> Result<SourceHandle> {
+        let Self { name, open, reopen, .. } = *self;
+        let (tx, rx) = mpsc::sync_channel(READ_QUEUE);
+        let label = name.clone();
+        std::thread::Builder::new()
+            .name(format!("audio-{name}-io"))
+            .spawn(move || read_input(open, reopen, &label, tx))
+            .context("Failed to spawn audio input thread")?;
+        spawn_reader(&name, sink, move |mut sink, stop| decode_s16le(&rx, &mut sink, stop))
+    }
+}
+
+/// Read raw bytes from `open`'s readers into `tx` until end of input, or
+/// until the reader thread has gone.
+fn read_input(mut open: OpenReader, reopen: bool, name: &str, tx: SyncSender<Vec<u8>>) {
+    let mut bytes = vec![0u8; READ_CHUNK * 2];
+    loop {
+        // A FIFO blocks here until a writer connects
+        let mut reader = match open() {
+            Ok(r) => r,
+            Err(e) => {
+                log::error!("Failed to open audio input {name}: {e}");
+                return;
+            }
+        };
+        loop {
+            match reader.read(&mut bytes) {
+                Ok(0) => break,
+                Ok(n) => {
+                    if tx.send(bytes[..n].to_vec()).is_err() {
+                        return;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    log::error!("Audio input read failed: {e}");
+                    break;
+                }
+            }
+        }
+        if !reopen {
+            log::info!("Audio input {name} finished");
+            return;
+        }
+    }
+}
+
+/// Decode s16le arriving on `rx` into `sink` until the input ends or
+/// delivery is stopped.
+fn decode_s16le(rx: &Receiver<Vec<u8>>, sink: &mut SourceSink, stop: &AtomicBool) {
+    // A sample split across reads leaves its first byte here
+    let mut carry = None;
+    let mut samples = Vec::with_capacity(READ_CHUNK);
+    loop {
+        let bytes = match rx.recv_timeout(READ_POLL) {
+            Ok(bytes) => bytes,
+            Err(RecvTimeoutError::Timeout) if !sink.is_running(stop) => return,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        samples.clear();
+        let mut data = &bytes[..];
+        if let Some(low) = carry.take() {
+            samples.push(i16::from_le_bytes([low, data[0]]) as f32 / 32768.0);
+            data = &data[1..];
+        }
+        let mut pairs = data.chunks_exact(2);
+        samples.extend(pairs.by_ref().map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0));
+        carry = pairs.remainder().first().copied();
+        if !sink.push_blocking(&samples, stop) {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Start `source` with an always-open gate and collect everything it delivers.
+    fn drain(source: Box<dyn AudioSource>, capacity: usize) -> Vec<f32> {
+        let (producer, mut consumer) = ring::channel(capacity);
+        let mut handle = source.start(SourceSink::new(producer, || true)).unwrap();
+        let mut out = Vec::new();
+        while !consumer.is_abandoned() {
+            consumer.pop_into(&mut out);
+            std::thread::yield_now();
+        }
+        consumer.pop_into(&mut out);
+        handle.wait();
+        assert!(handle.is_finished());
+        assert_eq!(consumer.take_dropped(), 0);
+        out
+    }
+
+    /// Hands out its bytes a few at a time, splitting samples across reads.
+    struct Trickle(Vec<u8>, usize);
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = 3.min(buf.len()).min(self.0.len() - self.1);
+            buf[..n].copy_from_slice(&self.0[self.1..self.1 + n]);
+            self.1 += n;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn raw_pcm_decodes_s16le_split_across_reads() {
+        let values: Vec<i16> = vec![0, 16384, -16384, 32767, -32768];
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let mut bytes = Some(bytes);
+        let source = RawPcmSource::new("test", 16_000, false, move || {
+            Ok(Box::new(Trickle(bytes.take().unwrap_or_default(), 0)))
+        });
+        let expected: Vec<f32> = values.iter().map(|&v| v as f32 / 32768.0).collect();
+        assert_eq!(drain(Box::new(source), 64), expected);
+    }
+
+    #[test]
+    fn file_source_waits_for_consumer_instead_of_dropping() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("speech.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8_000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        let samples: Vec<f32> = (0..5_000).map(|i| (i % 100) as f32 / 100.0).collect();
+        for &s in &samples {
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let source = FileSource::open(&path).unwrap();
+        assert_eq!(source.sample_rate(), 8_000);
+        // Ring far smaller than the file
+        assert_eq!(drain(Box::new(source), 2_048), samples);
+    }
+
+    #[test]
+    fn flac_file_is_decoded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("speech.flac");
+        let samples: Vec<f32> = (0..1_000).map(|i| ((i % 50) as f32 / 50.0 - 0.5) * 0.8).collect();
+        crate::history::flac::write(&path, &samples, 16_000).unwrap();
+
+        let decoded = drain(Box::new(FileSource::open(&path).unwrap()), 4_096);
+        assert_eq!(decoded.len(), samples.len());
+        assert!(decoded.iter().zip(&samples).all(|(a, b)| (a - b).abs() < 1e-3));
+    }
+
+    #[test]
+    fn blocked_raw_input_does_not_hold_up_stop() {
+        /// Blocks forever, like a FIFO without a writer.
+        struct Stalled;
+        impl Read for Stalled {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                loop {
+                    std::thread::park();
+                }
+            }
+        }
+
+        let source = RawPcmSource::new("stalled", 16_000, false, || Ok(Box::new(Stalled)));
+        let (producer, consumer) = ring::channel(64);
+        let handle = Box::new(source).start(SourceSink::new(producer, || true)).unwrap();
+        let SourceHandle::Reader(mut reader) = handle else { panic!("reader source") };
+        let thread = reader.thread.take().unwrap();
+        drop(reader);
+        thread.join().unwrap();
+        // The sink went with the reader thread
+        assert!(consumer.is_abandoned());
+    }
+
+    #[test]
+    fn file_source_pauses_while_gate_is_closed() {
+        let open = Arc::new(AtomicBool::new(false));
+        let (producer, mut consumer) = ring::channel(64);
+        let gate = open.clone();
+        let mut sink = SourceSink::new(producer, move || gate.load(Ordering::Relaxed));
+        let stop = AtomicBool::new(false);
+        let pusher = std::thread::spawn(move || sink.push_paused(&[0.5; 16], &stop));
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!pusher.is_finished());
+
+        open.store(true, Ordering::Relaxed);
+        assert!(pusher.join().unwrap());
+        let mut out = Vec::new();
+        consumer.pop_into(&mut out);
+        assert_eq!(out, vec![0.5; 16]);
+    }
+
+    #[test]
+    fn closed_gate_discards_audio() {
+        let (producer, mut consumer) = ring::channel(64);
+        let mut sink = SourceSink::new(producer, || false);
+        assert!(sink.push_blocking(&[0.5; 200], &AtomicBool::new(false)));
+        sink.push([0.5; 8]);
+        let mut out = Vec::new();
+        consumer.pop_into(&mut out);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn unknown_or_incomplete_source_config_is_an_error() {
+        let mut cfg = AudioConfig { source: "tape".into(), ..Default::default() };
+        assert!(create_source(&cfg).is_err());
+        cfg.source = "file".into();
+        assert!(create_source(&cfg).is_err());
+    }
+}
@@ -0,0 +1,18 @@
+//! Signal helpers shared by the audio and VAD tests.
+
+/// Deterministic uniform noise in [-amplitude, amplitude] (xorshift32).
+pub fn noise(len: usize, amplitude: f32, seed: u32) -> Vec<f32> {
+    let mut x = seed;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            amplitude * (x as f32 / u32::MAX as f32 * 2.0 - 1.0)
+        })
+        .collect()
+}
+
+pub fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
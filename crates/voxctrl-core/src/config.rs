@@ -38,6 +38,36 @@ impl fmt::Display for GpuBackend {
     }
 }
 
+// ── Hotkey mode enum ────────────────────────────────────────────────────
+
+/// How a hotkey drives recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HotkeyMode {
+    /// Press to start, press again to stop and transcribe.
+    #[default]
+    Toggle,
+    /// Push-to-talk: record while held, transcribe on release.
+    Hold,
+}
+
+// ── Text injection enum ─────────────────────────────────────────────────
+
+/// How the type-text action enters text into the focused window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InjectionMode {
+    /// Synthetic keystrokes, one per character.
+    #[default]
+    Keystrokes,
+    /// Paste through the clipboard, restoring its previous text afterwards.
+    /// Typed as keystrokes instead when the clipboard holds something other
+    /// than text (an image, files) or is empty, so that is never overwritten.
+    ClipboardPaste,
+    /// Clipboard paste for text of at least `paste_min_chars`, keystrokes otherwise.
+    Auto,
+}
+
 // ── Sub-configs for each pipeline stage ────────────────────────────────────
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -52,8 +82,38 @@ pub struct SttConfig {
     pub whisper_device: String,
     #[serde(default = "default_whisper_compute_type")]
     pub whisper_compute_type: String,
+    /// Language code (e.g. "en"), or "auto" to detect it for each utterance.
     #[serde(default)]
     pub whisper_language: Option<String>,
+    /// Languages "auto" may detect (whisper-native); empty allows all.
+    #[serde(default)]
+    pub allowed_languages: Vec<String>,
+    /// Decoder prompt biasing recognition toward expected terms (whisper-native).
+    /// When unset, the user dictionary's terms are used.
+    #[serde(default)]
+    pub initial_prompt: Option<String>,
+    /// Beams kept while decoding (whisper-native); 1 decodes greedily.
+    #[serde(default = "default_beam_size")]
+    pub beam_size: usize,
+    /// Length penalty exponent for ranking finished beams; unset ranks by
+    /// mean log-probability per token.
+    #[serde(default)]
+    pub length_penalty: Option<f32>,
+    /// Decoding temperatures tried in turn until a result passes the
+    /// fallback thresholds (whisper-native). `[0.0]` never falls back.
+    #[serde(default = "default_temperatures")]
+    pub temperatures: Vec<f32>,
+    /// Re-decode at the next temperature when the text's compression ratio exceeds this…
+    #[serde(default = "default_fallback_compression_ratio")]
+    pub fallback_compression_ratio: f32,
+    /// …or its average log-probability is below this.
+    #[serde(default = "default_fallback_logprob")]
+    pub fallback_logprob: f32,
+    /// Type finalized text while a hotkey recording is still running, using
+    /// the backend's incremental session (voxtral-native, whisper-native).
+    /// Computer-use recordings always wait for the whole utterance.
+    #[serde(default)]
+    pub streaming: bool,
 }
 
 impl Default for SttConfig {
@@ -65,18 +125,66 @@ impl Default for SttConfig {
             whisper_device: default_whisper_device(),
             whisper_compute_type: default_whisper_compute_type(),
             whisper_language: None,
+            allowed_languages: Vec::new(),
+            initial_prompt: None,
+            beam_size: default_beam_size(),
+            length_penalty: None,
+            temperatures: default_temperatures(),
+            fallback_compression_ratio: default_fallback_compression_ratio(),
+            fallback_logprob: default_fallback_logprob(),
+            streaming: false,
         }
     }
 }
 
+impl SttConfig {
+    /// The configured language, unless it is detected per utterance.
+    pub fn fixed_language(&self) -> Option<&str> {
+        self.whisper_language.as_deref().filter(|&lang| lang != "auto")
+    }
+
+    pub fn detects_language(&self) -> bool {
+        self.whisper_language.as_deref() == Some("auto")
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VadConfig {
     #[serde(default = "default_vad_backend")]
     pub backend: String,
     #[serde(default = "default_energy_threshold")]
     pub energy_threshold: f64,
+    /// Energy backend: track the noise floor instead of using a fixed
+    /// threshold (`energy_threshold` then only seeds the floor).
+    #[serde(default)]
+    pub energy_adaptive: bool,
+    /// Level above the noise floor that starts speech (adaptive energy VAD).
+    #[serde(default = "default_onset_margin_db")]
+    pub onset_margin_db: f32,
+    /// Level above the noise floor that speech must drop below to end.
+    #[serde(default = "default_offset_margin_db")]
+    pub offset_margin_db: f32,
+    /// Speech shorter than this (clicks, bumps) is ignored.
+    #[serde(default = "default_min_speech_ms")]
+    pub min_speech_ms: u32,
+    /// Pauses shorter than this don't end speech.
+    #[serde(default = "default_min_silence_ms")]
+    pub min_silence_ms: u32,
     #[serde(default = "default_silero_threshold")]
     pub silero_threshold: f32,
+    /// Spectral backend: speech/noise log-likelihood ratio a frame needs;
+    /// raise it to reject more.
+    #[serde(default)]
+    pub spectral_threshold: f32,
+    /// Hands-free mode: segment utterances with the VAD instead of the hotkey.
+    #[serde(default)]
+    pub hands_free: bool,
+    /// Audio kept from before speech onset, so the first syllable isn't clipped.
+    #[serde(default = "default_pre_roll_ms")]
+    pub pre_roll_ms: u32,
+    /// Trailing silence that closes an utterance in hands-free mode.
+    #[serde(default = "default_trailing_silence_ms")]
+    pub trailing_silence_ms: u32,
 }
 
 impl Default for VadConfig {
@@ -84,7 +192,16 @@ impl Default for VadConfig {
         Self {
             backend: default_vad_backend(),
             energy_threshold: default_energy_threshold(),
+            energy_adaptive: false,
+            onset_margin_db: default_onset_margin_db(),
+            offset_margin_db: default_offset_margin_db(),
+            min_speech_ms: default_min_speech_ms(),
+            min_silence_ms: default_min_silence_ms(),
             silero_threshold: default_silero_threshold(),
+            spectral_threshold: 0.0,
+            hands_free: false,
+            pre_roll_ms: default_pre_roll_ms(),
+            trailing_silence_ms: default_trailing_silence_ms(),
         }
     }
 }
@@ -96,6 +213,9 @@ pub struct RouterConfig {
     /// URL for LLM router (reuses voxtral URL by default).
     #[serde(default)]
     pub llm_url: Option<String>,
+    /// Grammar file for the rules router (default: commands.json next to config.json).
+    #[serde(default)]
+    pub rules_path: Option<PathBuf>,
 }
 
 impl Default for RouterConfig {
@@ -103,6 +223,94 @@ impl Default for RouterConfig {
         Self {
             backend: default_router_backend(),
             llm_url: None,
+            rules_path: None,
+        }
+    }
+}
+
+/// Text clean-up applied to transcripts before routing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct PostprocessConfig {
+    /// Turn spoken punctuation ("comma", "new paragraph", "open quote") into symbols.
+    #[serde(default)]
+    pub spoken_punctuation: bool,
+    /// Rule-table language (e.g. "en", "de"); falls back to `stt.whisper_language`, then English.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// User dictionary file (default: dictionary.json next to config.json).
+    #[serde(default)]
+    pub dictionary_path: Option<PathBuf>,
+    #[serde(default)]
+    pub hallucination: HallucinationConfig,
+}
+
+/// Rejection of likely STT hallucinations before post-processing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HallucinationConfig {
+    #[serde(default = "default_hallucination_enabled")]
+    pub enabled: bool,
+    /// Drop a segment whose no-speech probability exceeds this, unless its
+    /// average log-probability is at least `logprob_threshold`.
+    #[serde(default = "default_no_speech_threshold")]
+    pub no_speech_threshold: f32,
+    #[serde(default = "default_logprob_threshold")]
+    pub logprob_threshold: f32,
+    /// Drop a segment whose average log-probability is below this.
+    #[serde(default = "default_min_avg_logprob")]
+    pub min_avg_logprob: f32,
+    /// Drop text whose zlib compression ratio exceeds this (repetition loops).
+    #[serde(default = "default_compression_ratio_threshold")]
+    pub compression_ratio_threshold: f32,
+    /// Phrases removed when they make up or end a segment, matched ignoring
+    /// case and punctuation.
+    #[serde(default = "default_hallucination_blocklist")]
+    pub blocklist: Vec<String>,
+}
+
+impl Default for HallucinationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_hallucination_enabled(),
+            no_speech_threshold: default_no_speech_threshold(),
+            logprob_threshold: default_logprob_threshold(),
+            min_avg_logprob: default_min_avg_logprob(),
+            compression_ratio_threshold: default_compression_ratio_threshold(),
+            blocklist: default_hallucination_blocklist(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Record every transcript to the history file (opt-in).
+    #[serde(default)]
+    pub enabled: bool,
+    /// History file (default: history.jsonl next to config.json).
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Keep at most this many entries; older ones are dropped. 0 keeps all.
+    #[serde(default = "default_history_max_entries")]
+    pub max_entries: usize,
+    /// Keep each utterance's audio as FLAC alongside its entry (opt-in).
+    #[serde(default)]
+    pub keep_audio: bool,
+    /// Delete the oldest retained audio once the total exceeds this size.
+    #[serde(default = "default_history_audio_max_mb")]
+    pub audio_max_mb: u64,
+    /// Delete retained audio older than this many days.
+    #[serde(default = "default_history_audio_max_days")]
+    pub audio_max_days: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            max_entries: default_history_max_entries(),
+            keep_audio: false,
+            audio_max_mb: default_history_audio_max_mb(),
+            audio_max_days: default_history_audio_max_days(),
         }
     }
 }
@@ -129,6 +337,15 @@ pub struct ActionConfig {
     /// Computer-use provider type (default: "anthropic").
     #[serde(default = "default_cu_provider_type")]
     pub cu_provider_type: String,
+    /// Router backend for the computer-use hotkey pipeline (default: "goal").
+    #[serde(default = "default_cu_router")]
+    pub cu_router: String,
+    /// How type-text enters dictation: "keystrokes", "clipboard-paste" or "auto".
+    #[serde(default)]
+    pub injection: InjectionMode,
+    /// Shortest text pasted via the clipboard in "auto" injection mode.
+    #[serde(default = "default_paste_min_chars")]
+    pub paste_min_chars: usize,
 }
 
 impl Default for ActionConfig {
@@ -141,6 +358,9 @@ impl Default for ActionConfig {
             cu_max_tree_depth: None,
             cu_include_screenshots: None,
             cu_provider_type: default_cu_provider_type(),
+            cu_router: default_cu_router(),
+            injection: InjectionMode::Keystrokes,
+            paste_min_chars: default_paste_min_chars(),
         }
     }
 }
@@ -149,36 +369,122 @@ impl Default for ActionConfig {
 pub struct HotkeyConfig {
     #[serde(default = "default_hotkey_shortcut")]
     pub dict_shortcut: String,
+    /// Dictation hotkey behaviour: "toggle" or "hold" (push-to-talk).
+    #[serde(default)]
+    pub dict_mode: HotkeyMode,
     /// Computer-use hotkey shortcut string (optional, e.g. "Ctrl+Super+C").
     #[serde(default)]
     pub cu_shortcut: Option<String>,
+    /// Computer-use hotkey behaviour: "toggle" or "hold" (push-to-talk).
+    #[serde(default)]
+    pub cu_mode: HotkeyMode,
+    /// Hotkey that undoes the last dictation (optional, e.g. "Ctrl+Super+Z").
+    #[serde(default)]
+    pub undo_shortcut: Option<String>,
 }
 
 impl Default for HotkeyConfig {
     fn default() -> Self {
         Self {
             dict_shortcut: default_hotkey_shortcut(),
+            dict_mode: HotkeyMode::Toggle,
             cu_shortcut: None,
+            cu_mode: HotkeyMode::Toggle,
+            undo_shortcut: None,
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AudioConfig {
+    /// Where audio comes from: "device", "file", "stdin" or "fifo".
+    #[serde(default = "default_audio_source")]
+    pub source: String,
+    /// Input for the "file" and "fifo" sources.
+    #[serde(default)]
+    pub source_path: Option<PathBuf>,
     #[serde(default = "default_device_pattern")]
     pub device_pattern: String,
+    /// Devices to fall back to, in order, when `device_pattern` isn't
+    /// available or is unplugged.
+    #[serde(default)]
+    pub fallback_devices: Vec<String>,
     #[serde(default = "default_sample_rate")]
     pub sample_rate: u32,
     #[serde(default = "default_chunk_duration_ms")]
     pub chunk_duration_ms: u32,
+    /// Longest hotkey recording kept; audio past this is dropped.
+    #[serde(default = "default_max_recording_secs")]
+    pub max_recording_secs: u32,
+    /// Preprocessing applied to captured audio before VAD and STT.
+    #[serde(default)]
+    pub filters: AudioFilterConfig,
+    /// Playback device captured as a reference for rejecting speaker bleed
+    /// (`"default"` for the default output); `None` disables it.
+    #[serde(default)]
+    pub loopback_device: Option<String>,
+    /// How far the talker must exceed playback bleed for mic audio to pass.
+    #[serde(default = "default_bleed_margin_db")]
+    pub bleed_margin_db: f32,
 }
 
 impl Default for AudioConfig {
     fn default() -> Self {
         Self {
+            source: default_audio_source(),
+            source_path: None,
             device_pattern: default_device_pattern(),
+            fallback_devices: Vec::new(),
             sample_rate: default_sample_rate(),
             chunk_duration_ms: default_chunk_duration_ms(),
+            max_recording_secs: default_max_recording_secs(),
+            filters: AudioFilterConfig::default(),
+            loopback_device: None,
+            bleed_margin_db: default_bleed_margin_db(),
+        }
+    }
+}
+
+impl AudioConfig {
+    /// Device name patterns in order of preference.
+    pub fn device_preferences(&self) -> Vec<&str> {
+        std::iter::once(self.device_pattern.as_str())
+            .chain(self.fallback_devices.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+/// Audio preprocessing chain; every stage is off by default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioFilterConfig {
+    /// High-pass cutoff in Hz (e.g. 80) to remove DC offset and rumble.
+    #[serde(default)]
+    pub highpass_hz: Option<f32>,
+    /// Spectral-gating suppression of steady background noise.
+    #[serde(default)]
+    pub noise_suppression: bool,
+    /// Attenuation applied to noise-only frequency bins.
+    #[serde(default = "default_noise_reduction_db")]
+    pub noise_reduction_db: f32,
+    /// Automatic gain control towards `agc_target_dbfs`.
+    #[serde(default)]
+    pub agc: bool,
+    #[serde(default = "default_agc_target_dbfs")]
+    pub agc_target_dbfs: f32,
+    /// Largest boost (and cut) AGC applies.
+    #[serde(default = "default_agc_max_gain_db")]
+    pub agc_max_gain_db: f32,
+}
+
+impl Default for AudioFilterConfig {
+    fn default() -> Self {
+        Self {
+            highpass_hz: None,
+            noise_suppression: false,
+            noise_reduction_db: default_noise_reduction_db(),
+            agc: false,
+            agc_target_dbfs: default_agc_target_dbfs(),
+            agc_max_gain_db: default_agc_max_gain_db(),
         }
     }
 }
@@ -229,12 +535,16 @@ pub struct Config {
     #[serde(default)]
     pub router: RouterConfig,
     #[serde(default)]
+    pub postprocess: PostprocessConfig,
+    #[serde(default)]
     pub action: ActionConfig,
     #[serde(default)]
     pub audio: AudioConfig,
     #[serde(default)]
     pub hotkey: HotkeyConfig,
     #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
     pub models: ModelsConfig,
     #[serde(default)]
     pub gpu: GpuConfig,
@@ -246,9 +556,11 @@ impl Default for Config {
             stt: SttConfig::default(),
             vad: VadConfig::default(),
             router: RouterConfig::default(),
+            postprocess: PostprocessConfig::default(),
             action: ActionConfig::default(),
             audio: AudioConfig::default(),
             hotkey: HotkeyConfig::default(),
+            history: HistoryConfig::default(),
             models: ModelsConfig::default(),
             gpu: GpuConfig::default(),
         }
@@ -262,17 +574,58 @@ fn default_voxtral_url() -> String { "http://127.0.0.1:5200".into() }
 fn default_whisper_model() -> String { "small".into() }
 fn default_whisper_device() -> String { "cpu".into() }
 fn default_whisper_compute_type() -> String { "int8".into() }
+fn default_beam_size() -> usize { 1 }
+fn default_temperatures() -> Vec<f32> { vec![0.0] }
+fn default_fallback_compression_ratio() -> f32 { 2.4 }
+fn default_fallback_logprob() -> f32 { -1.0 }
 fn default_vad_backend() -> String { "energy".into() }
 fn default_energy_threshold() -> f64 { 0.015 }
+fn default_onset_margin_db() -> f32 { 10.0 }
+fn default_offset_margin_db() -> f32 { 6.0 }
+fn default_min_speech_ms() -> u32 { 60 }
+fn default_min_silence_ms() -> u32 { 200 }
 fn default_silero_threshold() -> f32 { 0.5 }
+fn default_pre_roll_ms() -> u32 { 300 }
+fn default_trailing_silence_ms() -> u32 { 800 }
 fn default_router_backend() -> String { "passthrough".into() }
 fn default_action_backend() -> String { "type-text".into() }
 fn default_cu_provider_type() -> String { "anthropic".into() }
+fn default_cu_router() -> String { "goal".into() }
+fn default_paste_min_chars() -> usize { 200 }
 fn default_hotkey_shortcut() -> String { "Ctrl+Super+Space".into() }
+fn default_audio_source() -> String { "device".into() }
 fn default_device_pattern() -> String { "DJI".into() }
 fn default_sample_rate() -> u32 { 16000 }
 fn default_chunk_duration_ms() -> u32 { 100 }
+fn default_max_recording_secs() -> u32 { 300 }
+fn default_bleed_margin_db() -> f32 { 6.0 }
+fn default_noise_reduction_db() -> f32 { 15.0 }
+fn default_agc_target_dbfs() -> f32 { -20.0 }
+fn default_agc_max_gain_db() -> f32 { 30.0 }
 fn default_zluda_auto_download() -> bool { true }
+fn default_hallucination_enabled() -> bool { true }
+fn default_no_speech_threshold() -> f32 { 0.6 }
+fn default_logprob_threshold() -> f32 { -1.0 }
+fn default_min_avg_logprob() -> f32 { -1.5 }
+fn default_compression_ratio_threshold() -> f32 { 2.4 }
+fn default_hallucination_blocklist() -> Vec<String> {
+    [
+        "thank you for watching",
+        "thanks for watching",
+        "thank you so much for watching",
+        "please subscribe",
+        "please like and subscribe",
+        "don't forget to like and subscribe",
+        "subscribe to my channel",
+        "see you in the next video",
+        "subtitles by the amara.org community",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+fn default_history_max_entries() -> usize { 1000 }
+fn default_history_audio_max_mb() -> u64 { 200 }
+fn default_history_audio_max_days() -> u64 { 30 }
 
 // ── Load / save ────────────────────────────────────────────────────────────
 
@@ -314,6 +667,8 @@ pub fn load_config() -> Config {
             || raw.get("audio").is_some()
             || raw.get("hotkey").is_some()
             || raw.get("models").is_some()
+            || raw.get("postprocess").is_some()
+            || raw.get("history").is_some()
         {
             return cfg;
         }
@@ -366,23 +721,49 @@ fn load_legacy_config(contents: &str) -> Config {
             whisper_device: flat.whisper_device,
             whisper_compute_type: flat.whisper_compute_type,
             whisper_language: flat.whisper_language,
+            allowed_languages: Vec::new(),
+            initial_prompt: None,
+            beam_size: default_beam_size(),
+            length_penalty: None,
+            temperatures: default_temperatures(),
+            fallback_compression_ratio: default_fallback_compression_ratio(),
+            fallback_logprob: default_fallback_logprob(),
+            streaming: false,
         },
         vad: VadConfig {
             backend: default_vad_backend(),
             energy_threshold: flat.silence_threshold,
+            energy_adaptive: false,
+            onset_margin_db: default_onset_margin_db(),
+            offset_margin_db: default_offset_margin_db(),
+            min_speech_ms: default_min_speech_ms(),
+            min_silence_ms: default_min_silence_ms(),
             silero_threshold: default_silero_threshold(),
+            spectral_threshold: 0.0,
+            hands_free: false,
+            pre_roll_ms: default_pre_roll_ms(),
+            trailing_silence_ms: default_trailing_silence_ms(),
         },
         router: RouterConfig::default(),
+        postprocess: PostprocessConfig::default(),
         action: ActionConfig::default(),
         audio: AudioConfig {
+            source: default_audio_source(),
+            source_path: None,
             device_pattern: flat.device_pattern,
+            fallback_devices: Vec::new(),
             sample_rate: flat.sample_rate,
             chunk_duration_ms: flat
                 .chunk_duration
                 .map(|d| (d * 1000.0) as u32)
                 .unwrap_or(default_chunk_duration_ms()),
+            max_recording_secs: default_max_recording_secs(),
+            filters: AudioFilterConfig::default(),
+            loopback_device: None,
+            bleed_margin_db: default_bleed_margin_db(),
         },
         hotkey: HotkeyConfig::default(),
+        history: HistoryConfig::default(),
         models: ModelsConfig::default(),
         gpu: GpuConfig::default(),
     }
@@ -464,6 +845,56 @@ mod tests {
         assert!(action.cu_include_screenshots.is_none());
     }
 
+    #[test]
+    fn test_vad_config_hands_free_defaults() {
+        let vad: VadConfig = serde_json::from_str(r#"{"backend": "energy"}"#).unwrap();
+        assert!(!vad.hands_free);
+        assert_eq!(vad.pre_roll_ms, 300);
+        assert_eq!(vad.trailing_silence_ms, 800);
+        assert!(!vad.energy_adaptive);
+        assert_eq!(vad.onset_margin_db, 10.0);
+        assert_eq!(vad.min_silence_ms, 200);
+    }
+
+    #[test]
+    fn test_audio_filter_config_defaults() {
+        let audio: AudioConfig =
+            serde_json::from_str(r#"{"filters": {"highpass_hz": 80, "agc": true}}"#).unwrap();
+        assert_eq!(audio.filters.highpass_hz, Some(80.0));
+        assert!(audio.filters.agc);
+        assert!(!audio.filters.noise_suppression);
+        assert_eq!(audio.filters.agc_target_dbfs, -20.0);
+        assert_eq!(audio.sample_rate, 16000);
+    }
+
+    #[test]
+    fn test_stt_language_auto() {
+        let stt: SttConfig =
+            serde_json::from_str(r#"{"whisper_language": "auto", "allowed_languages": ["en", "de"]}"#).unwrap();
+        assert!(stt.detects_language());
+        assert_eq!(stt.fixed_language(), None);
+        assert_eq!(stt.allowed_languages, vec!["en", "de"]);
+
+        let stt = SttConfig { whisper_language: Some("fr".into()), ..Default::default() };
+        assert!(!stt.detects_language());
+        assert_eq!(stt.fixed_language(), Some("fr"));
+    }
+
+    #[test]
+    fn test_hotkey_mode_parsing() {
+        let hk: HotkeyConfig = serde_json::from_str(r#"{"dict_mode": "hold"}"#).unwrap();
+        assert_eq!(hk.dict_mode, HotkeyMode::Hold);
+        assert_eq!(hk.cu_mode, HotkeyMode::Toggle);
+        assert_eq!(serde_json::to_string(&HotkeyMode::Hold).unwrap(), r#""hold""#);
+    }
+
+    #[test]
+    fn test_postprocess_config_defaults() {
+        let cfg: Config = serde_json::from_str(r#"{"stt": {}}"#).unwrap();
+        assert!(!cfg.postprocess.spoken_punctuation);
+        assert!(cfg.postprocess.language.is_none());
+    }
+
     #[test]
     fn test_two_defaults_are_equal() {
         assert_eq!(Config::default(), Config::default());
@@ -495,6 +926,9 @@ mod tests {
             cu_max_iterations: Some(20),
             cu_max_tree_depth: Some(12),
             cu_include_screenshots: Some(true),
+            cu_router: "rules".into(),
+            injection: InjectionMode::Auto,
+            paste_min_chars: 80,
         };
         let json = serde_json::to_string(&action).unwrap();
         let parsed: ActionConfig = serde_json::from_str(&json).unwrap();
@@ -505,5 +939,17 @@ mod tests {
         assert_eq!(parsed.cu_max_iterations, Some(20));
         assert_eq!(parsed.cu_max_tree_depth, Some(12));
         assert_eq!(parsed.cu_include_screenshots, Some(true));
+        assert_eq!(parsed.cu_router, "rules");
+        assert_eq!(parsed.injection, InjectionMode::Auto);
+        assert_eq!(parsed.paste_min_chars, 80);
+    }
+
+    #[test]
+    fn test_injection_mode_parsing() {
+        let action: ActionConfig = serde_json::from_str(r#"{"injection": "clipboard-paste"}"#).unwrap();
+        assert_eq!(action.injection, InjectionMode::ClipboardPaste);
+        assert_eq!(action.paste_min_chars, 200);
+        let action: ActionConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(action.injection, InjectionMode::Keystrokes);
     }
 }
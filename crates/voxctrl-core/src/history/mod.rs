@@ -0,0 +1,500 @@
+//! Transcript history — append-only JSONL log of every pipeline run.
+//!
+//! Each line of `history.jsonl` (next to config.json by default) is one
+//! [`HistoryEntry`]. The pipeline appends; the Settings window and TUI read
+//! the file back to search entries and re-type them.
+//!
+//! The file is rotated to the newest `history.max_entries` entries as it
+//! grows, so readers never load an unbounded log.
+//!
+//! With `history.keep_audio`, each entry's utterance is also kept as FLAC in
+//! `history-audio/` beside the history file, pruned by age and total size,
+//! so it can be re-transcribed later with another backend.
+
+pub mod flac;
+
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::action::ActionFactory;
+use crate::config::{self, ActionConfig, HistoryConfig};
+use crate::router::Intent;
+use crate::stt::Transcriber;
+
+/// Delay before re-typing an entry, so the user can focus the target window.
+pub const RETYPE_DELAY: Duration = Duration::from_secs(3);
+
+/// One pipeline run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Unix time in milliseconds.
+    pub timestamp_ms: u64,
+    pub stt_backend: String,
+    /// Language the STT backend reported, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub stt_secs: f64,
+    /// Time from the start of transcription to the end of the action.
+    pub total_secs: f64,
+    /// Transcript as returned by the STT backend.
+    pub raw_text: String,
+    /// Transcript after post-processing, as passed to the router.
+    pub text: String,
+    /// Routed intent, or `None` if routing failed.
+    pub intent: Option<Intent>,
+    /// Routing or action error; `None` if the action succeeded.
+    pub error: Option<String>,
+    /// Retained audio file name in the audio directory, if audio was kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<String>,
+}
+
+impl HistoryEntry {
+    /// Case-insensitive substring match against the raw and final text.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        query.is_empty()
+            || self.text.to_lowercase().contains(&query)
+            || self.raw_text.to_lowercase().contains(&query)
+    }
+
+    /// Short relative age such as "5 min ago".
+    pub fn age(&self) -> String {
+        let secs = now_ms().saturating_sub(self.timestamp_ms) / 1000;
+        match secs {
+            0..=59 => "just now".into(),
+            60..=3599 => format!("{} min ago", secs / 60),
+            3600..=86_399 => format!("{} h ago", secs / 3600),
+            _ => format!("{} d ago", secs / 86_400),
+        }
+    }
+}
+
+/// Append-only history file.
+///
+/// Appends and rotation are serialized per instance, so everything writing
+/// one file must share a single `History` (the dictation and computer-use
+/// pipelines share it through an `Arc`).
+pub struct History {
+    path: PathBuf,
+    /// Serializes appends from concurrent pipeline runs; holds the entry
+    /// count once known, for rotation.
+    lock: Mutex<Option<usize>>,
+    /// Entries kept on rotation; `None` keeps all.
+    max_entries: Option<usize>,
+    /// Audio retention limits; `None` when audio is not kept.
+    retention: Option<Retention>,
+}
+
+/// Limits for retained utterance audio.
+#[derive(Debug, Clone, Copy)]
+pub struct Retention {
+    pub max_bytes: u64,
+    pub max_age: Duration,
+}
+
+/// Default history location: `history.jsonl` next to config.json.
+pub fn default_history_path() -> PathBuf {
+    config::config_path().with_file_name("history.jsonl")
+}
+
+/// Configured history file path.
+pub fn history_path(cfg: &HistoryConfig) -> PathBuf {
+    cfg.path.clone().unwrap_or_else(default_history_path)
+}
+
+/// Directory holding retained audio for a history file: `<stem>-audio/` beside it.
+pub fn audio_dir(history_path: &Path) -> PathBuf {
+    let stem = history_path.file_stem().unwrap_or_default().to_string_lossy();
+    history_path.with_file_name(format!("{stem}-audio"))
+}
+
+/// Retained audio file for an entry, if it has one.
+pub fn audio_path(history_path: &Path, entry: &HistoryEntry) -> Option<PathBuf> {
+    entry.audio.as_ref().map(|name| audio_dir(history_path).join(name))
+}
+
+impl History {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, lock: Mutex::new(None), max_entries: None, retention: None }
+    }
+
+    /// Keep only the newest `max_entries` entries.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Also keep each utterance's audio, within the given limits.
+    pub fn with_audio(mut self, retention: Retention) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Open the configured history, or `None` if history is disabled.
+    pub fn from_config(cfg: &HistoryConfig) -> Option<Self> {
+        let mut history = Self::new(history_path(cfg));
+        if cfg.max_entries > 0 {
+            history = history.with_max_entries(cfg.max_entries);
+        }
+        let retention = Retention {
+            max_bytes: cfg.audio_max_mb.saturating_mul(1024 * 1024),
+            max_age: Duration::from_secs(cfg.audio_max_days.saturating_mul(86_400)),
+        };
+        match (cfg.enabled, cfg.keep_audio) {
+            (false, _) => None,
+            (true, false) => Some(history),
+            (true, true) => Some(history.with_audio(retention)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one entry as a JSON line, rotating the file once it holds a
+    /// tenth more than `max_entries`.
+    pub fn append(&self, entry: &HistoryEntry) -> io::Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        let mut count = self.lock.lock().unwrap();
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())?;
+
+        let Some(max) = self.max_entries else { return Ok(()) };
+        let mut n = match *count {
+            Some(n) => n + 1,
+            None => load(&self.path)?.len(),
+        };
+        // Rotate in batches rather than rewriting the file on every append
+        if n > max + max / 10 {
+            n = self.rotate(max)?;
+        }
+        *count = Some(n);
+        Ok(())
+    }
+
+    /// Rewrite the file with only the newest `keep` entries, deleting the
+    /// retained audio of dropped ones. Returns the number of entries left.
+    fn rotate(&self, keep: usize) -> io::Result<usize> {
+        let entries = load(&self.path)?;
+        let dropped = entries.len().saturating_sub(keep);
+        let mut contents = String::new();
+        for entry in &entries[dropped..] {
+            contents.push_str(&serde_json::to_string(entry)?);
+            contents.push('\n');
+        }
+        let tmp = self.path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp, contents)?;
+        std::fs::rename(&tmp, &self.path)?;
+
+        for path in entries[..dropped].iter().filter_map(|e| audio_path(&self.path, e)) {
+            if let Err(e) = std::fs::remove_file(&path) {
+                if e.kind() != io::ErrorKind::NotFound {
+                    log::warn!("Failed to delete history audio {:?}: {e}", path);
+                }
+            }
+        }
+        log::debug!("History: rotated out {dropped} entries");
+        Ok(entries.len() - dropped)
+    }
+
+    /// Store an utterance's audio if retention is enabled, then prune old files.
+    /// Returns the file name to record in the entry; failures are logged.
+    pub fn save_audio(&self, samples: &[f32], sample_rate: u32, timestamp_ms: u64) -> Option<String> {
+        let retention = self.retention?;
+        let dir = audio_dir(&self.path);
+        let name = format!("{timestamp_ms}.flac");
+        let saved = std::fs::create_dir_all(&dir)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| flac::write(&dir.join(&name), samples, sample_rate));
+        if let Err(e) = saved {
+            log::warn!("Failed to save history audio to {:?}: {e:#}", dir);
+            return None;
+        }
+        match prune_audio(&dir, retention) {
+            Ok(0) => {}
+            Ok(n) => log::debug!("History: pruned {n} audio file(s)"),
+            Err(e) => log::warn!("Failed to prune history audio in {:?}: {e}", dir),
+        }
+        Some(name)
+    }
+}
+
+/// Delete retained audio older than `max_age`, then the oldest files until the
+/// rest fit in `max_bytes`. Returns the number of files deleted.
+pub fn prune_audio(dir: &Path, retention: Retention) -> io::Result<usize> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().is_some_and(|e| e == "flac") {
+            let meta = entry.metadata()?;
+            let modified = meta.modified().unwrap_or(UNIX_EPOCH);
+            files.push((modified, meta.len(), path));
+        }
+    }
+    // Oldest first
+    files.sort();
+
+    let now = SystemTime::now();
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    let mut deleted = 0;
+    for (modified, len, path) in files {
+        let expired = now.duration_since(modified).unwrap_or_default() > retention.max_age;
+        if !expired && total <= retention.max_bytes {
+            break;
+        }
+        std::fs::remove_file(&path)?;
+        total -= len;
+        deleted += 1;
+    }
+    Ok(deleted)
+}
+
+/// Run an entry's retained audio through `stt` again, returning the new transcript.
+pub fn retranscribe(history_path: &Path, entry: &HistoryEntry, stt: &dyn Transcriber) -> anyhow::Result<String> {
+    let path = audio_path(history_path, entry)
+        .ok_or_else(|| anyhow::anyhow!("entry has no retained audio"))?;
+    let (samples, sample_rate) = flac::read(&path)?;
+    let samples = crate::audio::resample::to_16k(&samples, sample_rate);
+    stt.transcribe_pcm(&samples, crate::audio::resample::STT_SAMPLE_RATE)
+}
+
+/// Read all entries, oldest first. A missing file is an empty history;
+/// malformed lines are skipped.
+pub fn load(path: &Path) -> io::Result<Vec<HistoryEntry>> {
+    let file = match std::fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut entries = Vec::new();
+    for (i, line) in io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => log::warn!("History: skipping line {} of {:?}: {e}", i + 1, path),
+        }
+    }
+    Ok(entries)
+}
+
+/// Entries matching `query`, newest first.
+pub fn search<'a>(entries: &'a [HistoryEntry], query: &str) -> Vec<&'a HistoryEntry> {
+    entries.iter().rev().filter(|e| e.matches(query)).collect()
+}
+
+/// Type `text` at the cursor after [`RETYPE_DELAY`], on a background thread,
+/// through the action backend configured in `cfg` (so injection mode applies).
+pub fn retype(
+    text: String,
+    cfg: &ActionConfig,
+    action_factory: Option<&ActionFactory>,
+) -> anyhow::Result<JoinHandle<()>> {
+    let action = crate::action::create_action(cfg, action_factory)?;
+    let handle = std::thread::Builder::new()
+        .name("retype".into())
+        .spawn(move || {
+            std::thread::sleep(RETYPE_DELAY);
+            if let Err(e) = action.execute(&Intent::Dictate(text)) {
+                log::error!("Re-type failed: {e:#}");
+            }
+        })?;
+    Ok(handle)
+}
+
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str, timestamp_ms: u64) -> HistoryEntry {
+        HistoryEntry {
+            timestamp_ms,
+            stt_backend: "mock".into(),
+            language: None,
+            stt_secs: 0.5,
+            total_secs: 0.6,
+            raw_text: text.to_lowercase(),
+            text: text.into(),
+            intent: Some(Intent::Dictate(text.into())),
+            error: None,
+            audio: None,
+        }
+    }
+
+    #[test]
+    fn append_and_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path().join("history.jsonl"));
+        let a = entry("Hello", 1);
+        let mut b = entry("press enter", 2);
+        b.intent = Some(Intent::Command {
+            action: "key".into(),
+            args: serde_json::json!({"key": "enter"}),
+        });
+        b.error = Some("no keyboard".into());
+        history.append(&a).unwrap();
+        history.append(&b).unwrap();
+
+        assert_eq!(load(history.path()).unwrap(), vec![a, b]);
+    }
+
+    #[test]
+    fn load_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load(&dir.path().join("none.jsonl")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn load_skips_malformed_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let good = serde_json::to_string(&entry("ok", 1)).unwrap();
+        std::fs::write(&path, format!("{good}\n{{truncated\n\n{good}\n")).unwrap();
+        assert_eq!(load(&path).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn search_is_case_insensitive_and_newest_first() {
+        let entries = vec![entry("Deploy the app", 1), entry("lunch", 2), entry("deploy again", 3)];
+        let found: Vec<&str> = search(&entries, "DEPLOY").iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(found, vec!["deploy again", "Deploy the app"]);
+        assert_eq!(search(&entries, "  ").len(), 3);
+    }
+
+    #[test]
+    fn disabled_config_has_no_history() {
+        let cfg = HistoryConfig { enabled: false, keep_audio: true, ..Default::default() };
+        assert!(History::from_config(&cfg).is_none());
+        assert!(History::from_config(&HistoryConfig::default()).is_none());
+    }
+
+    #[test]
+    fn append_rotates_to_max_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        // An existing file is counted on the first append
+        std::fs::write(&path, format!("{}\n", serde_json::to_string(&entry("old", 0)).unwrap())).unwrap();
+        let history = History::new(path.clone()).with_max_entries(10);
+
+        for i in 1..=11 {
+            history.append(&entry(&format!("e{i}"), i)).unwrap();
+        }
+        // 12 entries > 10 + 10 / 10 → rotated down to the newest 10
+        let entries = load(&path).unwrap();
+        assert_eq!(entries.len(), 10);
+        assert_eq!(entries.first().unwrap().text, "e2");
+        assert_eq!(entries.last().unwrap().text, "e11");
+
+        history.append(&entry("e12", 12)).unwrap();
+        assert_eq!(load(&path).unwrap().len(), 11);
+    }
+
+    #[test]
+    fn rotation_deletes_audio_of_dropped_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let retention = Retention { max_bytes: u64::MAX, max_age: Duration::from_secs(86_400) };
+        let history = History::new(path.clone()).with_max_entries(1).with_audio(retention);
+
+        for i in 1..=2 {
+            let mut e = entry("hello", i);
+            e.audio = history.save_audio(&[0.1; 160], 16000, i);
+            history.append(&e).unwrap();
+        }
+        assert_eq!(load(&path).unwrap().len(), 1);
+        assert!(!audio_dir(&path).join("1.flac").exists());
+        assert!(audio_dir(&path).join("2.flac").exists());
+    }
+
+    #[test]
+    fn audio_is_only_kept_when_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let cfg = HistoryConfig { enabled: true, path: Some(path.clone()), ..Default::default() };
+        let history = History::from_config(&cfg).unwrap();
+        assert!(history.save_audio(&[0.1; 1600], 16000, 1).is_none());
+        assert!(!audio_dir(&path).exists());
+    }
+
+    #[test]
+    fn saved_audio_retranscribes() {
+        struct EchoLen;
+        impl Transcriber for EchoLen {
+            fn transcribe(&self, _: &Path) -> anyhow::Result<String> { unreachable!() }
+            fn transcribe_pcm(&self, samples: &[f32], rate: u32) -> anyhow::Result<String> {
+                Ok(format!("{} samples at {rate} Hz", samples.len()))
+            }
+            fn name(&self) -> &str { "echo" }
+            fn is_available(&self) -> bool { true }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let cfg = HistoryConfig { enabled: true, path: Some(path.clone()), keep_audio: true, ..Default::default() };
+        let history = History::from_config(&cfg).unwrap();
+        let mut e = entry("hello", 42);
+        e.audio = history.save_audio(&[0.1; 1600], 16000, 42);
+        assert_eq!(e.audio.as_deref(), Some("42.flac"));
+        assert_eq!(audio_path(&path, &e), Some(dir.path().join("history-audio/42.flac")));
+
+        assert_eq!(retranscribe(&path, &e, &EchoLen).unwrap(), "1600 samples at 16000 Hz");
+        assert!(retranscribe(&path, &entry("no audio", 1), &EchoLen).is_err());
+    }
+
+    #[test]
+    fn prune_enforces_size_and_age() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, age_days) in [("old.flac", 40), ("a.flac", 3), ("b.flac", 2), ("c.flac", 1)] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, [0u8; 100]).unwrap();
+            let modified = SystemTime::now() - Duration::from_secs(age_days * 86_400);
+            std::fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+        }
+        std::fs::write(dir.path().join("notes.txt"), [0u8; 1000]).unwrap();
+
+        let retention = Retention { max_bytes: 250, max_age: Duration::from_secs(30 * 86_400) };
+        assert_eq!(prune_audio(dir.path(), retention).unwrap(), 2);
+        let mut left: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec!["b.flac", "c.flac", "notes.txt"]);
+    }
+
+    #[test]
+    fn entries_without_audio_field_still_load() {
+        let json = r#"{"timestamp_ms":1,"stt_backend":"mock","stt_secs":0.1,"total_secs":0.2,"raw_text":"hi","text":"hi","intent":null,"error":null}"#;
+        let e: HistoryEntry = serde_json::from_str(json).unwrap();
+        assert!(e.audio.is_none());
+        assert!(e.language.is_none());
+        let json = serde_json::to_string(&e).unwrap();
+        assert!(!json.contains("audio") && !json.contains("language"));
+    }
+
+    #[test]
+    fn age_is_relative() {
+        assert_eq!(entry("x", now_ms()).age(), "just now");
+        assert_eq!(entry("x", now_ms() - 5 * 60_000).age(), "5 min ago");
+        assert_eq!(entry("x", now_ms() - 2 * 86_400_000).age(), "2 d ago");
+    }
+}
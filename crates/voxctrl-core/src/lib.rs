@@ -9,6 +9,7 @@ pub mod audio;
 pub mod config;
 pub mod models;
 pub mod pipeline;
+pub mod postprocess;
 pub mod recording;
 pub mod router;
 pub mod stt;
@@ -16,8 +17,10 @@ pub mod stt_client;
 pub mod stt_server;
 pub mod vad;
 pub mod gpu;
+pub mod history;
 pub mod util;
 
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Mutex;
 
 // ── IPC ──────────────────────────────────────────────────────────────────────
@@ -28,22 +31,73 @@ pub const PIPE_NAME: &str = "voxctrl-stt";
 // ── Shared state ───────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
 pub enum AppStatus {
     Idle,
     Recording,
     Transcribing,
 }
 
+impl AppStatus {
+    fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Recording,
+            2 => Self::Transcribing,
+            _ => Self::Idle,
+        }
+    }
+}
+
+/// [`AppStatus`] readable from the audio callback without taking a lock.
+pub struct AtomicStatus(AtomicU8);
+
+impl AtomicStatus {
+    pub fn new(status: AppStatus) -> Self {
+        Self(AtomicU8::new(status as u8))
+    }
+
+    pub fn load(&self) -> AppStatus {
+        AppStatus::from_u8(self.0.load(Ordering::Acquire))
+    }
+
+    pub fn store(&self, status: AppStatus) {
+        self.0.store(status as u8, Ordering::Release);
+    }
+
+    /// Move from `from` to `to`; returns `false` (leaving the status alone)
+    /// if another thread changed it first.
+    pub fn transition(&self, from: AppStatus, to: AppStatus) -> bool {
+        self.0
+            .compare_exchange(from as u8, to as u8, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+}
+
 pub struct SharedState {
-    pub status: Mutex<AppStatus>,
+    pub status: AtomicStatus,
+    /// Audio of the current manual recording, assembled from `capture`.
     pub chunks: Mutex<Vec<f32>>,
+    /// Reading end of the capture ring buffer; `None` in hands-free mode,
+    /// where the listener thread owns it.
+    pub capture: Mutex<Option<audio::Capture>>,
+    /// Cap on `chunks`, in samples — further audio is dropped.
+    pub max_chunks: AtomicUsize,
+    /// Actual capture rate of `chunks`, set when the stream starts.
+    pub sample_rate: AtomicU32,
+    /// Set while a streaming session transcribes the current recording as
+    /// it is captured (see `stt.streaming`).
+    pub streaming: AtomicBool,
 }
 
 impl SharedState {
     pub fn new() -> Self {
         Self {
-            status: Mutex::new(AppStatus::Idle),
+            status: AtomicStatus::new(AppStatus::Idle),
             chunks: Mutex::new(Vec::new()),
+            capture: Mutex::new(None),
+            max_chunks: AtomicUsize::new(usize::MAX),
+            sample_rate: AtomicU32::new(audio::resample::STT_SAMPLE_RATE),
+            streaming: AtomicBool::new(false),
         }
     }
 }
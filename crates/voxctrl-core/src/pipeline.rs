@@ -1,12 +1,16 @@
-//! Pipeline — wires together STT → Router → Action.
+//! Pipeline — wires together STT → Post-process → Router → Action.
 
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
-use crate::action::{ActionExecutor, ActionFactory};
-use crate::config::Config;
+use crate::action::{ActionExecutor, ActionFactory, UNDO_COMMAND};
+use crate::audio::resample::{self, STT_SAMPLE_RATE};
+use crate::config::{ActionConfig, Config, RouterConfig};
+use crate::history::{History, HistoryEntry};
+use crate::postprocess::hallucination::HallucinationFilter;
+use crate::postprocess::TextProcessor;
 use crate::router::{Intent, IntentRouter};
-use crate::stt::{SttFactory, Transcriber};
+use crate::stt::{SttFactory, Transcriber, Transcript};
 
 // ── SharedPipeline ──────────────────────────────────────────────────────────
 
@@ -35,9 +39,17 @@ impl SharedPipeline {
 }
 
 pub struct Pipeline {
-    pub stt: Box<dyn Transcriber>,
+    /// Shared with the computer-use pipeline, which reuses the loaded model.
+    pub stt: Arc<dyn Transcriber>,
+    /// Rejects likely hallucinated STT output; `None` when disabled.
+    pub hallucination: Option<HallucinationFilter>,
+    /// Text clean-up stages applied in order between STT and routing.
+    pub postprocess: Vec<Box<dyn TextProcessor>>,
     pub router: Box<dyn IntentRouter>,
     pub action: Box<dyn ActionExecutor>,
+    /// Transcript history; `None` when disabled. Shared with the
+    /// computer-use pipeline, so appends to the file are serialized.
+    pub history: Option<Arc<History>>,
 }
 
 impl Pipeline {
@@ -54,63 +66,217 @@ impl Pipeline {
         stt_factory: Option<&SttFactory>,
         action_factory: Option<&ActionFactory>,
     ) -> anyhow::Result<Self> {
-        let stt = crate::stt::create_transcriber(&cfg.stt, stt_model_dir, stt_factory)?;
+        // Bias recognition toward the user dictionary unless a prompt is set explicitly.
+        let mut stt_cfg = cfg.stt.clone();
+        if stt_cfg.initial_prompt.is_none() {
+            stt_cfg.initial_prompt = crate::postprocess::dictionary::Dictionary::configured(&cfg.postprocess)?
+                .and_then(|d| d.prompt());
+        }
+        let stt: Arc<dyn Transcriber> = crate::stt::create_transcriber(&stt_cfg, stt_model_dir, stt_factory)?.into();
+        let postprocess = crate::postprocess::create_processors(cfg)?;
         let router = crate::router::create_router(&cfg.router)?;
         let action = crate::action::create_action(&cfg.action, action_factory)?;
 
+        let stages: Vec<&str> = postprocess.iter().map(|p| p.name()).collect();
         log::info!(
-            "Pipeline: STT={}, Router={}, Action={}",
+            "Pipeline: STT={}, Post-process=[{}], Router={}, Action={}",
             stt.name(),
+            stages.join(", "),
             router.name(),
             action.name(),
         );
 
         Ok(Self {
             stt,
+            hallucination: HallucinationFilter::configured(&cfg.postprocess),
+            postprocess,
             router,
             action,
+            history: History::from_config(&cfg.history).map(Arc::new),
         })
     }
 
-    /// Run the full pipeline from raw PCM: transcribe → route → execute.
+    /// Build the computer-use pipeline driven by the CU hotkey.
+    ///
+    /// Reuses the loaded `stt` and the `history` of the `dictation` pipeline,
+    /// routes with `action.cu_router` and always executes through the
+    /// "computer-use" action backend.
+    pub fn computer_use_from_config(
+        cfg: &Config,
+        dictation: &Pipeline,
+        action_factory: Option<&ActionFactory>,
+    ) -> anyhow::Result<Self> {
+        let stt = dictation.stt.clone();
+        let router_cfg = RouterConfig { backend: cfg.action.cu_router.clone(), ..cfg.router.clone() };
+        let action_cfg = ActionConfig { backend: "computer-use".into(), ..cfg.action.clone() };
+        let postprocess = crate::postprocess::create_processors(cfg)?;
+        let router = crate::router::create_router(&router_cfg)?;
+        let action = crate::action::create_action(&action_cfg, action_factory)?;
+
+        log::info!(
+            "Computer-use pipeline: STT={}, Router={}, Action={}",
+            stt.name(),
+            router.name(),
+            action.name(),
+        );
+
+        Ok(Self {
+            stt,
+            hallucination: HallucinationFilter::configured(&cfg.postprocess),
+            postprocess,
+            router,
+            action,
+            history: dictation.history.clone(),
+        })
+    }
+
+    /// Run the full pipeline from raw mono PCM: transcribe → route → execute.
+    ///
+    /// Audio at any rate is resampled to 16 kHz before STT.
     pub fn process_pcm(&self, samples: &[f32], sample_rate: u32) -> anyhow::Result<()> {
         let start = std::time::Instant::now();
+        let samples = resample::to_16k(samples, sample_rate);
 
         // STT
-        let text = self.stt.transcribe_pcm(samples, sample_rate)?;
+        let transcript = self.stt.transcribe_detailed(&samples, STT_SAMPLE_RATE)?;
         let stt_elapsed = start.elapsed().as_secs_f64();
+        log::debug!(
+            "STT detail: {} segments, language={:?}, avg_logprob={:?}",
+            transcript.segments.len(),
+            transcript.language,
+            transcript.avg_logprob(),
+        );
+
+        let text = match &self.hallucination {
+            Some(filter) => filter.filter(&transcript),
+            None => transcript.text,
+        };
 
-        self.route_and_execute(start, stt_elapsed, text)
+        self.route_and_execute(start, stt_elapsed, text, transcript.language, "", &samples)
     }
 
-    /// Shared tail of the pipeline: log STT result, route, execute.
+    /// Run a finalized piece of a streaming session through the rest of the
+    /// pipeline: filter → post-process → route → execute.
+    ///
+    /// `start` is when the recording started. Dictation after the first piece
+    /// (`continues`) is typed with a leading space. No audio is recorded in
+    /// history for streamed pieces.
+    pub fn process_final(&self, start: std::time::Instant, text: String, continues: bool) -> anyhow::Result<()> {
+        let text = match &self.hallucination {
+            Some(filter) => filter.filter(&Transcript::from_text(text, 0.0)),
+            None => text,
+        };
+        let separator = if continues { " " } else { "" };
+        self.route_and_execute(start, start.elapsed().as_secs_f64(), text, None, separator, &[])
+    }
+
+    /// Shared tail of the pipeline: log STT result, post-process, route, execute, record.
+    ///
+    /// `language` is the language the STT backend reported, if any;
+    /// `separator` is typed before dictated text; `samples` is the 16 kHz
+    /// audio kept in history, if any.
     fn route_and_execute(
         &self,
         start: std::time::Instant,
         stt_elapsed: f64,
         text: String,
+        language: Option<String>,
+        separator: &str,
+        samples: &[f32],
     ) -> anyhow::Result<()> {
         if text.is_empty() {
             log::info!("STT returned empty text ({:.1}s), skipping", stt_elapsed);
             return Ok(());
         }
 
-        let preview = if text.len() > 80 { &text[..80] } else { &text };
-        log::info!("STT ({:.1}s): {}", stt_elapsed, preview);
+        // First 80 characters, cut on a char boundary
+        let preview = match text.char_indices().nth(80) {
+            Some((end, _)) => &text[..end],
+            None => &text,
+        };
+        match &language {
+            Some(lang) => log::info!("STT ({:.1}s, {lang}): {}", stt_elapsed, preview),
+            None => log::info!("STT ({:.1}s): {}", stt_elapsed, preview),
+        }
+
+        // Commands match the raw transcript, so post-processing can't rewrite them
+        let raw_text = text.clone();
+        let command = self.router.route_command(&raw_text);
 
-        // Route
-        let intent = self.router.route(&text)?;
-        match &intent {
-            Intent::Dictate(t) => log::debug!("Router → Dictate({} chars)", t.len()),
-            Intent::Command { action, .. } => log::info!("Router → Command({})", action),
+        // Post-process
+        let mut text = text;
+        if command.is_none() {
+            for stage in &self.postprocess {
+                text = stage.process_in(&text, language.as_deref());
+                log::debug!("{} → {:?}", stage.name(), text);
+            }
+            if text.is_empty() {
+                log::info!("Post-processing left no text, skipping");
+                return Ok(());
+            }
         }
 
-        // Execute
-        self.action.execute(&intent)?;
+        // Route
+        let mut routed = None;
+        let intent = match command {
+            Some(intent) => Ok(intent),
+            None => self.router.route(&text),
+        };
+        let result = intent.and_then(|intent| {
+            let intent = match intent {
+                Intent::Dictate(t) if !separator.is_empty() => Intent::Dictate(format!("{separator}{t}")),
+                intent => intent,
+            };
+            match &intent {
+                Intent::Dictate(t) => log::debug!("Router → Dictate({} chars)", t.len()),
+                Intent::Command { action, .. } => log::info!("Router → Command({})", action),
+            }
+
+            // Execute
+            let result = match &intent {
+                Intent::Command { action, .. } if action == UNDO_COMMAND => self.undo().map(|_| ()),
+                _ => self.action.execute(&intent),
+            };
+            routed = Some(intent);
+            result
+        });
+
+        // Record
+        if let Some(history) = &self.history {
+            let timestamp_ms = crate::history::now_ms();
+            let entry = HistoryEntry {
+                timestamp_ms,
+                stt_backend: self.stt.name().to_string(),
+                language,
+                stt_secs: stt_elapsed,
+                total_secs: start.elapsed().as_secs_f64(),
+                raw_text,
+                text,
+                intent: routed,
+                error: result.as_ref().err().map(|e| format!("{e:#}")),
+                audio: if samples.is_empty() { None } else { history.save_audio(samples, STT_SAMPLE_RATE, timestamp_ms) },
+            };
+            if let Err(e) = history.append(&entry) {
+                log::warn!("Failed to write history to {:?}: {e}", history.path());
+            }
+        }
+        result?;
 
         log::info!("Pipeline complete in {:.1}s", start.elapsed().as_secs_f64());
         Ok(())
     }
+
+    /// Revert the last undoable action ("scratch that" or the undo hotkey).
+    /// Returns `false` if there was nothing to undo.
+    pub fn undo(&self) -> anyhow::Result<bool> {
+        let undone = self.action.undo()?;
+        if undone {
+            log::info!("Undid last {} action", self.action.name());
+        } else {
+            log::info!("Nothing to undo ({})", self.action.name());
+        }
+        Ok(undone)
+    }
 }
 
 #[cfg(test)]
@@ -161,15 +327,65 @@ mod tests {
         fn name(&self) -> &str { "mock" }
     }
 
+    #[test]
+    fn undo_command_reverts_last_action() {
+        struct CommandRouter;
+        impl IntentRouter for CommandRouter {
+            fn route(&self, text: &str) -> anyhow::Result<Intent> {
+                Ok(match text {
+                    "scratch that" => Intent::Command { action: "undo".into(), args: serde_json::json!({}) },
+                    _ => Intent::Dictate(text.to_string()),
+                })
+            }
+            fn name(&self) -> &str { "command" }
+        }
+        struct UndoAction {
+            typed: Arc<Mutex<Vec<String>>>,
+        }
+        impl ActionExecutor for UndoAction {
+            fn execute(&self, intent: &Intent) -> anyhow::Result<()> {
+                match intent {
+                    Intent::Dictate(t) => self.typed.lock().unwrap().push(t.clone()),
+                    Intent::Command { action, .. } => anyhow::bail!("unexpected command {action}"),
+                }
+                Ok(())
+            }
+            fn name(&self) -> &str { "undo" }
+            fn undo(&self) -> anyhow::Result<bool> {
+                Ok(self.typed.lock().unwrap().pop().is_some())
+            }
+        }
+
+        let typed = Arc::new(Mutex::new(vec![]));
+        let mut pipeline = Pipeline {
+            stt: Arc::new(MockTranscriber { response: "hello".into() }),
+            postprocess: vec![],
+            router: Box::new(CommandRouter),
+            action: Box::new(UndoAction { typed: typed.clone() }),
+            hallucination: None,
+            history: None,
+        };
+        pipeline.process_pcm(&[0.1], 16000).unwrap();
+        assert_eq!(*typed.lock().unwrap(), vec!["hello"]);
+
+        pipeline.stt = Arc::new(MockTranscriber { response: "scratch that".into() });
+        pipeline.process_pcm(&[0.1], 16000).unwrap();
+        assert!(typed.lock().unwrap().is_empty());
+        assert!(!pipeline.undo().unwrap());
+    }
+
     #[test]
     fn process_pcm_chains_stt_router_action() {
         let routed = Arc::new(Mutex::new(vec![]));
         let executed = Arc::new(Mutex::new(vec![]));
 
         let pipeline = Pipeline {
-            stt: Box::new(MockTranscriber { response: "hello world".into() }),
+            stt: Arc::new(MockTranscriber { response: "hello world".into() }),
+            postprocess: vec![],
             router: Box::new(MockRouter { routed: routed.clone() }),
             action: Box::new(MockAction { executed: executed.clone() }),
+            hallucination: None,
+            history: None,
         };
 
         pipeline.process_pcm(&[0.1, 0.2], 16000).unwrap();
@@ -178,15 +394,38 @@ mod tests {
         assert_eq!(&*executed.lock().unwrap(), &["hello world"]);
     }
 
+    #[test]
+    fn process_pcm_handles_long_multibyte_text() {
+        let executed = Arc::new(Mutex::new(vec![]));
+        // Byte 80 falls inside a character
+        let text = format!("a{}", "é".repeat(60));
+
+        let pipeline = Pipeline {
+            stt: Arc::new(MockTranscriber { response: text.clone() }),
+            postprocess: vec![],
+            router: Box::new(MockRouter { routed: Arc::new(Mutex::new(vec![])) }),
+            action: Box::new(MockAction { executed: executed.clone() }),
+            hallucination: None,
+            history: None,
+        };
+
+        pipeline.process_pcm(&[0.1], 16000).unwrap();
+
+        assert_eq!(&*executed.lock().unwrap(), &[text]);
+    }
+
     #[test]
     fn process_pcm_skips_empty_text() {
         let routed = Arc::new(Mutex::new(vec![]));
         let executed = Arc::new(Mutex::new(vec![]));
 
         let pipeline = Pipeline {
-            stt: Box::new(MockTranscriber { response: "".into() }),
+            stt: Arc::new(MockTranscriber { response: "".into() }),
+            postprocess: vec![],
             router: Box::new(MockRouter { routed: routed.clone() }),
             action: Box::new(MockAction { executed: executed.clone() }),
+            hallucination: None,
+            history: None,
         };
 
         pipeline.process_pcm(&[0.1], 16000).unwrap();
@@ -195,6 +434,216 @@ mod tests {
         assert!(executed.lock().unwrap().is_empty(), "action should not be called for empty text");
     }
 
+    #[test]
+    fn process_pcm_drops_hallucinated_text() {
+        let routed = Arc::new(Mutex::new(vec![]));
+        let mut pipeline = Pipeline {
+            stt: Arc::new(MockTranscriber { response: "Thanks for watching!".into() }),
+            postprocess: vec![],
+            router: Box::new(MockRouter { routed: routed.clone() }),
+            action: Box::new(MockAction { executed: Arc::new(Mutex::new(vec![])) }),
+            hallucination: HallucinationFilter::configured(&Default::default()),
+            history: None,
+        };
+        pipeline.process_pcm(&[0.1], 16000).unwrap();
+        assert!(routed.lock().unwrap().is_empty(), "blocklisted phrase should be dropped");
+
+        pipeline.stt = Arc::new(MockTranscriber { response: "Ship it. Thanks for watching!".into() });
+        pipeline.process_pcm(&[0.1], 16000).unwrap();
+        assert_eq!(*routed.lock().unwrap(), vec!["Ship it."]);
+    }
+
+    struct UppercaseStage;
+    impl TextProcessor for UppercaseStage {
+        fn process(&self, text: &str) -> String { text.to_uppercase() }
+        fn name(&self) -> &str { "upper" }
+    }
+
+    struct DropStage;
+    impl TextProcessor for DropStage {
+        fn process(&self, _: &str) -> String { String::new() }
+        fn name(&self) -> &str { "drop" }
+    }
+
+    #[test]
+    fn process_pcm_postprocesses_before_routing() {
+        let routed = Arc::new(Mutex::new(vec![]));
+        let executed = Arc::new(Mutex::new(vec![]));
+
+        let pipeline = Pipeline {
+            stt: Arc::new(MockTranscriber { response: "hello world".into() }),
+            postprocess: vec![Box::new(UppercaseStage)],
+            router: Box::new(MockRouter { routed: routed.clone() }),
+            action: Box::new(MockAction { executed: executed.clone() }),
+            hallucination: None,
+            history: None,
+        };
+
+        pipeline.process_pcm(&[0.1], 16000).unwrap();
+
+        assert_eq!(&*routed.lock().unwrap(), &["HELLO WORLD"]);
+        assert_eq!(&*executed.lock().unwrap(), &["HELLO WORLD"]);
+    }
+
+    #[test]
+    fn process_pcm_skips_text_emptied_by_postprocess() {
+        let routed = Arc::new(Mutex::new(vec![]));
+
+        let pipeline = Pipeline {
+            stt: Arc::new(MockTranscriber { response: "um".into() }),
+            postprocess: vec![Box::new(DropStage), Box::new(UppercaseStage)],
+            router: Box::new(MockRouter { routed: routed.clone() }),
+            action: Box::new(MockAction { executed: Arc::new(Mutex::new(vec![])) }),
+            hallucination: None,
+            history: None,
+        };
+
+        pipeline.process_pcm(&[0.1], 16000).unwrap();
+        assert!(routed.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn process_pcm_records_history() {
+        struct FailAction;
+        impl ActionExecutor for FailAction {
+            fn execute(&self, _: &Intent) -> anyhow::Result<()> { anyhow::bail!("no focus") }
+            fn name(&self) -> &str { "fail" }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let pipeline = Pipeline {
+            stt: Arc::new(MockTranscriber { response: "hello".into() }),
+            postprocess: vec![Box::new(UppercaseStage)],
+            router: Box::new(MockRouter { routed: Arc::new(Mutex::new(vec![])) }),
+            action: Box::new(FailAction),
+            hallucination: None,
+            history: Some(Arc::new(History::new(path.clone()))),
+        };
+
+        assert!(pipeline.process_pcm(&[0.1], 16000).is_err());
+
+        let entries = crate::history::load(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].stt_backend, "mock");
+        assert_eq!(entries[0].raw_text, "hello");
+        assert_eq!(entries[0].text, "HELLO");
+        assert_eq!(entries[0].intent, Some(Intent::Dictate("HELLO".into())));
+        assert_eq!(entries[0].error.as_deref(), Some("no focus"));
+        assert!(entries[0].audio.is_none());
+    }
+
+    #[test]
+    fn rules_commands_match_before_spoken_punctuation() {
+        let executed = Arc::new(Mutex::new(vec![]));
+        let mut pipeline = Pipeline {
+            stt: Arc::new(MockTranscriber { response: "New line.".into() }),
+            postprocess: vec![Box::new(crate::postprocess::punctuation::SpokenPunctuation::new("en"))],
+            router: Box::new(crate::router::rules::RulesRouter::from_json(
+                r#"{"commands": [{"phrases": ["new line"], "action": "key", "args": {"key": "enter"}}]}"#,
+            ).unwrap()),
+            action: Box::new(MockAction { executed: executed.clone() }),
+            hallucination: None,
+            history: None,
+        };
+
+        pipeline.process_pcm(&[0.1], 16000).unwrap();
+        assert_eq!(*executed.lock().unwrap(), vec!["key"]);
+
+        // Inside a dictation the phrase is still spoken punctuation
+        pipeline.stt = Arc::new(MockTranscriber { response: "one new line two".into() });
+        pipeline.process_pcm(&[0.1], 16000).unwrap();
+        assert_eq!(*executed.lock().unwrap(), vec!["key", "one\nTwo"]);
+    }
+
+    #[test]
+    fn process_pcm_reports_detected_language() {
+        struct GermanTranscriber;
+        impl Transcriber for GermanTranscriber {
+            fn transcribe(&self, _: &std::path::Path) -> anyhow::Result<String> { unreachable!() }
+            fn transcribe_pcm(&self, _: &[f32], _: u32) -> anyhow::Result<String> { unreachable!() }
+            fn transcribe_detailed(&self, _: &[f32], _: u32) -> anyhow::Result<crate::stt::Transcript> {
+                let mut t = crate::stt::Transcript::from_text("hallo komma welt".into(), 1.0);
+                t.language = Some("de".into());
+                Ok(t)
+            }
+            fn name(&self) -> &str { "german" }
+            fn is_available(&self) -> bool { true }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let routed = Arc::new(Mutex::new(vec![]));
+        let pipeline = Pipeline {
+            stt: Arc::new(GermanTranscriber),
+            postprocess: vec![Box::new(crate::postprocess::punctuation::SpokenPunctuation::detected("en"))],
+            router: Box::new(MockRouter { routed: routed.clone() }),
+            action: Box::new(MockAction { executed: Arc::new(Mutex::new(vec![])) }),
+            hallucination: None,
+            history: Some(Arc::new(History::new(path.clone()))),
+        };
+
+        pipeline.process_pcm(&[0.1], 16000).unwrap();
+
+        assert_eq!(*routed.lock().unwrap(), vec!["hallo, welt"]);
+        let entries = crate::history::load(&path).unwrap();
+        assert_eq!(entries[0].language.as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn process_pcm_keeps_audio_when_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let retention = crate::history::Retention {
+            max_bytes: u64::MAX,
+            max_age: std::time::Duration::from_secs(86_400),
+        };
+        let pipeline = Pipeline {
+            stt: Arc::new(MockTranscriber { response: "hello".into() }),
+            postprocess: vec![],
+            router: Box::new(MockRouter { routed: Arc::new(Mutex::new(vec![])) }),
+            action: Box::new(MockAction { executed: Arc::new(Mutex::new(vec![])) }),
+            hallucination: None,
+            history: Some(Arc::new(History::new(path.clone()).with_audio(retention))),
+        };
+
+        pipeline.process_pcm(&[0.25; 800], 8000).unwrap();
+
+        // Retained at the rate STT saw
+        let entries = crate::history::load(&path).unwrap();
+        let audio = crate::history::audio_path(&path, &entries[0]).unwrap();
+        let (samples, rate) = crate::history::flac::read(&audio).unwrap();
+        assert_eq!((samples.len(), rate), (1600, 16000));
+    }
+
+    #[test]
+    fn process_pcm_resamples_to_16k() {
+        struct RateTranscriber;
+        impl Transcriber for RateTranscriber {
+            fn transcribe(&self, _: &std::path::Path) -> anyhow::Result<String> { Ok(String::new()) }
+            fn transcribe_pcm(&self, samples: &[f32], sample_rate: u32) -> anyhow::Result<String> {
+                Ok(format!("{}@{}", samples.len(), sample_rate))
+            }
+            fn name(&self) -> &str { "rate" }
+            fn is_available(&self) -> bool { true }
+        }
+
+        let executed = Arc::new(Mutex::new(vec![]));
+        let pipeline = Pipeline {
+            stt: Arc::new(RateTranscriber),
+            postprocess: vec![],
+            router: Box::new(MockRouter { routed: Arc::new(Mutex::new(vec![])) }),
+            action: Box::new(MockAction { executed: executed.clone() }),
+            hallucination: None,
+            history: None,
+        };
+
+        pipeline.process_pcm(&[0.0; 4800], 48000).unwrap();
+        pipeline.process_pcm(&[0.0; 1600], 16000).unwrap();
+
+        assert_eq!(&*executed.lock().unwrap(), &["1600@16000", "1600@16000"]);
+    }
+
     #[test]
     fn process_pcm_calls_transcribe_pcm_not_transcribe() {
         use std::sync::atomic::{AtomicBool, Ordering};
@@ -219,12 +668,15 @@ mod tests {
         let pcm_called = Arc::new(AtomicBool::new(false));
         let file_called = Arc::new(AtomicBool::new(false));
         let pipeline = Pipeline {
-            stt: Box::new(TrackingTranscriber {
+            stt: Arc::new(TrackingTranscriber {
                 pcm_called: pcm_called.clone(),
                 file_called: file_called.clone(),
             }),
+            postprocess: vec![],
             router: Box::new(MockRouter { routed: Arc::new(Mutex::new(vec![])) }),
             action: Box::new(MockAction { executed: Arc::new(Mutex::new(vec![])) }),
+            hallucination: None,
+            history: None,
         };
 
         pipeline.process_pcm(&[0.1; 1600], 16000).unwrap();
@@ -232,6 +684,39 @@ mod tests {
         assert!(!file_called.load(Ordering::SeqCst), "transcribe (file) should NOT have been called");
     }
 
+    #[test]
+    fn computer_use_pipeline_shares_stt_and_history() {
+        let executed = Arc::new(Mutex::new(vec![]));
+        let factory_executed = executed.clone();
+        let factory = move |cfg: &ActionConfig| -> Option<anyhow::Result<Box<dyn ActionExecutor>>> {
+            (cfg.backend == "computer-use").then(|| {
+                Ok(Box::new(MockAction { executed: factory_executed.clone() }) as Box<dyn ActionExecutor>)
+            })
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let dictation = Pipeline {
+            stt: Arc::new(MockTranscriber { response: "open settings".into() }),
+            postprocess: vec![],
+            router: Box::new(MockRouter { routed: Arc::new(Mutex::new(vec![])) }),
+            action: Box::new(MockAction { executed: Arc::new(Mutex::new(vec![])) }),
+            hallucination: None,
+            history: Some(Arc::new(History::new(path.clone()))),
+        };
+        let mut cfg = Config::default();
+        cfg.postprocess.dictionary_path = Some("/nonexistent/dictionary.json".into());
+
+        let pipeline = Pipeline::computer_use_from_config(&cfg, &dictation, Some(&factory)).unwrap();
+        assert!(Arc::ptr_eq(&pipeline.stt, &dictation.stt));
+        // One instance, so appends and rotation from both pipelines are serialized
+        assert!(Arc::ptr_eq(pipeline.history.as_ref().unwrap(), dictation.history.as_ref().unwrap()));
+        assert_eq!(pipeline.router.name(), "goal");
+
+        pipeline.process_pcm(&[0.1], 16000).unwrap();
+        assert_eq!(*executed.lock().unwrap(), vec!["goal"]);
+        assert_eq!(crate::history::load(&path).unwrap().len(), 1);
+    }
+
     #[test]
     fn process_pcm_propagates_stt_error() {
         struct FailTranscriber;
@@ -243,9 +728,12 @@ mod tests {
         }
 
         let pipeline = Pipeline {
-            stt: Box::new(FailTranscriber),
+            stt: Arc::new(FailTranscriber),
+            postprocess: vec![],
             router: Box::new(MockRouter { routed: Arc::new(Mutex::new(vec![])) }),
             action: Box::new(MockAction { executed: Arc::new(Mutex::new(vec![])) }),
+            hallucination: None,
+            history: None,
         };
 
         let result = pipeline.process_pcm(&[0.1], 16000);
@@ -257,9 +745,12 @@ mod tests {
 
     fn make_shared(response: &str) -> SharedPipeline {
         SharedPipeline::new(Pipeline {
-            stt: Box::new(MockTranscriber { response: response.into() }),
+            stt: Arc::new(MockTranscriber { response: response.into() }),
+            postprocess: vec![],
             router: Box::new(MockRouter { routed: Arc::new(Mutex::new(vec![])) }),
             action: Box::new(MockAction { executed: Arc::new(Mutex::new(vec![])) }),
+            hallucination: None,
+            history: None,
         })
     }
 
@@ -276,9 +767,12 @@ mod tests {
         let old = sp.get();
 
         sp.swap(Pipeline {
-            stt: Box::new(MockTranscriber { response: "v2".into() }),
+            stt: Arc::new(MockTranscriber { response: "v2".into() }),
+            postprocess: vec![],
             router: Box::new(MockRouter { routed: Arc::new(Mutex::new(vec![])) }),
             action: Box::new(MockAction { executed: Arc::new(Mutex::new(vec![])) }),
+            hallucination: None,
+            history: None,
         });
 
         let new = sp.get();
@@ -313,15 +807,18 @@ mod tests {
             handles.push(thread::spawn(move || {
                 for j in 0..50 {
                     sp.swap(Pipeline {
-                        stt: Box::new(MockTranscriber {
+                        stt: Arc::new(MockTranscriber {
                             response: format!("w{i}-{j}"),
                         }),
+                        postprocess: vec![],
                         router: Box::new(MockRouter {
                             routed: Arc::new(Mutex::new(vec![])),
                         }),
                         action: Box::new(MockAction {
                             executed: Arc::new(Mutex::new(vec![])),
                         }),
+                        hallucination: None,
+                        history: None,
                     });
                 }
             }));
@@ -342,9 +839,12 @@ mod tests {
         let snapshot = sp.get();
 
         sp.swap(Pipeline {
-            stt: Box::new(MockTranscriber { response: "replaced".into() }),
+            stt: Arc::new(MockTranscriber { response: "replaced".into() }),
+            postprocess: vec![],
             router: Box::new(MockRouter { routed: Arc::new(Mutex::new(vec![])) }),
             action: Box::new(MockAction { executed: Arc::new(Mutex::new(vec![])) }),
+            hallucination: None,
+            history: None,
         });
 
         // snapshot still works with original pipeline
@@ -0,0 +1,243 @@
+//! User dictionary — fixes product names and identifiers the recognizer mangles.
+//!
+//! Loaded from `dictionary.json` next to config.json (or `postprocess.dictionary_path`):
+//!
+//! ```json
+//! {
+//!   "vocabulary": ["Kubernetes"],
+//!   "replace": [{ "heard": "vox control", "written": "voxctrl" }],
+//!   "case": ["GitHub", "PostgreSQL"],
+//!   "regex": [{ "pattern": "\\bticket (\\d+)", "replace": "TICKET-$1" }]
+//! }
+//! ```
+//!
+//! `replace` entries match whole words, ignoring case and spacing; `case`
+//! entries force a term's spelling wherever it appears in any case; `regex`
+//! entries run last, in order, with `$1`-style group references. Every
+//! written term plus `vocabulary` also forms the decoder prompt (see
+//! [`Dictionary::prompt`]) so recognition is biased toward them.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use regex::{NoExpand, Regex};
+use serde::Deserialize;
+
+use crate::config::{self, PostprocessConfig};
+
+#[derive(Deserialize)]
+struct DictionaryFile {
+    #[serde(default)]
+    vocabulary: Vec<String>,
+    #[serde(default)]
+    replace: Vec<ReplaceDef>,
+    #[serde(default)]
+    case: Vec<String>,
+    #[serde(default)]
+    regex: Vec<RegexDef>,
+}
+
+#[derive(Deserialize)]
+struct ReplaceDef {
+    heard: String,
+    written: String,
+}
+
+#[derive(Deserialize)]
+struct RegexDef {
+    pattern: String,
+    replace: String,
+}
+
+/// Compiled dictionary; applies its rules to every transcript.
+pub struct Dictionary {
+    /// Whole-word rules from `replace` and `case`, in file order.
+    words: Vec<(Regex, String)>,
+    patterns: Vec<(Regex, String)>,
+    /// Terms for the decoder prompt, deduplicated, in file order.
+    terms: Vec<String>,
+}
+
+/// Default dictionary location: `dictionary.json` next to config.json.
+pub fn default_dictionary_path() -> PathBuf {
+    config::config_path().with_file_name("dictionary.json")
+}
+
+impl Dictionary {
+    /// Load the configured dictionary, or `None` if the file doesn't exist.
+    pub fn configured(cfg: &PostprocessConfig) -> anyhow::Result<Option<Self>> {
+        let path = cfg.dictionary_path.clone().unwrap_or_else(default_dictionary_path);
+        if !path.exists() {
+            log::debug!("Dictionary: {:?} not found", path);
+            return Ok(None);
+        }
+        let dict = Self::load(&path)?;
+        log::info!(
+            "Dictionary: {} rules, {} terms from {:?}",
+            dict.words.len() + dict.patterns.len(),
+            dict.terms.len(),
+            path
+        );
+        Ok(Some(dict))
+    }
+
+    /// Load a dictionary file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("read dictionary {}", path.display()))?;
+        Self::from_json(&json).with_context(|| format!("parse dictionary {}", path.display()))
+    }
+
+    /// Build a dictionary from JSON.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let file: DictionaryFile = serde_json::from_str(json)?;
+        let mut words = Vec::new();
+        let mut terms: Vec<String> = Vec::new();
+        let mut add_term = |term: &str| {
+            if !term.trim().is_empty() && !terms.iter().any(|t| t == term) {
+                terms.push(term.to_string());
+            }
+        };
+
+        for def in &file.replace {
+            words.push((word_regex(&def.heard)?, def.written.clone()));
+            add_term(&def.written);
+        }
+        for term in &file.case {
+            words.push((word_regex(term)?, term.clone()));
+            add_term(term);
+        }
+        for term in &file.vocabulary {
+            add_term(term);
+        }
+        let patterns = file
+            .regex
+            .iter()
+            .map(|def| {
+                let re = Regex::new(&def.pattern)
+                    .with_context(|| format!("invalid regex {:?}", def.pattern))?;
+                Ok((re, def.replace.clone()))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self { words, patterns, terms })
+    }
+
+    /// Decoder prompt listing the dictionary's terms, or `None` if it has none.
+    pub fn prompt(&self) -> Option<String> {
+        if self.terms.is_empty() {
+            None
+        } else {
+            Some(format!("{}.", self.terms.join(", ")))
+        }
+    }
+}
+
+impl super::TextProcessor for Dictionary {
+    fn process(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (re, written) in &self.words {
+            text = re.replace_all(&text, NoExpand(written)).into_owned();
+        }
+        for (re, replace) in &self.patterns {
+            text = re.replace_all(&text, replace.as_str()).into_owned();
+        }
+        text
+    }
+
+    fn name(&self) -> &str {
+        "dictionary"
+    }
+}
+
+/// Case-insensitive whole-word pattern for a phrase, tolerant of extra spaces.
+fn word_regex(phrase: &str) -> anyhow::Result<Regex> {
+    let words: Vec<String> = phrase.split_whitespace().map(regex::escape).collect();
+    if words.is_empty() {
+        anyhow::bail!("empty dictionary phrase");
+    }
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let start = if is_word(phrase.trim().chars().next()) { r"\b" } else { "" };
+    let end = if is_word(phrase.trim().chars().last()) { r"\b" } else { "" };
+    Ok(Regex::new(&format!(r"(?i){start}{}{end}", words.join(r"\s+")))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TextProcessor;
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+        "vocabulary": ["Kubernetes"],
+        "replace": [
+            { "heard": "vox control", "written": "voxctrl" },
+            { "heard": "post gress", "written": "PostgreSQL" }
+        ],
+        "case": ["GitHub", "iOS"],
+        "regex": [{ "pattern": "\\bticket (\\d+)", "replace": "TICKET-$1" }]
+    }"#;
+
+    fn sample() -> Dictionary {
+        Dictionary::from_json(SAMPLE).unwrap()
+    }
+
+    #[test]
+    fn replaces_heard_phrases_ignoring_case_and_spacing() {
+        assert_eq!(sample().process("Vox  Control is running"), "voxctrl is running");
+        assert_eq!(sample().process("migrate to post gress."), "migrate to PostgreSQL.");
+    }
+
+    #[test]
+    fn replacement_matches_whole_words_only() {
+        assert_eq!(sample().process("voxcontrol post gressive"), "voxcontrol post gressive");
+    }
+
+    #[test]
+    fn case_rules_fix_spelling() {
+        assert_eq!(sample().process("push to github from IOS"), "push to GitHub from iOS");
+    }
+
+    #[test]
+    fn regex_rules_expand_groups() {
+        assert_eq!(sample().process("see ticket 42"), "see TICKET-42");
+    }
+
+    #[test]
+    fn written_text_is_not_expanded() {
+        let dict = Dictionary::from_json(r#"{"replace": [{"heard": "price", "written": "$1"}]}"#).unwrap();
+        assert_eq!(dict.process("the price"), "the $1");
+    }
+
+    #[test]
+    fn prompt_lists_terms_once() {
+        assert_eq!(sample().prompt().as_deref(), Some("voxctrl, PostgreSQL, GitHub, iOS, Kubernetes."));
+        assert!(Dictionary::from_json("{}").unwrap().prompt().is_none());
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        assert!(Dictionary::from_json(r#"{"regex": [{"pattern": "(", "replace": ""}]}"#).is_err());
+        assert!(Dictionary::from_json(r#"{"replace": [{"heard": " ", "written": "x"}]}"#).is_err());
+    }
+
+    #[test]
+    fn missing_file_is_none() {
+        let cfg = PostprocessConfig {
+            dictionary_path: Some("/nonexistent/dictionary.json".into()),
+            ..Default::default()
+        };
+        assert!(Dictionary::configured(&cfg).unwrap().is_none());
+    }
+
+    #[test]
+    fn load_from_file() {
+        let tmp = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        std::fs::write(tmp.path(), r#"{"case": ["voxctrl"]}"#).unwrap();
+        let cfg = PostprocessConfig {
+            dictionary_path: Some(tmp.path().to_path_buf()),
+            ..Default::default()
+        };
+        let dict = Dictionary::configured(&cfg).unwrap().unwrap();
+        assert_eq!(dict.process("VoxCtrl rocks"), "voxctrl rocks");
+    }
+}
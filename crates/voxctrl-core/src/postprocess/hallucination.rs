@@ -0,0 +1,360 @@
+//! Hallucination filter — drops transcript segments Whisper likely made up.
+//!
+//! On silence, noise or music Whisper tends to produce stock phrases from
+//! its training subtitles ("Thank you for watching.") or to loop on one
+//! phrase. The decoder guards in whisper-native stop the worst loops; this
+//! stage runs after STT for every backend and judges each segment on:
+//!
+//! - no-speech probability, together with a low average log-probability
+//!   (the rule Whisper itself uses to skip silent windows);
+//! - a very low average log-probability on its own;
+//! - compression ratio: repetitive text compresses far better than speech;
+//! - a blocklist of known phrases, removed when they make up or end a segment.
+//!
+//! zlib's ratio on ordinary prose rises with its length, so like Whisper the
+//! ratio is only taken over about 30 s of speech at a time: per segment,
+//! across ~30 s windows of segments, and per 600 bytes of any longer text.
+//!
+//! Confidence checks only apply to backends that report it (see
+//! [`Transcript`]); the text checks apply to all.
+
+use std::io::Write;
+
+use crate::config::{HallucinationConfig, PostprocessConfig};
+use crate::stt::{Transcript, TranscriptSegment};
+
+/// Texts shorter than this aren't judged on compression ratio: zlib's
+/// overhead dominates and short phrases can't loop much anyway.
+const MIN_COMPRESSION_CHARS: usize = 24;
+/// Longest text judged on compression ratio as a whole; longer text is
+/// judged in pieces. About 30 s of fast speech.
+const MAX_COMPRESSION_BYTES: usize = 600;
+/// Span of the windows of segments judged together on compression ratio,
+/// matching Whisper's 30 s window.
+const WINDOW_SECS: f64 = 30.0;
+
+pub struct HallucinationFilter {
+    cfg: HallucinationConfig,
+    /// Blocklist phrases as normalized words.
+    blocklist: Vec<Vec<String>>,
+}
+
+impl HallucinationFilter {
+    pub fn new(cfg: &HallucinationConfig) -> Self {
+        let blocklist = cfg
+            .blocklist
+            .iter()
+            .map(|phrase| words(phrase).into_iter().map(|(_, w)| w).collect::<Vec<_>>())
+            .filter(|phrase| !phrase.is_empty())
+            .collect();
+        Self { cfg: cfg.clone(), blocklist }
+    }
+
+    /// The filter configured in `postprocess.hallucination`, or `None` if disabled.
+    pub fn configured(cfg: &PostprocessConfig) -> Option<Self> {
+        cfg.hallucination.enabled.then(|| Self::new(&cfg.hallucination))
+    }
+
+    /// Text to keep from `transcript`; empty if all of it was rejected.
+    /// Every rejection is logged with its reason.
+    pub fn filter(&self, transcript: &Transcript) -> String {
+        let mut changed = false;
+        let mut kept: Vec<(&TranscriptSegment, String)> = Vec::with_capacity(transcript.segments.len());
+        for segment in &transcript.segments {
+            match self.filter_segment(segment) {
+                Ok(text) => {
+                    changed |= text != segment.text;
+                    kept.push((segment, text));
+                }
+                Err(reason) => {
+                    log::info!(
+                        "Hallucination filter: dropped {:.1}–{:.1}s {:?}: {reason}",
+                        segment.start_secs, segment.end_secs, segment.text,
+                    );
+                    changed = true;
+                }
+            }
+        }
+
+        // Loops spread over many short segments only show across segments.
+        let mut start = 0;
+        while start < kept.len() {
+            let window_start = kept[start].0.start_secs;
+            let len = kept[start..].iter().take_while(|(s, _)| s.end_secs - window_start <= WINDOW_SECS).count().max(1);
+            let end = start + len;
+            let text = join(kept[start..end].iter().map(|(_, t)| t.as_str()));
+            match self.too_repetitive(&text) {
+                Some(ratio) => {
+                    log::info!(
+                        "Hallucination filter: dropped {:.1}–{:.1}s {text:?}: repetitive (compression ratio {ratio:.1})",
+                        window_start, kept[end - 1].0.end_secs,
+                    );
+                    kept.drain(start..end);
+                    changed = true;
+                }
+                None => start = end,
+            }
+        }
+
+        // Keep the backend's own text (and spacing) when nothing was removed.
+        if changed {
+            join(kept.iter().map(|(_, t)| t.as_str()))
+        } else {
+            transcript.text.clone()
+        }
+    }
+
+    /// The segment's text with blocklisted phrases trimmed, or why it was
+    /// rejected outright.
+    fn filter_segment(&self, segment: &TranscriptSegment) -> Result<String, String> {
+        let cfg = &self.cfg;
+        if let (Some(no_speech), avg_logprob) = (segment.no_speech_prob, segment.avg_logprob) {
+            if no_speech > cfg.no_speech_threshold && avg_logprob.is_none_or(|p| p < cfg.logprob_threshold) {
+                return Err(match avg_logprob {
+                    Some(p) => format!("no speech (p={no_speech:.2}, avg log-prob {p:.2})"),
+                    None => format!("no speech (p={no_speech:.2})"),
+                });
+            }
+        }
+        if let Some(p) = segment.avg_logprob.filter(|&p| p < cfg.min_avg_logprob) {
+            return Err(format!("low confidence (avg log-prob {p:.2})"));
+        }
+        if let Some(ratio) = self.too_repetitive(&segment.text) {
+            return Err(format!("repetitive (compression ratio {ratio:.1})"));
+        }
+
+        let mut text = segment.text.clone();
+        while let Some(cut) = self.blocklisted_tail(&text) {
+            let phrase = text[cut..].trim().to_string();
+            text.truncate(cut);
+            let rest = text.trim_end();
+            if rest.is_empty() {
+                return Err(format!("blocklisted phrase {phrase:?}"));
+            }
+            log::info!("Hallucination filter: trimmed blocklisted phrase {phrase:?} from {:?}", segment.text);
+            text = rest.to_string();
+        }
+        Ok(text)
+    }
+
+    /// Byte offset where a blocklisted phrase ending `text` starts.
+    fn blocklisted_tail(&self, text: &str) -> Option<usize> {
+        let words = words(text);
+        self.blocklist.iter().find_map(|phrase| {
+            let start = words.len().checked_sub(phrase.len())?;
+            let tail = &words[start..];
+            tail.iter().map(|(_, w)| w).eq(phrase.iter()).then(|| tail[0].0)
+        })
+    }
+
+    /// The compression ratio of `text`, if above the threshold. Text longer
+    /// than [`MAX_COMPRESSION_BYTES`] is judged by its most repetitive piece.
+    fn too_repetitive(&self, text: &str) -> Option<f32> {
+        if text.chars().count() < MIN_COMPRESSION_CHARS {
+            return None;
+        }
+        let ratio = pieces(text, MAX_COMPRESSION_BYTES).map(compression_ratio).fold(0.0, f32::max);
+        (ratio > self.cfg.compression_ratio_threshold).then_some(ratio)
+    }
+}
+
+/// Non-empty texts, trimmed and joined with spaces.
+fn join<'a>(texts: impl Iterator<Item = &'a str>) -> String {
+    texts.map(str::trim).filter(|t| !t.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+/// `text` split at whitespace into pieces of at most `max_bytes` (longer
+/// only when a single word is).
+fn pieces(text: &str, max_bytes: usize) -> impl Iterator<Item = &str> {
+    let mut rest = text.trim();
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let cut = if rest.len() <= max_bytes {
+            rest.len()
+        } else {
+            // One byte past the limit, so a space right at it is found
+            let mut end = max_bytes + 1;
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            let head = &rest[..end];
+            head.rfind(char::is_whitespace).filter(|&i| i > 0).unwrap_or_else(|| {
+                rest.find(char::is_whitespace).unwrap_or(rest.len())
+            })
+        };
+        let (piece, tail) = rest.split_at(cut);
+        rest = tail.trim_start();
+        Some(piece)
+    })
+}
+
+/// Words of `text` as (byte offset, lower-cased alphanumerics), skipping
+/// words with no letters or digits ("—", "...").
+fn words(text: &str) -> Vec<(usize, String)> {
+    text.split_whitespace()
+        .filter_map(|w| {
+            let offset = w.as_ptr() as usize - text.as_ptr() as usize;
+            let norm: String = w.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect();
+            (!norm.is_empty()).then_some((offset, norm))
+        })
+        .collect()
+}
+
+/// Ratio of the UTF-8 length of `text` to its zlib-compressed length, as
+/// Whisper computes it. Ordinary speech scores under 2; loops score higher.
+pub fn compression_ratio(text: &str) -> f32 {
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    // Writing to a Vec can't fail
+    let _ = encoder.write_all(text.as_bytes());
+    let compressed = encoder.finish().map(|c| c.len()).unwrap_or(0);
+    text.len() as f32 / compressed.max(1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter() -> HallucinationFilter {
+        HallucinationFilter::new(&HallucinationConfig::default())
+    }
+
+    fn seg(text: &str, no_speech: Option<f32>, logprob: Option<f32>) -> TranscriptSegment {
+        TranscriptSegment {
+            text: text.into(),
+            start_secs: 0.0,
+            end_secs: 2.0,
+            avg_logprob: logprob,
+            no_speech_prob: no_speech,
+        }
+    }
+
+    fn run(segments: Vec<TranscriptSegment>) -> String {
+        filter().filter(&Transcript::from_segments(segments, None))
+    }
+
+    #[test]
+    fn confident_speech_is_kept() {
+        let t = Transcript::from_text("Meet me at  the station at noon.".into(), 2.0);
+        assert_eq!(filter().filter(&t), "Meet me at  the station at noon.");
+        assert_eq!(run(vec![seg("Hello there.", Some(0.1), Some(-0.3))]), "Hello there.");
+    }
+
+    #[test]
+    fn no_speech_needs_low_confidence_too() {
+        assert_eq!(run(vec![seg("Hmm.", Some(0.9), Some(-1.2))]), "");
+        // Confidently decoded despite a high no-speech probability
+        assert_eq!(run(vec![seg("Okay.", Some(0.9), Some(-0.4))]), "Okay.");
+        // Backend reports no-speech only
+        assert_eq!(run(vec![seg("Hmm.", Some(0.9), None)]), "");
+    }
+
+    #[test]
+    fn low_confidence_segment_is_dropped() {
+        let text = run(vec![seg("Send the report.", Some(0.1), Some(-0.2)), seg("Blorf snee.", Some(0.2), Some(-2.1))]);
+        assert_eq!(text, "Send the report.");
+    }
+
+    #[test]
+    fn repetitive_text_is_dropped() {
+        let looped = "I'm going to go to the store. ".repeat(8);
+        assert!(compression_ratio(&looped) > 2.4);
+        assert_eq!(run(vec![seg(&looped, None, None)]), "");
+
+        // A loop split across many short segments
+        let segments = (0..10).map(|_| seg("I'm sorry.", None, None)).collect();
+        assert_eq!(run(segments), "");
+
+        assert!(compression_ratio("The quick brown fox jumps over the lazy dog near the river bank.") < 2.0);
+    }
+
+    /// Minutes of varied sentences, five seconds each, drawn from a small vocabulary.
+    fn long_dictation() -> Vec<TranscriptSegment> {
+        const WORDS: &[&str] = &[
+            "the", "we", "team", "build", "ship", "review", "meeting", "plan", "budget", "quarter", "design",
+            "user", "report", "feature", "bug", "fix", "test", "deploy", "server", "client", "data", "model",
+            "change", "update", "release", "note", "week", "month", "call", "email", "project", "goal", "risk",
+            "issue", "task", "sprint", "board", "customer", "feedback", "support", "price", "market", "sales",
+            "growth", "cost", "time", "schedule", "office", "remote", "hire", "launch", "demo", "draft",
+        ];
+        let mut x: u64 = 12345;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x as usize
+        };
+        (0..60)
+            .map(|i| {
+                let n = 8 + next() % 6;
+                let text = (0..n).map(|_| WORDS[next() % WORDS.len()]).collect::<Vec<_>>().join(" ") + ".";
+                TranscriptSegment { start_secs: i as f64 * 5.0, end_secs: i as f64 * 5.0 + 5.0, ..seg(&text, None, None) }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn long_dictation_is_kept() {
+        let segments = long_dictation();
+        let transcript = Transcript::from_segments(segments.clone(), None);
+        // Repetitive by whole-text ratio, though no 30 s of it is
+        assert!(compression_ratio(&transcript.text) > 2.4);
+        assert_eq!(filter().filter(&transcript), transcript.text);
+
+        // Text-only backends report the whole dictation as one segment
+        let t = Transcript::from_text(transcript.text.clone(), 300.0);
+        assert_eq!(filter().filter(&t), transcript.text);
+    }
+
+    #[test]
+    fn loop_inside_long_dictation_is_dropped() {
+        let mut segments = long_dictation();
+        let looped: Vec<TranscriptSegment> = (0..10)
+            .map(|i| TranscriptSegment { start_secs: 300.0 + i as f64, end_secs: 301.0 + i as f64, ..seg("I'm sorry.", None, None) })
+            .collect();
+        let speech = Transcript::from_segments(segments.clone(), None).text;
+        segments.extend(looped);
+        assert_eq!(run(segments), speech);
+    }
+
+    #[test]
+    fn pieces_split_at_whitespace() {
+        let text = "aaa bbb ccc ddd";
+        assert_eq!(pieces(text, 7).collect::<Vec<_>>(), vec!["aaa bbb", "ccc ddd"]);
+        assert_eq!(pieces(text, 100).collect::<Vec<_>>(), vec![text]);
+        assert_eq!(pieces("abcdefgh ij", 4).collect::<Vec<_>>(), vec!["abcdefgh", "ij"]);
+        assert_eq!(pieces("  ", 4).count(), 0);
+    }
+
+    #[test]
+    fn blocklisted_phrases_are_dropped_or_trimmed() {
+        assert_eq!(run(vec![seg("Thank you for watching!", None, None)]), "");
+        assert_eq!(run(vec![seg("Let's ship it. Thanks for watching.", None, None)]), "Let's ship it.");
+        assert_eq!(
+            run(vec![seg("Done. Please subscribe. Thank you for watching.", None, None)]),
+            "Done.",
+        );
+        // Only at the end: quoted in the middle is real speech
+        assert_eq!(
+            run(vec![seg("He said thanks for watching and left.", None, None)]),
+            "He said thanks for watching and left.",
+        );
+    }
+
+    #[test]
+    fn custom_blocklist_is_normalized() {
+        let cfg = HallucinationConfig { blocklist: vec!["Sous-titres réalisés par".into()], ..Default::default() };
+        let f = HallucinationFilter::new(&cfg);
+        let t = Transcript::from_text("Bonjour. Sous-titres réalisés par".into(), 2.0);
+        assert_eq!(f.filter(&t), "Bonjour.");
+    }
+
+    #[test]
+    fn disabled_by_config() {
+        let mut cfg = PostprocessConfig::default();
+        assert!(HallucinationFilter::configured(&cfg).is_some());
+        cfg.hallucination.enabled = false;
+        assert!(HallucinationFilter::configured(&cfg).is_none());
+    }
+}
@@ -0,0 +1,91 @@
+//! Post-processing — text clean-up between STT and routing.
+//!
+//! Each enabled stage rewrites the transcript in turn; stages are built from
+//! config so they are rebuilt along with the rest of the pipeline.
+
+pub mod dictionary;
+pub mod hallucination;
+pub mod punctuation;
+
+use crate::config::Config;
+
+/// Trait for transcript post-processing stages.
+pub trait TextProcessor: Send + Sync {
+    /// Rewrite a transcript. Returning an empty string drops the utterance.
+    fn process(&self, text: &str) -> String;
+    /// Rewrite a transcript in `language`, as detected by the STT backend.
+    /// Stages that don't depend on the language ignore it.
+    fn process_in(&self, text: &str, _language: Option<&str>) -> String {
+        self.process(text)
+    }
+    fn name(&self) -> &str;
+}
+
+/// Create the enabled post-processing stages, in the order they run.
+pub fn create_processors(cfg: &Config) -> anyhow::Result<Vec<Box<dyn TextProcessor>>> {
+    let mut stages: Vec<Box<dyn TextProcessor>> = Vec::new();
+    if cfg.postprocess.spoken_punctuation {
+        let stage = match cfg.postprocess.language.as_deref().or(cfg.stt.fixed_language()) {
+            Some(lang) => punctuation::SpokenPunctuation::new(lang),
+            None if cfg.stt.detects_language() => punctuation::SpokenPunctuation::detected("en"),
+            None => punctuation::SpokenPunctuation::new("en"),
+        };
+        stages.push(Box::new(stage));
+    }
+    if let Some(dict) = dictionary::Dictionary::configured(&cfg.postprocess)? {
+        stages.push(Box::new(dict));
+    }
+    Ok(stages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_by_default() {
+        let mut cfg = Config::default();
+        cfg.postprocess.dictionary_path = Some("/nonexistent/dictionary.json".into());
+        assert!(create_processors(&cfg).unwrap().is_empty());
+    }
+
+    #[test]
+    fn spoken_punctuation_uses_stt_language() {
+        let mut cfg = Config::default();
+        cfg.postprocess.spoken_punctuation = true;
+        cfg.stt.whisper_language = Some("de".into());
+        cfg.postprocess.dictionary_path = Some("/nonexistent/dictionary.json".into());
+        let stages = create_processors(&cfg).unwrap();
+        assert_eq!(stages.len(), 1);
+        assert_eq!(stages[0].process("hallo komma welt"), "hallo, welt");
+    }
+
+    #[test]
+    fn spoken_punctuation_follows_detected_language() {
+        let mut cfg = Config::default();
+        cfg.postprocess.spoken_punctuation = true;
+        cfg.stt.whisper_language = Some("auto".into());
+        cfg.postprocess.dictionary_path = Some("/nonexistent/dictionary.json".into());
+        let stages = create_processors(&cfg).unwrap();
+        assert_eq!(stages[0].process_in("hallo komma welt", Some("de")), "hallo, welt");
+        assert_eq!(stages[0].process_in("hello comma world", Some("en")), "hello, world");
+        assert_eq!(stages[0].process_in("hello comma world", None), "hello, world");
+
+        // An explicit rule-table language wins over detection
+        cfg.postprocess.language = Some("en".into());
+        let stages = create_processors(&cfg).unwrap();
+        assert_eq!(stages[0].process_in("hallo komma welt", Some("de")), "hallo komma welt");
+    }
+
+    #[test]
+    fn dictionary_runs_after_punctuation() {
+        let tmp = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        std::fs::write(tmp.path(), r#"{"regex": [{"pattern": "(\\w+), (\\w+)", "replace": "$2, $1"}]}"#).unwrap();
+        let mut cfg = Config::default();
+        cfg.postprocess.spoken_punctuation = true;
+        cfg.postprocess.dictionary_path = Some(tmp.path().to_path_buf());
+        let stages = create_processors(&cfg).unwrap();
+        let text = stages.iter().fold("one comma two".to_string(), |t, s| s.process(&t));
+        assert_eq!(text, "two, one");
+    }
+}
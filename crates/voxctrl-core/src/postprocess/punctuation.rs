@@ -0,0 +1,406 @@
+//! Spoken punctuation — turns dictated formatting words into symbols.
+//!
+//! "hello comma world period" → "hello, world." Each language has its own
+//! rule table; phrases match case- and punctuation-insensitively, longest
+//! phrase first, so "question mark" wins over a lone "mark". Punctuation the
+//! recognizer guessed next to a spoken mark is replaced by the spoken one.
+//!
+//! Besides marks, every table has an all-caps span ("all caps NASA end
+//! caps") and a literal escape ("literal comma" types the word "comma").
+
+/// What a spoken mark turns into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mark {
+    /// Attaches to the preceding word: "," "." ")" closing quote.
+    Close(&'static str),
+    /// Attaches to the following word: "(" opening quote.
+    Open(&'static str),
+    /// Line or paragraph break; the next word is capitalized.
+    Break(&'static str),
+}
+
+/// Rule table for one language.
+struct Rules {
+    name: &'static str,
+    marks: &'static [(&'static str, Mark)],
+    caps_on: &'static str,
+    caps_off: &'static str,
+    literal: &'static str,
+}
+
+const EN: Rules = Rules {
+    name: "spoken-punctuation (en)",
+    marks: &[
+        ("comma", Mark::Close(",")),
+        ("period", Mark::Close(".")),
+        ("full stop", Mark::Close(".")),
+        ("question mark", Mark::Close("?")),
+        ("exclamation mark", Mark::Close("!")),
+        ("exclamation point", Mark::Close("!")),
+        ("colon", Mark::Close(":")),
+        ("semicolon", Mark::Close(";")),
+        ("semi colon", Mark::Close(";")),
+        ("ellipsis", Mark::Close("...")),
+        ("open quote", Mark::Open("\"")),
+        ("close quote", Mark::Close("\"")),
+        ("end quote", Mark::Close("\"")),
+        ("open paren", Mark::Open("(")),
+        ("open parenthesis", Mark::Open("(")),
+        ("close paren", Mark::Close(")")),
+        ("close parenthesis", Mark::Close(")")),
+        ("new line", Mark::Break("\n")),
+        ("new paragraph", Mark::Break("\n\n")),
+    ],
+    caps_on: "all caps",
+    caps_off: "end caps",
+    literal: "literal",
+};
+
+const DE: Rules = Rules {
+    name: "spoken-punctuation (de)",
+    marks: &[
+        ("komma", Mark::Close(",")),
+        ("punkt", Mark::Close(".")),
+        ("fragezeichen", Mark::Close("?")),
+        ("ausrufezeichen", Mark::Close("!")),
+        ("doppelpunkt", Mark::Close(":")),
+        ("semikolon", Mark::Close(";")),
+        ("anführungszeichen auf", Mark::Open("„")),
+        ("anführungszeichen zu", Mark::Close("“")),
+        ("klammer auf", Mark::Open("(")),
+        ("klammer zu", Mark::Close(")")),
+        ("neue zeile", Mark::Break("\n")),
+        ("neuer absatz", Mark::Break("\n\n")),
+    ],
+    caps_on: "alles groß",
+    caps_off: "ende groß",
+    literal: "wörtlich",
+};
+
+const FR: Rules = Rules {
+    name: "spoken-punctuation (fr)",
+    marks: &[
+        ("virgule", Mark::Close(",")),
+        ("point", Mark::Close(".")),
+        ("point final", Mark::Close(".")),
+        ("point d'interrogation", Mark::Close("?")),
+        ("point d'exclamation", Mark::Close("!")),
+        ("deux points", Mark::Close(":")),
+        ("deux-points", Mark::Close(":")),
+        ("point virgule", Mark::Close(";")),
+        ("point-virgule", Mark::Close(";")),
+        ("ouvrez les guillemets", Mark::Open("«\u{a0}")),
+        ("fermez les guillemets", Mark::Close("\u{a0}»")),
+        ("ouvrir la parenthèse", Mark::Open("(")),
+        ("fermer la parenthèse", Mark::Close(")")),
+        ("à la ligne", Mark::Break("\n")),
+        ("nouvelle ligne", Mark::Break("\n")),
+        ("nouveau paragraphe", Mark::Break("\n\n")),
+    ],
+    caps_on: "tout en majuscules",
+    caps_off: "fin des majuscules",
+    literal: "littéralement",
+};
+
+const ES: Rules = Rules {
+    name: "spoken-punctuation (es)",
+    marks: &[
+        ("coma", Mark::Close(",")),
+        ("punto", Mark::Close(".")),
+        ("punto y coma", Mark::Close(";")),
+        ("dos puntos", Mark::Close(":")),
+        ("signo de interrogación", Mark::Close("?")),
+        ("signo de exclamación", Mark::Close("!")),
+        ("abrir comillas", Mark::Open("«")),
+        ("cerrar comillas", Mark::Close("»")),
+        ("abrir paréntesis", Mark::Open("(")),
+        ("cerrar paréntesis", Mark::Close(")")),
+        ("nueva línea", Mark::Break("\n")),
+        ("nuevo párrafo", Mark::Break("\n\n")),
+    ],
+    caps_on: "todo mayúsculas",
+    caps_off: "fin mayúsculas",
+    literal: "literal",
+};
+
+/// Rule table for a language code such as "en" or "de-AT".
+fn rules_for(lang: &str) -> Option<&'static Rules> {
+    let primary = lang.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+    match primary.as_str() {
+        "en" => Some(&EN),
+        "de" => Some(&DE),
+        "fr" => Some(&FR),
+        "es" => Some(&ES),
+        _ => None,
+    }
+}
+
+/// Punctuation the recognizer may have guessed next to a spoken mark.
+const GUESSED: &[char] = &[',', '.', ';', ':', '!', '?'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Mark(Mark),
+    CapsOn,
+    CapsOff,
+    Literal,
+}
+
+/// Spoken-punctuation post-processor for one language, or for the language
+/// detected in each utterance.
+pub struct SpokenPunctuation {
+    rules: &'static Rules,
+    /// Normalized phrase words, longest phrase first.
+    phrases: Vec<(Vec<String>, Token)>,
+    /// Use the rule table of the language the STT backend detected, if any.
+    follow_detected: bool,
+}
+
+impl SpokenPunctuation {
+    /// Build the processor for `lang`, falling back to English if there is no table for it.
+    pub fn new(lang: &str) -> Self {
+        let rules = rules_for(lang).unwrap_or_else(|| {
+            log::warn!("No spoken-punctuation rules for language {lang:?}, using English");
+            &EN
+        });
+        let mut phrases: Vec<(Vec<String>, Token)> = rules
+            .marks
+            .iter()
+            .map(|&(phrase, mark)| (phrase, Token::Mark(mark)))
+            .chain([
+                (rules.caps_on, Token::CapsOn),
+                (rules.caps_off, Token::CapsOff),
+                (rules.literal, Token::Literal),
+            ])
+            .map(|(phrase, token)| (phrase.split_whitespace().map(normalize).collect(), token))
+            .collect();
+        phrases.sort_by_key(|(words, _)| std::cmp::Reverse(words.len()));
+        Self { rules, phrases, follow_detected: false }
+    }
+
+    /// Build a processor that follows the detected language, using `fallback`
+    /// when none was detected or there is no table for it.
+    pub fn detected(fallback: &str) -> Self {
+        Self { follow_detected: true, ..Self::new(fallback) }
+    }
+
+    /// Longest phrase matching the start of `keys`, with its word count.
+    fn match_at(&self, keys: &[String]) -> Option<(usize, Token)> {
+        self.phrases
+            .iter()
+            .find(|(words, _)| keys.starts_with(words))
+            .map(|(words, token)| (words.len(), *token))
+    }
+}
+
+impl super::TextProcessor for SpokenPunctuation {
+    fn process(&self, text: &str) -> String {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let keys: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+        let mut out = Writer::default();
+        let mut i = 0;
+        while i < words.len() {
+            match self.match_at(&keys[i..]) {
+                Some((n, Token::Literal)) if i + n < words.len() => {
+                    out.word(words[i + n]);
+                    i += n + 1;
+                }
+                Some((n, Token::CapsOn)) => {
+                    out.caps = true;
+                    i += n;
+                }
+                Some((n, Token::CapsOff)) => {
+                    out.caps = false;
+                    i += n;
+                }
+                Some((n, Token::Mark(mark))) => {
+                    out.mark(mark);
+                    i += n;
+                }
+                _ => {
+                    out.word(words[i]);
+                    i += 1;
+                }
+            }
+        }
+        out.text
+    }
+
+    fn process_in(&self, text: &str, language: Option<&str>) -> String {
+        let switch = language
+            .filter(|_| self.follow_detected)
+            .filter(|&lang| rules_for(lang).is_some_and(|rules| !std::ptr::eq(rules, self.rules)));
+        match switch {
+            Some(lang) => Self::new(lang).process(text),
+            None => self.process(text),
+        }
+    }
+
+    fn name(&self) -> &str {
+        if self.follow_detected {
+            "spoken-punctuation (detected)"
+        } else {
+            self.rules.name
+        }
+    }
+}
+
+/// Output builder tracking spacing and capitalization between words.
+#[derive(Default)]
+struct Writer {
+    text: String,
+    /// Next word attaches without a space (after an opening mark or break).
+    glue: bool,
+    /// Next word starts a sentence or line.
+    capitalize: bool,
+    caps: bool,
+}
+
+impl Writer {
+    fn word(&mut self, word: &str) {
+        if !self.glue && !self.text.is_empty() {
+            self.text.push(' ');
+        }
+        if self.caps {
+            self.text.push_str(&word.to_uppercase());
+        } else if self.capitalize {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                self.text.extend(first.to_uppercase());
+                self.text.push_str(chars.as_str());
+            }
+        } else {
+            self.text.push_str(word);
+        }
+        self.glue = false;
+        self.capitalize = false;
+    }
+
+    fn mark(&mut self, mark: Mark) {
+        match mark {
+            Mark::Close(p) => {
+                let len = self.text.trim_end_matches(GUESSED).len();
+                self.text.truncate(len);
+                self.text.push_str(p);
+                self.glue = false;
+                self.capitalize = p.ends_with(['.', '?', '!']);
+            }
+            Mark::Open(p) => {
+                if !self.glue && !self.text.is_empty() {
+                    self.text.push(' ');
+                }
+                self.text.push_str(p);
+                self.glue = true;
+            }
+            Mark::Break(p) => {
+                let len = self.text.trim_end_matches(' ').len();
+                self.text.truncate(len);
+                self.text.push_str(p);
+                self.glue = true;
+                self.capitalize = true;
+            }
+        }
+    }
+}
+
+/// Lowercase a word and drop everything but letters, digits and apostrophes.
+fn normalize(word: &str) -> String {
+    word.chars()
+        .map(|c| if c == '’' { '\'' } else { c })
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TextProcessor;
+    use super::*;
+
+    fn en(text: &str) -> String {
+        SpokenPunctuation::new("en").process(text)
+    }
+
+    #[test]
+    fn marks_attach_to_previous_word() {
+        assert_eq!(en("hello comma world period how are you"), "hello, world. How are you");
+        assert_eq!(en("really question mark yes exclamation point"), "really? Yes!");
+    }
+
+    #[test]
+    fn spoken_mark_replaces_guessed_punctuation() {
+        assert_eq!(en("Hello, comma, world."), "Hello, world.");
+        assert_eq!(en("Wait. Question mark."), "Wait?");
+    }
+
+    #[test]
+    fn breaks_capitalize_next_line() {
+        assert_eq!(en("First line. New line. second line"), "First line.\nSecond line");
+        assert_eq!(en("one new paragraph two"), "one\n\nTwo");
+    }
+
+    #[test]
+    fn quotes_and_parens_wrap_words() {
+        assert_eq!(en("he said open quote hi there close quote"), "he said \"hi there\"");
+        assert_eq!(en("Open quote, hi, close quote."), "\"hi\"");
+        assert_eq!(en("see open paren below close paren"), "see (below)");
+    }
+
+    #[test]
+    fn all_caps_span() {
+        assert_eq!(en("use all caps nasa data end caps now"), "use NASA DATA now");
+    }
+
+    #[test]
+    fn literal_escapes_next_word() {
+        assert_eq!(en("type the word literal comma here"), "type the word comma here");
+        assert_eq!(en("trailing literal"), "trailing literal");
+    }
+
+    #[test]
+    fn plain_text_is_unchanged() {
+        assert_eq!(en("Nothing to see here."), "Nothing to see here.");
+        assert_eq!(en(""), "");
+    }
+
+    #[test]
+    fn longest_phrase_wins() {
+        let fr = SpokenPunctuation::new("fr");
+        assert_eq!(fr.process("ça va point d'interrogation"), "ça va?");
+        assert_eq!(fr.process("un point virgule deux point"), "un; deux.");
+        let es = SpokenPunctuation::new("es");
+        assert_eq!(es.process("uno punto y coma dos"), "uno; dos");
+    }
+
+    #[test]
+    fn language_specific_quotes() {
+        let de = SpokenPunctuation::new("de");
+        assert_eq!(
+            de.process("er sagte Anführungszeichen auf hallo Anführungszeichen zu"),
+            "er sagte „hallo“"
+        );
+        let fr = SpokenPunctuation::new("fr");
+        assert_eq!(
+            fr.process("ouvrez les guillemets bonjour fermez les guillemets"),
+            "«\u{a0}bonjour\u{a0}»"
+        );
+    }
+
+    #[test]
+    fn language_tags_and_fallback() {
+        assert_eq!(SpokenPunctuation::new("en-US").name(), "spoken-punctuation (en)");
+        assert_eq!(SpokenPunctuation::new("de_AT").name(), "spoken-punctuation (de)");
+        assert_eq!(SpokenPunctuation::new("xx").process("a comma b"), "a, b");
+    }
+
+    #[test]
+    fn detected_language_picks_table() {
+        let auto = SpokenPunctuation::detected("en");
+        assert_eq!(auto.process_in("ça va point d'interrogation", Some("fr")), "ça va?");
+        assert_eq!(auto.process_in("hello comma world", Some("en")), "hello, world");
+        // No table for Japanese: the fallback applies
+        assert_eq!(auto.process_in("a comma b", Some("ja")), "a, b");
+        // A fixed-language processor ignores detection
+        assert_eq!(SpokenPunctuation::new("en").process_in("hallo komma welt", Some("de")), "hallo komma welt");
+    }
+}
@@ -1,16 +1,26 @@
 //! Recording state machine — shared between GUI and TUI.
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
 use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
+use crate::audio::ring::Liveness;
+use crate::audio::Capture;
 use crate::config::Config;
-use crate::pipeline::SharedPipeline;
+use crate::pipeline::{Pipeline, SharedPipeline};
+use crate::stt::SessionUpdate;
+use crate::vad::segmenter::UtteranceSegmenter;
 use crate::{AppStatus, SharedState};
 
+/// How often worker threads drain the capture ring buffer.
+const CAPTURE_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
 /// Toggle the recording state: Idle → Recording → Transcribing → (back to Idle).
 ///
-/// - **Idle → Recording**: clears buffered chunks, sets status to Recording.
-/// - **Recording → Transcribing → Idle**: drains chunks, spawns a transcription
-///   thread (or returns to Idle immediately if no audio was captured).
+/// - **Idle → Recording**: see [`start_recording`].
+/// - **Recording → Transcribing → Idle**: see [`stop_recording`].
 /// - **Transcribing → (ignored)**: toggle is a no-op while a transcription is
 ///   already in flight.
 pub fn toggle_recording(
@@ -18,50 +28,330 @@ pub fn toggle_recording(
     cfg: &Config,
     pipeline: &Arc<SharedPipeline>,
 ) {
-    let current = *state.status.lock().unwrap();
-    match current {
-        AppStatus::Idle => {
-            state.chunks.lock().unwrap().clear();
-            *state.status.lock().unwrap() = AppStatus::Recording;
-            log::info!("Recording started");
-        }
-        AppStatus::Recording => {
-            *state.status.lock().unwrap() = AppStatus::Transcribing;
-            log::info!("Recording stopped, transcribing…");
-
-            let chunks: Vec<f32> = state.chunks.lock().unwrap().drain(..).collect();
-            if chunks.is_empty() {
-                log::info!("No audio captured, returning to idle");
-                *state.status.lock().unwrap() = AppStatus::Idle;
-                return;
-            }
-
-            let state_clone = state.clone();
-            let sample_rate = cfg.audio.sample_rate;
-            // Snapshot the current pipeline — in-flight transcription keeps it alive
-            let snap = pipeline.get();
-            std::thread::Builder::new()
-                .name("transcription".into())
-                .spawn(move || {
-                    if let Err(e) = snap.process_pcm(&chunks, sample_rate) {
-                        log::error!("Pipeline error: {e}");
-                    }
-                    *state_clone.status.lock().unwrap() = AppStatus::Idle;
-                    log::info!("Back to idle");
-                })
-                .expect("spawn transcription thread");
-        }
+    match state.status.load() {
+        AppStatus::Idle => start_recording(state, cfg, pipeline),
+        AppStatus::Recording => stop_recording(state, cfg, pipeline),
         AppStatus::Transcribing => {
             log::debug!("Ignoring toggle — already transcribing");
         }
     }
 }
 
+/// Idle → Recording: clears buffered chunks and starts capturing.
+///
+/// With `stt.streaming` set, a streaming thread transcribes and types the
+/// recording while it is captured (see [`spawn_streaming`]), unless the
+/// pipeline's action needs the whole utterance at once (computer use).
+///
+/// No-op in any other state, so repeated presses (e.g. key auto-repeat while
+/// a push-to-talk hotkey is held) are harmless.
+pub fn start_recording(state: &Arc<SharedState>, cfg: &Config, pipeline: &Arc<SharedPipeline>) {
+    if cfg.vad.hands_free {
+        log::debug!("Ignoring start — hands-free mode segments utterances automatically");
+        return;
+    }
+    let status = state.status.load();
+    if status != AppStatus::Idle {
+        log::debug!("Ignoring start — status is {:?}", status);
+        return;
+    }
+    // Drop audio left over from the end of the previous recording
+    if let Some(capture) = state.capture.lock().unwrap().as_mut() {
+        capture.reset();
+    }
+    state.chunks.lock().unwrap().clear();
+    if !state.status.transition(AppStatus::Idle, AppStatus::Recording) {
+        log::debug!("Ignoring start — status changed concurrently");
+        return;
+    }
+    log::info!("Recording started");
+    let pipeline = pipeline.get();
+    if cfg.stt.streaming && pipeline.action.streams() {
+        state.streaming.store(true, Ordering::SeqCst);
+        if let Err(e) = spawn_streaming(state.clone(), pipeline) {
+            log::error!("Failed to spawn streaming thread: {e}");
+            state.streaming.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Recording → Transcribing → Idle: drains chunks and spawns a transcription
+/// thread (or returns to Idle immediately if no audio was captured). A
+/// streaming recording is left to its streaming thread, which finishes the
+/// session and returns to Idle.
+///
+/// No-op unless currently recording.
+pub fn stop_recording(
+    state: &Arc<SharedState>,
+    cfg: &Config,
+    pipeline: &Arc<SharedPipeline>,
+) {
+    if cfg.vad.hands_free {
+        log::debug!("Ignoring stop — hands-free mode segments utterances automatically");
+        return;
+    }
+    if !state.status.transition(AppStatus::Recording, AppStatus::Transcribing) {
+        log::debug!("Ignoring stop — status is {:?}", state.status.load());
+        return;
+    }
+    log::info!("Recording stopped, transcribing…");
+    if state.streaming.load(Ordering::SeqCst) {
+        return;
+    }
+
+    finish_capture(state);
+    let chunks: Vec<f32> = std::mem::take(&mut *state.chunks.lock().unwrap());
+    if chunks.is_empty() {
+        log::info!("No audio captured, returning to idle");
+        state.status.store(AppStatus::Idle);
+        return;
+    }
+
+    let state_clone = state.clone();
+    let sample_rate = state.sample_rate.load(Ordering::Relaxed);
+    // Snapshot the current pipeline — in-flight transcription keeps it alive
+    let snap = pipeline.get();
+    std::thread::Builder::new()
+        .name("transcription".into())
+        .spawn(move || {
+            if let Err(e) = snap.process_pcm(&chunks, sample_rate) {
+                log::error!("Pipeline error: {e}");
+            }
+            state_clone.status.store(AppStatus::Idle);
+            log::info!("Back to idle");
+        })
+        .expect("spawn transcription thread");
+}
+
+/// Move captured audio from the ring buffer, through bleed rejection and the
+/// capture filters, into `state.chunks`, capped at `state.max_chunks` samples.
+///
+/// Called periodically by the recorder thread; [`finish_capture`] collects
+/// the rest when a recording stops.
+pub fn collect_capture(state: &SharedState) {
+    collect(state, false);
+}
+
+/// Collect the tail captured since the recorder thread's last pass, plus the
+/// audio the capture filters still hold back, so the end of the utterance
+/// isn't lost.
+pub fn finish_capture(state: &SharedState) {
+    collect(state, true);
+}
+
+fn collect(state: &SharedState, flush: bool) {
+    let mut capture = state.capture.lock().unwrap();
+    let Some(capture) = capture.as_mut() else { return };
+    let dropped = capture.consumer.take_dropped();
+    if dropped > 0 {
+        log::warn!("Audio capture overflowed — dropped {dropped} samples");
+    }
+
+    let mut chunks = state.chunks.lock().unwrap();
+    let before = chunks.len();
+    capture.read_into(&mut chunks);
+    if flush {
+        capture.flush_into(&mut chunks);
+    }
+    let max = state.max_chunks.load(Ordering::Relaxed);
+    if chunks.len() > max {
+        chunks.truncate(max);
+        if before < max {
+            log::warn!(
+                "Recording reached the {:.0}s limit — ignoring further audio",
+                max as f64 / state.sample_rate.load(Ordering::Relaxed) as f64,
+            );
+        }
+    }
+}
+
+/// Spawn the recorder thread, which keeps the capture ring buffer drained
+/// into `state.chunks` until the stream behind `liveness` is torn down.
+pub fn spawn_recorder(state: Arc<SharedState>, liveness: Liveness) -> std::io::Result<JoinHandle<()>> {
+    std::thread::Builder::new()
+        .name("recorder".into())
+        .spawn(move || {
+            while liveness.is_connected() {
+                collect_capture(&state);
+                std::thread::sleep(CAPTURE_POLL_INTERVAL);
+            }
+            log::debug!("Recorder thread stopped");
+        })
+}
+
+/// Spawn the thread that feeds a hotkey recording through the STT backend's
+/// incremental session and types each finalized piece as it arrives, so long
+/// dictations appear progressively. Once the recording stops it finishes the
+/// session and returns to Idle.
+fn spawn_streaming(state: Arc<SharedState>, pipeline: Arc<Pipeline>) -> std::io::Result<JoinHandle<()>> {
+    std::thread::Builder::new()
+        .name("streaming".into())
+        .spawn(move || {
+            if let Err(e) = stream_recording(&state, &pipeline) {
+                log::error!("Pipeline error: {e}");
+            }
+            state.streaming.store(false, Ordering::SeqCst);
+            state.status.store(AppStatus::Idle);
+            log::info!("Back to idle");
+        })
+}
+
+fn stream_recording(state: &SharedState, pipeline: &Pipeline) -> anyhow::Result<()> {
+    let start = Instant::now();
+    // Started on the first audio, once the capture rate is known
+    let mut session = None;
+    let mut fed = 0;
+    let mut typed = false;
+    let mut emit = |updates: Vec<SessionUpdate>| -> anyhow::Result<()> {
+        for update in updates {
+            match update {
+                SessionUpdate::Final(text) => {
+                    pipeline.process_final(start, text, typed)?;
+                    typed = true;
+                }
+                SessionUpdate::Partial(text) => log::debug!("Partial: {text:?}"),
+            }
+        }
+        Ok(())
+    };
+    loop {
+        let recording = state.status.load() == AppStatus::Recording;
+        if !recording {
+            finish_capture(state);
+        }
+        let new = state.chunks.lock().unwrap()[fed..].to_vec();
+        fed += new.len();
+        if !new.is_empty() {
+            if session.is_none() {
+                session = Some(pipeline.stt.start_session(state.sample_rate.load(Ordering::Relaxed))?);
+            }
+            emit(session.as_mut().unwrap().push_pcm(&new)?)?;
+        }
+        if !recording {
+            break;
+        }
+        if new.is_empty() {
+            std::thread::sleep(CAPTURE_POLL_INTERVAL);
+        }
+    }
+    match session.as_mut() {
+        Some(session) => emit(session.finish()?),
+        None => {
+            log::info!("No audio captured, returning to idle");
+            Ok(())
+        }
+    }
+}
+
+/// Spawn the hands-free listener thread.
+///
+/// Mono audio read from `capture` is filtered and fed through `segmenter`; each completed
+/// utterance is handed to a transcription thread, which pushes it through the
+/// current pipeline, so the listener keeps draining the capture ring however
+/// long STT takes. Status: Recording while an utterance is open, Transcribing
+/// while utterances are queued or processed, Idle otherwise. The thread exits
+/// (flushing any open utterance and waiting for queued ones) once the
+/// producer is dropped, i.e. when the capture stream is torn down.
+pub fn spawn_hands_free(
+    state: Arc<SharedState>,
+    pipeline: Arc<SharedPipeline>,
+    mut segmenter: UtteranceSegmenter,
+    mut capture: Capture,
+    sample_rate: u32,
+) -> std::io::Result<JoinHandle<()>> {
+    let (tx, rx) = mpsc::channel::<Vec<f32>>();
+    // Utterances queued or being transcribed
+    let pending = Arc::new(AtomicUsize::new(0));
+    let worker = spawn_utterance_worker(state.clone(), pipeline, rx, pending.clone(), sample_rate)?;
+    std::thread::Builder::new()
+        .name("hands-free".into())
+        .spawn(move || {
+            log::info!("Hands-free listener started");
+            let queue = |utterance: Vec<f32>| {
+                pending.fetch_add(1, Ordering::SeqCst);
+                log::info!(
+                    "Utterance captured ({:.2}s), transcribing…",
+                    utterance.len() as f64 / sample_rate as f64,
+                );
+                // The worker only exits once this sender is dropped
+                let _ = tx.send(utterance);
+            };
+            let mut chunk = Vec::new();
+            loop {
+                // Checked before reading so samples pushed just before teardown aren't lost
+                let abandoned = capture.consumer.is_abandoned();
+                chunk.clear();
+                capture.read_into(&mut chunk);
+                let dropped = capture.consumer.take_dropped();
+                if dropped > 0 {
+                    log::warn!("Audio capture overflowed — dropped {dropped} samples");
+                }
+                for utterance in segmenter.push(&chunk) {
+                    queue(utterance);
+                }
+                let status = if segmenter.in_speech() {
+                    AppStatus::Recording
+                } else if pending.load(Ordering::SeqCst) > 0 {
+                    AppStatus::Transcribing
+                } else {
+                    AppStatus::Idle
+                };
+                state.status.store(status);
+                if chunk.is_empty() {
+                    if abandoned {
+                        break;
+                    }
+                    std::thread::sleep(CAPTURE_POLL_INTERVAL);
+                }
+            }
+            chunk.clear();
+            capture.flush_into(&mut chunk);
+            for utterance in segmenter.push(&chunk) {
+                queue(utterance);
+            }
+            if let Some(utterance) = segmenter.flush() {
+                queue(utterance);
+            }
+            drop(tx);
+            if worker.join().is_err() {
+                log::error!("Hands-free transcription thread panicked");
+            }
+            state.status.store(AppStatus::Idle);
+            log::info!("Hands-free listener stopped");
+        })
+}
+
+/// Spawn the thread that transcribes hands-free utterances in order as they
+/// arrive on `utterances`, until the listener drops the sender.
+fn spawn_utterance_worker(
+    state: Arc<SharedState>,
+    pipeline: Arc<SharedPipeline>,
+    utterances: mpsc::Receiver<Vec<f32>>,
+    pending: Arc<AtomicUsize>,
+    sample_rate: u32,
+) -> std::io::Result<JoinHandle<()>> {
+    std::thread::Builder::new()
+        .name("transcription".into())
+        .spawn(move || {
+            for samples in utterances {
+                if let Err(e) = pipeline.get().process_pcm(&samples, sample_rate) {
+                    log::error!("Pipeline error: {e}");
+                }
+                if pending.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    // Unless the listener has since opened a new utterance
+                    state.status.transition(AppStatus::Transcribing, AppStatus::Idle);
+                }
+            }
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::sync::{Arc, Mutex};
 
+    use crate::audio::filter::FilterChain;
+
     use crate::action::ActionExecutor;
     use crate::router::{Intent, IntentRouter};
     use crate::stt::Transcriber;
@@ -94,13 +384,49 @@ mod tests {
     }
 
     use crate::pipeline::{Pipeline, SharedPipeline};
+    use crate::stt::{SessionUpdate, TranscriptionSession};
+
+    /// Finalizes the length of every pushed chunk.
+    struct LenSession;
+    impl TranscriptionSession for LenSession {
+        fn push_pcm(&mut self, samples: &[f32]) -> anyhow::Result<Vec<SessionUpdate>> {
+            Ok(vec![SessionUpdate::Final(samples.len().to_string())])
+        }
+        fn finish(&mut self) -> anyhow::Result<Vec<SessionUpdate>> {
+            Ok(vec![SessionUpdate::Final("end".into())])
+        }
+    }
+
+    /// Streams through [`LenSession`]; batch transcription yields "whole".
+    struct SessionTranscriber;
+    impl Transcriber for SessionTranscriber {
+        fn transcribe(&self, _: &std::path::Path) -> anyhow::Result<String> { unreachable!() }
+        fn transcribe_pcm(&self, _: &[f32], _: u32) -> anyhow::Result<String> { Ok("whole".into()) }
+        fn start_session(&self, _: u32) -> anyhow::Result<Box<dyn TranscriptionSession + '_>> {
+            Ok(Box::new(LenSession))
+        }
+        fn name(&self) -> &str { "session" }
+        fn is_available(&self) -> bool { true }
+    }
+
+    fn wait_for(done: impl Fn() -> bool) {
+        for _ in 0..200 {
+            if done() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
 
     fn make_pipeline() -> (Arc<SharedPipeline>, Arc<Mutex<Vec<String>>>) {
         let executed = Arc::new(Mutex::new(vec![]));
         let pipeline = Arc::new(SharedPipeline::new(Pipeline {
-            stt: Box::new(StubTranscriber),
+            stt: Arc::new(StubTranscriber),
+            postprocess: vec![],
             router: Box::new(StubRouter),
             action: Box::new(StubAction { executed: executed.clone() }),
+            hallucination: None,
+            history: None,
         }));
         (pipeline, executed)
     }
@@ -113,7 +439,7 @@ mod tests {
 
         toggle_recording(&state, &cfg, &pipeline);
 
-        assert_eq!(*state.status.lock().unwrap(), AppStatus::Recording);
+        assert_eq!(state.status.load(), AppStatus::Recording);
         assert!(state.chunks.lock().unwrap().is_empty());
     }
 
@@ -124,7 +450,7 @@ mod tests {
         let cfg = Config::default();
 
         // Idle → Recording
-        *state.status.lock().unwrap() = AppStatus::Recording;
+        state.status.store(AppStatus::Recording);
         state.chunks.lock().unwrap().extend_from_slice(&[0.1, 0.2, 0.3]);
 
         // Recording → Transcribing (spawns thread)
@@ -132,29 +458,412 @@ mod tests {
 
         // Wait for the transcription thread to finish
         for _ in 0..200 {
-            if *state.status.lock().unwrap() == AppStatus::Idle {
+            if state.status.load() == AppStatus::Idle {
                 break;
             }
             std::thread::sleep(std::time::Duration::from_millis(10));
         }
 
-        assert_eq!(*state.status.lock().unwrap(), AppStatus::Idle);
+        assert_eq!(state.status.load(), AppStatus::Idle);
         assert_eq!(&*executed.lock().unwrap(), &["ok"]);
     }
 
+    #[test]
+    fn recording_is_labelled_with_capture_rate() {
+        struct LenTranscriber;
+        impl Transcriber for LenTranscriber {
+            fn transcribe(&self, _: &std::path::Path) -> anyhow::Result<String> { Ok(String::new()) }
+            fn transcribe_pcm(&self, samples: &[f32], _: u32) -> anyhow::Result<String> {
+                Ok(samples.len().to_string())
+            }
+            fn name(&self) -> &str { "len" }
+            fn is_available(&self) -> bool { true }
+        }
+
+        let state = Arc::new(SharedState::new());
+        // Device fell back to 48 kHz despite `audio.sample_rate` = 16000
+        state.sample_rate.store(48000, std::sync::atomic::Ordering::Relaxed);
+        let executed = Arc::new(Mutex::new(vec![]));
+        let pipeline = Arc::new(SharedPipeline::new(Pipeline {
+            stt: Arc::new(LenTranscriber),
+            postprocess: vec![],
+            router: Box::new(StubRouter),
+            action: Box::new(StubAction { executed: executed.clone() }),
+            hallucination: None,
+            history: None,
+        }));
+        let cfg = Config::default();
+
+        state.status.store(AppStatus::Recording);
+        state.chunks.lock().unwrap().extend_from_slice(&[0.0; 4800]);
+        toggle_recording(&state, &cfg, &pipeline);
+
+        for _ in 0..200 {
+            if state.status.load() == AppStatus::Idle {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        // 100 ms of 48 kHz audio reaches STT as 1600 samples at 16 kHz
+        assert_eq!(&*executed.lock().unwrap(), &["1600"]);
+    }
+
     #[test]
     fn recording_empty_audio_returns_to_idle() {
         let state = Arc::new(SharedState::new());
         let (pipeline, _) = make_pipeline();
         let cfg = Config::default();
 
-        *state.status.lock().unwrap() = AppStatus::Recording;
+        state.status.store(AppStatus::Recording);
         // No chunks pushed — empty audio
 
         toggle_recording(&state, &cfg, &pipeline);
 
         // Should return to Idle synchronously (no thread spawned)
-        assert_eq!(*state.status.lock().unwrap(), AppStatus::Idle);
+        assert_eq!(state.status.load(), AppStatus::Idle);
+    }
+
+    #[test]
+    fn hands_free_ignores_toggle() {
+        let state = Arc::new(SharedState::new());
+        let (pipeline, _) = make_pipeline();
+        let mut cfg = Config::default();
+        cfg.vad.hands_free = true;
+
+        toggle_recording(&state, &cfg, &pipeline);
+
+        assert_eq!(state.status.load(), AppStatus::Idle);
+    }
+
+    struct PeakVad;
+    impl crate::vad::VoiceDetector for PeakVad {
+        fn is_speech(&mut self, samples: &[f32], _: u32) -> bool {
+            samples.iter().any(|s| s.abs() > 0.1)
+        }
+        fn name(&self) -> &str { "peak" }
+    }
+
+    #[test]
+    fn hands_free_processes_each_utterance() {
+        let state = Arc::new(SharedState::new());
+        let (pipeline, executed) = make_pipeline();
+        let mut cfg = Config::default();
+        cfg.vad.pre_roll_ms = 0;
+        cfg.vad.trailing_silence_ms = 20;
+        // 1 kHz with 10 ms frames keeps the buffers tiny
+        let segmenter = UtteranceSegmenter::new(Box::new(PeakVad), &cfg.vad, 1000, 10);
+
+        let (mut tx, rx) = crate::audio::ring::channel(1024);
+        let capture = Capture::new(rx, FilterChain::default());
+        let handle = spawn_hands_free(state.clone(), pipeline, segmenter, capture, 1000).unwrap();
+        tx.push(&[0.5; 30]);
+        tx.push(&[0.0; 20]);
+        tx.push(&[0.5; 10]);
+        // Stream torn down mid-utterance — the open utterance is flushed
+        drop(tx);
+        handle.join().unwrap();
+
+        assert_eq!(&*executed.lock().unwrap(), &["ok", "ok"]);
+        assert_eq!(state.status.load(), AppStatus::Idle);
+    }
+
+    #[test]
+    fn hands_free_keeps_draining_capture_during_slow_stt() {
+        /// Blocks the first transcription until released; reports sample counts.
+        struct GatedTranscriber {
+            gate: Mutex<Option<std::sync::mpsc::Receiver<()>>>,
+        }
+        impl Transcriber for GatedTranscriber {
+            fn transcribe(&self, _: &std::path::Path) -> anyhow::Result<String> { Ok(String::new()) }
+            fn transcribe_pcm(&self, samples: &[f32], _: u32) -> anyhow::Result<String> {
+                if let Some(gate) = self.gate.lock().unwrap().take() {
+                    let _ = gate.recv();
+                }
+                Ok(samples.len().to_string())
+            }
+            fn name(&self) -> &str { "gated" }
+            fn is_available(&self) -> bool { true }
+        }
+
+        let (release, gate) = std::sync::mpsc::channel();
+        let executed = Arc::new(Mutex::new(vec![]));
+        let pipeline = Arc::new(SharedPipeline::new(Pipeline {
+            stt: Arc::new(GatedTranscriber { gate: Mutex::new(Some(gate)) }),
+            postprocess: vec![],
+            router: Box::new(StubRouter),
+            action: Box::new(StubAction { executed: executed.clone() }),
+            hallucination: None,
+            history: None,
+        }));
+        let state = Arc::new(SharedState::new());
+        let mut cfg = Config::default();
+        cfg.vad.pre_roll_ms = 0;
+        cfg.vad.trailing_silence_ms = 20;
+        // 1 kHz with 10 ms frames; the ring holds 64 ms
+        let segmenter = UtteranceSegmenter::new(Box::new(PeakVad), &cfg.vad, 1000, 10);
+        let (mut tx, rx) = crate::audio::ring::channel(64);
+        let capture = Capture::new(rx, FilterChain::default());
+        let handle = spawn_hands_free(state.clone(), pipeline, segmenter, capture, 1000).unwrap();
+
+        // Three utterances, 180 samples in all, while the first is stuck in STT
+        for _ in 0..3 {
+            for piece in [[0.5; 20], [0.5; 20], [0.0; 20]] {
+                assert_eq!(tx.push(&piece), piece.len(), "capture ring overflowed");
+                std::thread::sleep(Duration::from_millis(40));
+            }
+        }
+        assert_eq!(state.status.load(), AppStatus::Transcribing);
+        release.send(()).unwrap();
+        drop(tx);
+        handle.join().unwrap();
+
+        let executed = executed.lock().unwrap();
+        assert_eq!(executed.len(), 3);
+        assert!(executed.iter().all(|n| *n == executed[0]), "{executed:?}");
+        assert_eq!(state.status.load(), AppStatus::Idle);
+    }
+
+    #[cfg(feature = "vad-energy")]
+    #[test]
+    fn file_source_runs_headless_through_hands_free_pipeline() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("two-utterances.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        // silence, tone, silence, tone, silence
+        for (secs, amplitude) in [(0.5, 0.0), (1.0, 0.5), (1.5, 0.0), (1.0, 0.5), (1.5, 0.0)] {
+            for i in 0..(16_000.0 * secs) as usize {
+                let s = amplitude * (i as f32 * 440.0 * std::f32::consts::TAU / 16_000.0).sin();
+                writer.write_sample((s * 32767.0) as i16).unwrap();
+            }
+        }
+        writer.finalize().unwrap();
+
+        let state = Arc::new(SharedState::new());
+        let (pipeline, executed) = make_pipeline();
+        let mut cfg = Config::default();
+        cfg.vad.hands_free = true;
+        cfg.vad.backend = "energy".into();
+        let source = crate::audio::source::FileSource::open(&path).unwrap();
+        let capture = crate::audio::start_capture_from(state.clone(), &cfg, pipeline, Box::new(source)).unwrap();
+        capture.wait();
+
+        assert_eq!(&*executed.lock().unwrap(), &["ok", "ok"]);
+        assert_eq!(state.status.load(), AppStatus::Idle);
+    }
+
+    #[test]
+    fn stop_collects_audio_still_in_ring_buffer() {
+        let state = Arc::new(SharedState::new());
+        let (pipeline, executed) = make_pipeline();
+        let cfg = Config::default();
+        let (mut tx, rx) = crate::audio::ring::channel(64);
+        *state.capture.lock().unwrap() = Some(Capture::new(rx, FilterChain::default()));
+
+        toggle_recording(&state, &cfg, &pipeline);
+        // Captured after the recorder thread's last pass
+        tx.push(&[0.1, 0.2, 0.3]);
+        toggle_recording(&state, &cfg, &pipeline);
+
+        for _ in 0..200 {
+            if state.status.load() == AppStatus::Idle {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(&*executed.lock().unwrap(), &["ok"]);
+    }
+
+    #[test]
+    fn collect_capture_caps_recording_length() {
+        let state = SharedState::new();
+        state.max_chunks.store(4, Ordering::Relaxed);
+        let (mut tx, rx) = crate::audio::ring::channel(64);
+        *state.capture.lock().unwrap() = Some(Capture::new(rx, FilterChain::default()));
+
+        tx.push(&[0.1; 3]);
+        collect_capture(&state);
+        tx.push(&[0.2; 3]);
+        collect_capture(&state);
+
+        assert_eq!(&*state.chunks.lock().unwrap(), &[0.1, 0.1, 0.1, 0.2]);
+    }
+
+    #[test]
+    fn collect_capture_filters_new_audio_only() {
+        struct Double;
+        impl crate::audio::filter::AudioFilter for Double {
+            fn process(&mut self, samples: &mut [f32]) {
+                samples.iter_mut().for_each(|s| *s *= 2.0);
+            }
+            fn reset(&mut self) {}
+            fn name(&self) -> &str { "double" }
+        }
+
+        let state = SharedState::new();
+        let (mut tx, rx) = crate::audio::ring::channel(64);
+        let filters = FilterChain::new(vec![Box::new(Double)]);
+        *state.capture.lock().unwrap() = Some(Capture::new(rx, filters));
+
+        tx.push(&[0.1; 2]);
+        collect_capture(&state);
+        tx.push(&[0.2; 2]);
+        collect_capture(&state);
+
+        assert_eq!(&*state.chunks.lock().unwrap(), &[0.2, 0.2, 0.4, 0.4]);
+    }
+
+    #[test]
+    fn finish_capture_appends_filter_tail() {
+        struct Delay(f32);
+        impl crate::audio::filter::AudioFilter for Delay {
+            fn process(&mut self, samples: &mut [f32]) {
+                for s in samples {
+                    self.0 = std::mem::replace(s, self.0);
+                }
+            }
+            fn reset(&mut self) {}
+            fn flush(&mut self) -> Vec<f32> {
+                vec![std::mem::take(&mut self.0)]
+            }
+            fn name(&self) -> &str { "delay" }
+        }
+
+        let state = SharedState::new();
+        let (mut tx, rx) = crate::audio::ring::channel(64);
+        let filters = FilterChain::new(vec![Box::new(Delay(0.0))]);
+        *state.capture.lock().unwrap() = Some(Capture::new(rx, filters));
+
+        tx.push(&[0.1, 0.2]);
+        collect_capture(&state);
+        tx.push(&[0.3]);
+        finish_capture(&state);
+
+        assert_eq!(&*state.chunks.lock().unwrap(), &[0.0, 0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn recorder_thread_exits_when_stream_is_dropped() {
+        let state = Arc::new(SharedState::new());
+        let (mut tx, rx) = crate::audio::ring::channel(64);
+        let liveness = rx.liveness();
+        *state.capture.lock().unwrap() = Some(Capture::new(rx, FilterChain::default()));
+        let handle = spawn_recorder(state.clone(), liveness).unwrap();
+
+        tx.push(&[0.5; 8]);
+        drop(tx);
+        handle.join().unwrap();
+        collect_capture(&state);
+
+        assert_eq!(state.chunks.lock().unwrap().len(), 8);
+    }
+
+    #[test]
+    fn start_is_noop_while_recording() {
+        let state = Arc::new(SharedState::new());
+        let (pipeline, _) = make_pipeline();
+        let cfg = Config::default();
+
+        state.status.store(AppStatus::Recording);
+        state.chunks.lock().unwrap().extend_from_slice(&[0.1, 0.2]);
+
+        start_recording(&state, &cfg, &pipeline);
+
+        // Auto-repeat must not discard audio captured so far
+        assert_eq!(state.status.load(), AppStatus::Recording);
+        assert_eq!(state.chunks.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn streaming_types_finals_while_recording() {
+        let state = Arc::new(SharedState::new());
+        let executed = Arc::new(Mutex::new(vec![]));
+        let pipeline = Arc::new(SharedPipeline::new(Pipeline {
+            stt: Arc::new(SessionTranscriber),
+            postprocess: vec![],
+            router: Box::new(StubRouter),
+            action: Box::new(StubAction { executed: executed.clone() }),
+            hallucination: None,
+            history: None,
+        }));
+        let mut cfg = Config::default();
+        cfg.stt.streaming = true;
+
+        start_recording(&state, &cfg, &pipeline);
+        // Stand in for the recorder thread
+        state.chunks.lock().unwrap().extend_from_slice(&[0.1; 3]);
+        wait_for(|| !executed.lock().unwrap().is_empty());
+        // Typed before the recording stops
+        assert_eq!(state.status.load(), AppStatus::Recording);
+        assert_eq!(&*executed.lock().unwrap(), &["3"]);
+
+        state.chunks.lock().unwrap().extend_from_slice(&[0.1; 2]);
+        stop_recording(&state, &cfg, &pipeline);
+        wait_for(|| state.status.load() == AppStatus::Idle);
+
+        assert_eq!(state.status.load(), AppStatus::Idle);
+        assert_eq!(&*executed.lock().unwrap(), &["3", " 2", " end"]);
+        assert!(!state.streaming.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn streaming_skipped_for_whole_utterance_actions() {
+        /// Like a computer-use goal: one execution per utterance.
+        struct GoalAction {
+            executed: Arc<Mutex<Vec<String>>>,
+        }
+        impl ActionExecutor for GoalAction {
+            fn execute(&self, intent: &Intent) -> anyhow::Result<()> {
+                if let Intent::Dictate(t) = intent {
+                    self.executed.lock().unwrap().push(t.clone());
+                }
+                Ok(())
+            }
+            fn name(&self) -> &str { "goal" }
+            fn streams(&self) -> bool { false }
+        }
+
+        let state = Arc::new(SharedState::new());
+        let executed = Arc::new(Mutex::new(vec![]));
+        let pipeline = Arc::new(SharedPipeline::new(Pipeline {
+            stt: Arc::new(SessionTranscriber),
+            postprocess: vec![],
+            router: Box::new(StubRouter),
+            action: Box::new(GoalAction { executed: executed.clone() }),
+            hallucination: None,
+            history: None,
+        }));
+        let mut cfg = Config::default();
+        cfg.stt.streaming = true;
+
+        start_recording(&state, &cfg, &pipeline);
+        assert!(!state.streaming.load(Ordering::SeqCst));
+        state.chunks.lock().unwrap().extend_from_slice(&[0.1; 3]);
+        state.chunks.lock().unwrap().extend_from_slice(&[0.1; 2]);
+        stop_recording(&state, &cfg, &pipeline);
+        wait_for(|| state.status.load() == AppStatus::Idle);
+
+        // Transcribed once, after the recording stopped
+        assert_eq!(state.status.load(), AppStatus::Idle);
+        assert_eq!(&*executed.lock().unwrap(), &["whole"]);
+    }
+
+    #[test]
+    fn stop_is_noop_when_idle() {
+        let state = Arc::new(SharedState::new());
+        let (pipeline, executed) = make_pipeline();
+        let cfg = Config::default();
+
+        stop_recording(&state, &cfg, &pipeline);
+
+        assert_eq!(state.status.load(), AppStatus::Idle);
+        assert!(executed.lock().unwrap().is_empty());
     }
 
     #[test]
@@ -163,11 +872,11 @@ mod tests {
         let (pipeline, _) = make_pipeline();
         let cfg = Config::default();
 
-        *state.status.lock().unwrap() = AppStatus::Transcribing;
+        state.status.store(AppStatus::Transcribing);
 
         toggle_recording(&state, &cfg, &pipeline);
 
-        assert_eq!(*state.status.lock().unwrap(), AppStatus::Transcribing);
+        assert_eq!(state.status.load(), AppStatus::Transcribing);
     }
 }
 
@@ -0,0 +1,40 @@
+//! Goal router — all text becomes a goal for the computer-use agent.
+
+use super::{Intent, IntentRouter};
+
+/// Command action carrying a free-form goal in `args.goal`.
+pub const GOAL_COMMAND: &str = "goal";
+
+/// Routes every transcription as a [`GOAL_COMMAND`] command, so the
+/// computer-use executor treats the utterance as a task rather than text to type.
+pub struct GoalRouter;
+
+impl IntentRouter for GoalRouter {
+    fn route(&self, text: &str) -> anyhow::Result<Intent> {
+        Ok(Intent::Command {
+            action: GOAL_COMMAND.into(),
+            args: serde_json::json!({ "goal": text }),
+        })
+    }
+
+    fn name(&self) -> &str {
+        "goal"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goal_wraps_text() {
+        let intent = GoalRouter.route("open the downloads folder").unwrap();
+        assert_eq!(
+            intent,
+            Intent::Command {
+                action: "goal".into(),
+                args: serde_json::json!({ "goal": "open the downloads folder" }),
+            }
+        );
+    }
+}
@@ -2,19 +2,23 @@
 //!
 //! Routes transcribed text to an intent: dictation (type text) or command (execute action).
 
+pub mod goal;
 pub mod passthrough;
+pub mod rules;
 #[cfg(feature = "router-llm")]
 pub mod llm;
 
+use serde::{Deserialize, Serialize};
+
 use crate::config::RouterConfig;
 
 /// What should happen with the transcribed text.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Intent {
     /// Type this text at the cursor.
     Dictate(String),
     /// Execute a named action with arguments.
-    #[allow(dead_code)]
     Command {
         action: String,
         args: serde_json::Value,
@@ -25,6 +29,11 @@ pub enum Intent {
 pub trait IntentRouter: Send + Sync {
     /// Classify transcribed text into an intent.
     fn route(&self, text: &str) -> anyhow::Result<Intent>;
+    /// Match the raw transcript against whole-utterance commands before
+    /// post-processing rewrites it. Default: no early commands.
+    fn route_command(&self, _text: &str) -> Option<Intent> {
+        None
+    }
     fn name(&self) -> &str;
 }
 
@@ -32,6 +41,8 @@ pub trait IntentRouter: Send + Sync {
 pub fn create_router(cfg: &RouterConfig) -> anyhow::Result<Box<dyn IntentRouter>> {
     match cfg.backend.as_str() {
         "passthrough" => Ok(Box::new(passthrough::PassthroughRouter)),
+        "goal" => Ok(Box::new(goal::GoalRouter)),
+        "rules" => Ok(Box::new(rules::RulesRouter::new(cfg)?)),
         "llm" => {
             #[cfg(feature = "router-llm")]
             return Ok(Box::new(llm::LlmRouter::new(cfg)?));
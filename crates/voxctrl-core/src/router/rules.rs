@@ -0,0 +1,377 @@
+//! Rules router — deterministic phrase grammar, no model or network needed.
+//!
+//! The grammar is a JSON file of phrase patterns with slots:
+//!
+//! ```json
+//! {
+//!   "commands": [
+//!     { "phrases": ["new line", "next line"], "action": "key", "args": { "key": "enter" } },
+//!     { "phrases": ["select {n:int} words"], "action": "select_words" },
+//!     { "phrases": ["press {key}"], "action": "key" }
+//!   ]
+//! }
+//! ```
+//!
+//! A transcript that matches a phrase in full (case- and punctuation-
+//! insensitive) becomes `Intent::Command` with the rule's `args` plus one
+//! entry per slot; anything else is `Intent::Dictate`. `{name}` slots capture
+//! one or more words, `{name:int}` captures a single number ("3" or "three").
+//!
+//! Commands are matched against the raw transcript, before post-processing
+//! (see [`IntentRouter::route_command`]), so spoken punctuation can't turn
+//! "new line" into a line break first.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use super::{Intent, IntentRouter};
+use crate::config::{self, RouterConfig};
+
+/// Grammar used when no grammar file exists.
+const DEFAULT_GRAMMAR: &str = r#"{
+  "commands": [
+    { "phrases": ["new line", "next line"], "action": "key", "args": { "key": "enter" } },
+    { "phrases": ["new paragraph"], "action": "key", "args": { "key": "enter", "repeat": 2 } },
+    { "phrases": ["press {key}"], "action": "key" },
+    { "phrases": ["select {n:int} words", "select {n:int} word"], "action": "select_words" },
+    { "phrases": ["scratch that", "undo that"], "action": "undo" }
+  ]
+}"#;
+
+#[derive(Deserialize)]
+struct GrammarFile {
+    commands: Vec<CommandDef>,
+}
+
+#[derive(Deserialize)]
+struct CommandDef {
+    phrases: Vec<String>,
+    action: String,
+    #[serde(default)]
+    args: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Slot { name: String, int: bool },
+}
+
+struct Rule {
+    tokens: Vec<Token>,
+    action: String,
+    args: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Routes transcripts through a user-editable phrase grammar.
+pub struct RulesRouter {
+    rules: Vec<Rule>,
+}
+
+/// Default grammar location: `commands.json` next to config.json.
+pub fn default_rules_path() -> PathBuf {
+    config::config_path().with_file_name("commands.json")
+}
+
+impl RulesRouter {
+    pub fn new(cfg: &RouterConfig) -> anyhow::Result<Self> {
+        let path = cfg.rules_path.clone().unwrap_or_else(default_rules_path);
+        if path.exists() {
+            let router = Self::load(&path)?;
+            log::info!("RulesRouter: {} rules from {:?}", router.rules.len(), path);
+            Ok(router)
+        } else {
+            log::info!("RulesRouter: {:?} not found — using built-in grammar", path);
+            Self::from_json(DEFAULT_GRAMMAR)
+        }
+    }
+
+    /// Load a grammar file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("read grammar {}", path.display()))?;
+        Self::from_json(&json).with_context(|| format!("parse grammar {}", path.display()))
+    }
+
+    /// Build a router from grammar JSON.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let grammar: GrammarFile = serde_json::from_str(json)?;
+        let mut rules = Vec::new();
+        for def in grammar.commands {
+            for phrase in &def.phrases {
+                let tokens = parse_pattern(phrase)
+                    .with_context(|| format!("invalid phrase {phrase:?} for action {:?}", def.action))?;
+                rules.push(Rule {
+                    tokens,
+                    action: def.action.clone(),
+                    args: def.args.clone(),
+                });
+            }
+        }
+        Ok(Self { rules })
+    }
+}
+
+impl IntentRouter for RulesRouter {
+    fn route(&self, text: &str) -> anyhow::Result<Intent> {
+        Ok(self.route_command(text).unwrap_or_else(|| Intent::Dictate(text.to_string())))
+    }
+
+    fn route_command(&self, text: &str) -> Option<Intent> {
+        let words = split_words(text);
+        for rule in &self.rules {
+            let mut slots = Vec::new();
+            if match_tokens(&rule.tokens, &words, &mut slots) {
+                let mut args = rule.args.clone();
+                for (name, value) in slots {
+                    args.insert(name, value);
+                }
+                log::debug!("RulesRouter: {text:?} → {}", rule.action);
+                return Some(Intent::Command {
+                    action: rule.action.clone(),
+                    args: serde_json::Value::Object(args),
+                });
+            }
+        }
+        None
+    }
+
+    fn name(&self) -> &str {
+        "rules"
+    }
+}
+
+/// Split a phrase pattern into literal words and `{slot}` / `{slot:int}` tokens.
+fn parse_pattern(pattern: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    for part in pattern.split_whitespace() {
+        if let Some(inner) = part.strip_prefix('{').and_then(|p| p.strip_suffix('}')) {
+            let (name, int) = match inner.split_once(':') {
+                Some((name, "int")) => (name, true),
+                Some((_, kind)) => anyhow::bail!("unknown slot type {kind:?}"),
+                None => (inner, false),
+            };
+            if name.is_empty() {
+                anyhow::bail!("empty slot name");
+            }
+            tokens.push(Token::Slot { name: name.to_string(), int });
+        } else {
+            let word = normalize(part);
+            if word.is_empty() {
+                anyhow::bail!("pattern word {part:?} has no letters or digits");
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+    if tokens.is_empty() {
+        anyhow::bail!("empty pattern");
+    }
+    Ok(tokens)
+}
+
+/// Split a transcript into words, dropping surrounding punctuation.
+/// Returns `(original, normalized)` pairs so slots keep the spoken casing.
+fn split_words(text: &str) -> Vec<(String, String)> {
+    text.split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|w| !w.is_empty())
+        .map(|w| (w.to_string(), normalize(w)))
+        .collect()
+}
+
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Match `tokens` against all of `words`, collecting slot values.
+fn match_tokens(
+    tokens: &[Token],
+    words: &[(String, String)],
+    slots: &mut Vec<(String, serde_json::Value)>,
+) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return words.is_empty();
+    };
+    match token {
+        Token::Word(expected) => {
+            matches!(words.first(), Some((_, w)) if w == expected)
+                && match_tokens(rest, &words[1..], slots)
+        }
+        Token::Slot { name, int: true } => {
+            let Some(n) = words.first().and_then(|(_, w)| parse_number(w)) else {
+                return false;
+            };
+            slots.push((name.clone(), n.into()));
+            if match_tokens(rest, &words[1..], slots) {
+                return true;
+            }
+            slots.pop();
+            false
+        }
+        Token::Slot { name, int: false } => {
+            // Shortest capture first so literal words after the slot can match
+            for len in 1..=words.len() {
+                let value: Vec<&str> = words[..len].iter().map(|(orig, _)| orig.as_str()).collect();
+                slots.push((name.clone(), value.join(" ").into()));
+                if match_tokens(rest, &words[len..], slots) {
+                    return true;
+                }
+                slots.pop();
+            }
+            false
+        }
+    }
+}
+
+/// Parse digits or a spoken number word ("zero" … "twenty", tens up to "ninety").
+fn parse_number(word: &str) -> Option<u64> {
+    if let Ok(n) = word.parse() {
+        return Some(n);
+    }
+    const UNITS: [&str; 21] = [
+        "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+        "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen",
+        "eighteen", "nineteen", "twenty",
+    ];
+    const TENS: [&str; 7] = ["thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+    if let Some(i) = UNITS.iter().position(|&u| u == word) {
+        return Some(i as u64);
+    }
+    TENS.iter().position(|&t| t == word).map(|i| (i as u64 + 3) * 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn default_router() -> RulesRouter {
+        RulesRouter::from_json(DEFAULT_GRAMMAR).unwrap()
+    }
+
+    fn command(intent: Intent) -> (String, serde_json::Value) {
+        match intent {
+            Intent::Command { action, args } => (action, args),
+            Intent::Dictate(text) => panic!("expected Command, got Dictate({text:?})"),
+        }
+    }
+
+    #[test]
+    fn default_grammar_parses() {
+        assert!(!default_router().rules.is_empty());
+    }
+
+    #[test]
+    fn literal_phrase_with_static_args() {
+        let (action, args) = command(default_router().route("New line.").unwrap());
+        assert_eq!(action, "key");
+        assert_eq!(args, json!({"key": "enter"}));
+    }
+
+    #[test]
+    fn int_slot_accepts_digits_and_words() {
+        let router = default_router();
+        let (action, args) = command(router.route("select 3 words").unwrap());
+        assert_eq!(action, "select_words");
+        assert_eq!(args, json!({"n": 3}));
+
+        let (_, args) = command(router.route("Select five words.").unwrap());
+        assert_eq!(args, json!({"n": 5}));
+    }
+
+    #[test]
+    fn int_slot_rejects_non_numbers() {
+        let intent = default_router().route("select many words").unwrap();
+        assert!(matches!(intent, Intent::Dictate(_)));
+    }
+
+    #[test]
+    fn text_slot_keeps_original_casing() {
+        let router = RulesRouter::from_json(
+            r#"{"commands": [{"phrases": ["switch to {app}"], "action": "switch_app"}]}"#,
+        )
+        .unwrap();
+        let (action, args) = command(router.route("Switch to Visual Studio Code.").unwrap());
+        assert_eq!(action, "switch_app");
+        assert_eq!(args, json!({"app": "Visual Studio Code"}));
+    }
+
+    #[test]
+    fn text_slot_followed_by_literal() {
+        let router = RulesRouter::from_json(
+            r#"{"commands": [{"phrases": ["move {what} up"], "action": "move"}]}"#,
+        )
+        .unwrap();
+        let (_, args) = command(router.route("move this line up").unwrap());
+        assert_eq!(args, json!({"what": "this line"}));
+    }
+
+    #[test]
+    fn scratch_that_is_undo() {
+        let (action, args) = command(default_router().route("Scratch that.").unwrap());
+        assert_eq!(action, crate::action::UNDO_COMMAND);
+        assert_eq!(args, json!({}));
+    }
+
+    #[test]
+    fn partial_match_is_dictation() {
+        let intent = default_router().route("add a new line here").unwrap();
+        match intent {
+            Intent::Dictate(text) => assert_eq!(text, "add a new line here"),
+            Intent::Command { .. } => panic!("expected Dictate"),
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let router = RulesRouter::from_json(
+            r#"{"commands": [
+                {"phrases": ["press enter"], "action": "submit"},
+                {"phrases": ["press {key}"], "action": "key"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(command(router.route("press enter").unwrap()).0, "submit");
+        assert_eq!(command(router.route("press tab").unwrap()).0, "key");
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        assert!(parse_pattern("").is_err());
+        assert!(parse_pattern("select {n:float} words").is_err());
+        assert!(parse_pattern("go {}").is_err());
+        assert!(RulesRouter::from_json(r#"{"commands": [{"phrases": ["..."], "action": "x"}]}"#).is_err());
+    }
+
+    #[test]
+    fn missing_file_uses_builtin_grammar() {
+        let cfg = RouterConfig {
+            backend: "rules".into(),
+            rules_path: Some("/nonexistent/commands.json".into()),
+            ..Default::default()
+        };
+        let router = RulesRouter::new(&cfg).unwrap();
+        assert_eq!(command(router.route("new paragraph").unwrap()).1, json!({"key": "enter", "repeat": 2}));
+    }
+
+    #[test]
+    fn load_from_file() {
+        let tmp = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        std::fs::write(tmp.path(), r#"{"commands": [{"phrases": ["ship it"], "action": "deploy"}]}"#).unwrap();
+        let router = RulesRouter::load(tmp.path()).unwrap();
+        assert_eq!(command(router.route("Ship it!").unwrap()).0, "deploy");
+    }
+
+    #[test]
+    fn parse_number_words() {
+        assert_eq!(parse_number("twelve"), Some(12));
+        assert_eq!(parse_number("forty"), Some(40));
+        assert_eq!(parse_number("42"), Some(42));
+        assert_eq!(parse_number("lots"), None);
+    }
+}
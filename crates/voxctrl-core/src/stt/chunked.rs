@@ -0,0 +1,155 @@
+//! Chunked session — progressive transcription that decodes each piece of
+//! audio exactly once.
+//!
+//! Audio is buffered until a chunk is full, cut at the quietest point near
+//! its end, and the head is decoded and emitted as `Final`. The remainder
+//! starts the next chunk. Decoding cost therefore grows linearly with the
+//! length of the recording. No partials are produced — they would require
+//! re-decoding the open chunk.
+
+use super::windowed::{quietest_cut, CUT_FRAME_MS};
+use super::{SessionUpdate, Transcriber, TranscriptionSession};
+
+/// Timing parameters for a [`ChunkedSession`].
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkParams {
+    /// Buffered audio that triggers a cut.
+    pub chunk_ms: u32,
+    /// Tail of the chunk searched for the quietest cut point.
+    pub cut_search_ms: u32,
+}
+
+pub struct ChunkedSession<'a, T: Transcriber + ?Sized> {
+    stt: &'a T,
+    sample_rate: u32,
+    chunk: usize,
+    cut_search: usize,
+    buffer: Vec<f32>,
+}
+
+impl<'a, T: Transcriber + ?Sized> ChunkedSession<'a, T> {
+    pub fn new(stt: &'a T, sample_rate: u32, params: ChunkParams) -> Self {
+        let samples = |ms: u32| ((sample_rate as u64 * ms as u64) / 1000) as usize;
+        let chunk = samples(params.chunk_ms).max(1);
+        Self {
+            stt,
+            sample_rate,
+            chunk,
+            cut_search: samples(params.cut_search_ms).min(chunk),
+            buffer: Vec::new(),
+        }
+    }
+
+    fn decode(&self, samples: &[f32], updates: &mut Vec<SessionUpdate>) -> anyhow::Result<()> {
+        let text = self.stt.transcribe_pcm(samples, self.sample_rate)?;
+        let text = text.trim();
+        if !text.is_empty() {
+            updates.push(SessionUpdate::Final(text.to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl<T: Transcriber + ?Sized> TranscriptionSession for ChunkedSession<'_, T> {
+    fn push_pcm(&mut self, samples: &[f32]) -> anyhow::Result<Vec<SessionUpdate>> {
+        self.buffer.extend_from_slice(samples);
+        let frame = ((self.sample_rate * CUT_FRAME_MS) / 1000).max(1) as usize;
+        let mut updates = Vec::new();
+        while self.buffer.len() >= self.chunk {
+            let cut = quietest_cut(&self.buffer[..self.chunk], self.cut_search, frame);
+            self.decode(&self.buffer[..cut], &mut updates)?;
+            log::debug!(
+                "Chunked session: decoded {:.1}s chunk",
+                cut as f64 / self.sample_rate as f64,
+            );
+            self.buffer.drain(..cut);
+        }
+        Ok(updates)
+    }
+
+    fn finish(&mut self) -> anyhow::Result<Vec<SessionUpdate>> {
+        let mut updates = Vec::new();
+        if !self.buffer.is_empty() {
+            let buffer = std::mem::take(&mut self.buffer);
+            self.decode(&buffer, &mut updates)?;
+        }
+        Ok(updates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    /// Returns "chunk N" for the N-th decode and records decoded lengths.
+    #[derive(Default)]
+    struct CountingTranscriber {
+        lengths: Mutex<Vec<usize>>,
+    }
+
+    impl Transcriber for CountingTranscriber {
+        fn transcribe(&self, _: &Path) -> anyhow::Result<String> {
+            unreachable!()
+        }
+        fn transcribe_pcm(&self, samples: &[f32], _: u32) -> anyhow::Result<String> {
+            let mut lengths = self.lengths.lock().unwrap();
+            lengths.push(samples.len());
+            Ok(format!("chunk {}", lengths.len()))
+        }
+        fn name(&self) -> &str { "counting" }
+        fn is_available(&self) -> bool { true }
+    }
+
+    /// 1 kHz: 1 s chunks, 300 ms cut search.
+    fn params() -> ChunkParams {
+        ChunkParams { chunk_ms: 1000, cut_search_ms: 300 }
+    }
+
+    #[test]
+    fn nothing_decoded_before_chunk_fills() {
+        let stt = CountingTranscriber::default();
+        let mut session = ChunkedSession::new(&stt, 1000, params());
+        assert!(session.push_pcm(&[0.5; 999]).unwrap().is_empty());
+        assert!(stt.lengths.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn chunk_is_cut_at_quietest_point() {
+        let stt = CountingTranscriber::default();
+        let mut session = ChunkedSession::new(&stt, 1000, params());
+        let mut audio = vec![0.5f32; 1000];
+        audio[800..850].iter_mut().for_each(|s| *s = 0.0);
+
+        let updates = session.push_pcm(&audio).unwrap();
+        assert_eq!(updates, vec![SessionUpdate::Final("chunk 1".into())]);
+        assert_eq!(*stt.lengths.lock().unwrap(), vec![800]);
+
+        let updates = session.finish().unwrap();
+        assert_eq!(updates, vec![SessionUpdate::Final("chunk 2".into())]);
+        assert_eq!(*stt.lengths.lock().unwrap(), vec![800, 200]);
+    }
+
+    #[test]
+    fn every_sample_is_decoded_once() {
+        let stt = CountingTranscriber::default();
+        let mut session = ChunkedSession::new(&stt, 1000, params());
+        let audio: Vec<f32> = (0..100).map(|i| ((i * 37) % 11) as f32 / 10.0).collect();
+        for _ in 0..100 {
+            session.push_pcm(&audio).unwrap();
+        }
+        session.finish().unwrap();
+
+        let lengths = stt.lengths.lock().unwrap();
+        assert_eq!(lengths.iter().sum::<usize>(), 10_000);
+        assert!(lengths.iter().all(|&n| n <= 1000), "{lengths:?}");
+    }
+
+    #[test]
+    fn finish_without_audio_is_empty() {
+        let stt = CountingTranscriber::default();
+        let mut session = ChunkedSession::new(&stt, 1000, params());
+        assert!(session.finish().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,294 @@
+//! Long-form transcription for backends limited to a fixed window (Whisper
+//! hears at most 30 s at a time).
+//!
+//! A long recording is split into segments that end at the quietest point
+//! near the window limit, so words are rarely cut. When no quiet point is
+//! found, the next segment starts a little earlier and the words repeated
+//! across the boundary are dropped while stitching. Each segment is decoded
+//! with the previous segment's text as its prompt, which keeps spelling and
+//! style consistent across segments.
+
+use std::ops::Range;
+
+use super::windowed::quietest_cut;
+use super::Transcript;
+
+/// Length of the frames compared when looking for a quiet cut point.
+const CUT_FRAME_MS: u32 = 50;
+/// A cut frame quieter than this (about -40 dBFS RMS) is a pause between
+/// words, so no overlap is needed.
+const QUIET_RMS: f32 = 0.01;
+/// Most words searched for a repeat when stitching overlapping segments.
+const MAX_STITCH_WORDS: usize = 12;
+
+/// Segmenting parameters for [`transcribe_long`].
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentParams {
+    /// Longest segment passed to the decoder.
+    pub max_segment_ms: u32,
+    /// Tail of each segment searched for the quietest cut point.
+    pub cut_search_ms: u32,
+    /// Audio repeated at the start of the next segment when the cut isn't quiet.
+    pub overlap_ms: u32,
+}
+
+impl Default for SegmentParams {
+    /// Whisper's 30 s window.
+    fn default() -> Self {
+        Self { max_segment_ms: 30_000, cut_search_ms: 5000, overlap_ms: 1000 }
+    }
+}
+
+/// One segment of a long recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub range: Range<usize>,
+    /// Whether the segment starts inside the previous one.
+    pub overlaps: bool,
+}
+
+/// Split `samples` into segments of at most `max_segment_ms`, cutting at
+/// quiet points.
+pub fn segment(samples: &[f32], sample_rate: u32, params: SegmentParams) -> Vec<Segment> {
+    let ms = |ms: u32| ((sample_rate as u64 * ms as u64) / 1000) as usize;
+    let max = ms(params.max_segment_ms).max(1);
+    let search = ms(params.cut_search_ms).min(max / 2);
+    let overlap = ms(params.overlap_ms).min(search);
+    let frame = ms(CUT_FRAME_MS).max(1);
+
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut overlaps = false;
+    while samples.len() - start > max {
+        let window = &samples[start..start + max];
+        let cut = start + quietest_cut(window, search, frame);
+        segments.push(Segment { range: start..cut, overlaps });
+
+        let cut_frame = &samples[cut..(cut + frame).min(samples.len())];
+        let rms = (cut_frame.iter().map(|s| s * s).sum::<f32>() / cut_frame.len() as f32).sqrt();
+        overlaps = rms > QUIET_RMS && overlap > 0;
+        start = if overlaps { cut - overlap } else { cut };
+    }
+    segments.push(Segment { range: start..samples.len(), overlaps });
+    segments
+}
+
+/// Append `next` to `text`. With `overlaps`, leading words of `next` that
+/// repeat the end of `text` (ignoring case and punctuation) are dropped.
+///
+/// Returns the number of words dropped.
+pub fn stitch(text: &mut String, next: &str, overlaps: bool) -> usize {
+    let next = next.trim();
+    let mut words: Vec<&str> = next.split_whitespace().collect();
+    let dropped = if overlaps {
+        let tail: Vec<String> = text.split_whitespace().map(normalize).collect();
+        let head: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+        let repeated = (1..=MAX_STITCH_WORDS.min(tail.len()).min(head.len()))
+            .rev()
+            .find(|&n| tail[tail.len() - n..] == head[..n])
+            .unwrap_or(0);
+        words.drain(..repeated);
+        repeated
+    } else {
+        0
+    };
+    if !words.is_empty() {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(&words.join(" "));
+    }
+    dropped
+}
+
+fn normalize(word: &str) -> String {
+    word.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Remove the first `n` words from the transcript's leading segments,
+/// dropping segments left empty.
+fn drop_leading_words(transcript: &mut Transcript, mut n: usize) {
+    while n > 0 && !transcript.segments.is_empty() {
+        let seg = &mut transcript.segments[0];
+        let words: Vec<&str> = seg.text.split_whitespace().collect();
+        if words.len() <= n {
+            n -= words.len();
+            transcript.segments.remove(0);
+        } else {
+            seg.text = words[n..].join(" ");
+            n = 0;
+        }
+    }
+}
+
+/// Transcribe `samples` segment by segment and stitch the results.
+///
+/// `decode` is called with each segment's audio and the previous segment's
+/// text (empty for the first), to use as the decoder prompt. Segment times
+/// in the result are relative to the start of `samples`.
+pub fn transcribe_long(
+    samples: &[f32],
+    sample_rate: u32,
+    params: SegmentParams,
+    mut decode: impl FnMut(&[f32], &str) -> anyhow::Result<Transcript>,
+) -> anyhow::Result<Transcript> {
+    let segments = segment(samples, sample_rate, params);
+    if segments.len() > 1 {
+        log::info!(
+            "Long-form transcription: {:.1}s in {} segments",
+            samples.len() as f64 / sample_rate as f64,
+            segments.len(),
+        );
+    }
+    let mut result = Transcript::default();
+    let mut previous = String::new();
+    for seg in segments {
+        let mut part = decode(&samples[seg.range.clone()], &previous)?;
+        let start_secs = seg.range.start as f64 / sample_rate as f64;
+        log::debug!(
+            "Segment {:.1}–{:.1}s: {:?}",
+            start_secs,
+            seg.range.end as f64 / sample_rate as f64,
+            part.text,
+        );
+        let dropped = stitch(&mut result.text, &part.text, seg.overlaps);
+        drop_leading_words(&mut part, dropped);
+        part.offset(start_secs);
+        result.segments.append(&mut part.segments);
+        result.language = result.language.or(part.language);
+        previous = part.text;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stt::TranscriptSegment;
+
+    const RATE: u32 = 1000;
+
+    /// 1 kHz: 10 s segments, 2 s cut search, 500 ms overlap.
+    fn params() -> SegmentParams {
+        SegmentParams { max_segment_ms: 10_000, cut_search_ms: 2000, overlap_ms: 500 }
+    }
+
+    /// Loud audio with a 200 ms pause starting at each of `pauses` (ms).
+    fn speech_with_pauses(len_ms: usize, pauses: &[usize]) -> Vec<f32> {
+        (0..len_ms)
+            .map(|i| if pauses.iter().any(|&p| (p..p + 200).contains(&i)) { 0.0 } else { 0.3 })
+            .collect()
+    }
+
+    #[test]
+    fn short_audio_is_one_segment() {
+        let audio = vec![0.3; 4000];
+        assert_eq!(segment(&audio, RATE, params()), vec![Segment { range: 0..4000, overlaps: false }]);
+    }
+
+    #[test]
+    fn long_audio_is_cut_at_pauses() {
+        // Two minutes with a pause every 9 s
+        let pauses: Vec<usize> = (1..14).map(|i| i * 9000).collect();
+        let audio = speech_with_pauses(120_000, &pauses);
+        let segments = segment(&audio, RATE, params());
+
+        assert_eq!(segments.first().unwrap().range.start, 0);
+        assert_eq!(segments.last().unwrap().range.end, audio.len());
+        for pair in segments.windows(2) {
+            assert_eq!(pair[0].range.end, pair[1].range.start, "no gaps or overlap");
+        }
+        for seg in &segments {
+            assert!(seg.range.len() <= 10_000);
+            assert!(!seg.overlaps);
+            if seg.range.end < audio.len() {
+                assert_eq!(audio[seg.range.end], 0.0, "cut inside a pause");
+            }
+        }
+    }
+
+    #[test]
+    fn continuous_audio_overlaps_segments() {
+        let audio = vec![0.3; 25_000];
+        let segments = segment(&audio, RATE, params());
+        assert!(segments.len() >= 3);
+        assert!(segments[1].overlaps);
+        assert_eq!(segments[1].range.start + 500, segments[0].range.end);
+    }
+
+    #[test]
+    fn stitching_drops_repeated_words() {
+        let mut text = String::from("so the plan is to ship it");
+        assert_eq!(stitch(&mut text, "Ship it, on Friday.", true), 2);
+        assert_eq!(text, "so the plan is to ship it on Friday.");
+
+        // Without overlap a repeat is kept: it was really said twice
+        let mut text = String::from("that");
+        stitch(&mut text, "that is all", false);
+        assert_eq!(text, "that that is all");
+
+        let mut text = String::new();
+        stitch(&mut text, "  hello ", false);
+        stitch(&mut text, "", true);
+        assert_eq!(text, "hello");
+    }
+
+    #[test]
+    fn two_minute_dictation_is_transcribed_completely() {
+        let pauses: Vec<usize> = (1..14).map(|i| i * 9000).collect();
+        let audio = speech_with_pauses(120_000, &pauses);
+        let mut prompts = Vec::new();
+        let mut decoded = 0;
+        let transcript = transcribe_long(&audio, RATE, params(), |seg, prompt| {
+            prompts.push(prompt.to_string());
+            decoded += seg.len();
+            Ok(Transcript::from_text(format!("part{}", prompts.len()), seg.len() as f64 / RATE as f64))
+        })
+        .unwrap();
+
+        assert_eq!(decoded, audio.len());
+        let n = prompts.len();
+        assert!(n >= 12);
+        let expected: Vec<String> = (1..=n).map(|i| format!("part{i}")).collect();
+        assert_eq!(transcript.text, expected.join(" "));
+        assert_eq!(prompts[0], "");
+        assert_eq!(prompts[1], "part1");
+
+        // Segment times are relative to the whole recording
+        assert_eq!(transcript.segments.len(), n);
+        assert_eq!(transcript.segments[0].start_secs, 0.0);
+        assert_eq!(transcript.segments[n - 1].end_secs, 120.0);
+        for pair in transcript.segments.windows(2) {
+            assert_eq!(pair[0].end_secs, pair[1].start_secs);
+        }
+    }
+
+    #[test]
+    fn overlapping_words_are_dropped_from_segments() {
+        let audio = vec![0.3; 15_000];
+        let parts = [
+            vec![("so the plan", 0.0, 4.0), ("is to ship it", 4.0, 9.0)],
+            vec![("ship it", 0.0, 0.5), ("on Friday", 0.5, 5.0)],
+        ];
+        let mut calls = 0;
+        let transcript = transcribe_long(&audio, RATE, params(), |_, _| {
+            let segments = parts[calls]
+                .iter()
+                .map(|&(text, start, end)| TranscriptSegment {
+                    text: text.into(),
+                    start_secs: start,
+                    end_secs: end,
+                    ..Default::default()
+                })
+                .collect();
+            calls += 1;
+            Ok(Transcript::from_segments(segments, Some("en".into())))
+        })
+        .unwrap();
+
+        assert_eq!(transcript.text, "so the plan is to ship it on Friday");
+        let texts: Vec<&str> = transcript.segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["so the plan", "is to ship it", "on Friday"]);
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+    }
+}
@@ -1,15 +1,22 @@
 //! Speech-to-Text — pluggable trait + factory.
 
+pub mod chunked;
+pub mod longform;
+pub mod transcript;
 #[cfg(feature = "stt-voxtral-http")]
 pub mod voxtral_http;
+pub mod windowed;
+
+pub use transcript::{Transcript, TranscriptSegment};
 
 use std::path::{Path, PathBuf};
 
 use crate::config::SttConfig;
 
-/// Load a WAV file and return its f32 PCM samples and sample rate.
+/// Load a WAV file and return its mono f32 PCM samples and sample rate.
 ///
-/// Handles both 16-bit integer and 32-bit float WAV formats.
+/// Handles both 16-bit integer and 32-bit float WAV formats; multi-channel
+/// files are downmixed.
 pub fn load_wav_pcm(path: &Path) -> anyhow::Result<(Vec<f32>, u32)> {
     let reader = hound::WavReader::open(path)?;
     let spec = reader.spec();
@@ -24,6 +31,11 @@ pub fn load_wav_pcm(path: &Path) -> anyhow::Result<(Vec<f32>, u32)> {
                 .collect::<Result<_, _>>()?
         }
     };
+    let samples = if spec.channels > 1 {
+        crate::audio::downmix_to_mono(&samples, spec.channels)
+    } else {
+        samples
+    };
     Ok((samples, spec.sample_rate))
 }
 
@@ -58,6 +70,26 @@ pub trait Transcriber: Send + Sync {
         self.transcribe(tmp.path())
     }
 
+    /// Transcribe raw PCM into a [`Transcript`] with segment timing, language
+    /// and confidence.
+    ///
+    /// Default implementation wraps `transcribe_pcm` in one segment spanning
+    /// the audio, with no language or confidence. Backends that know more
+    /// (whisper-native, whisper-cpp) override this.
+    fn transcribe_detailed(&self, samples: &[f32], sample_rate: u32) -> anyhow::Result<Transcript> {
+        let text = self.transcribe_pcm(samples, sample_rate)?;
+        Ok(Transcript::from_text(text, samples.len() as f64 / sample_rate.max(1) as f64))
+    }
+
+    /// Start an incremental session for PCM arriving at `sample_rate`.
+    ///
+    /// Default implementation buffers all audio and transcribes it once on
+    /// `finish()`. Backends that can decode progressively override this
+    /// (see [`windowed::WindowedSession`] and [`chunked::ChunkedSession`]).
+    fn start_session(&self, sample_rate: u32) -> anyhow::Result<Box<dyn TranscriptionSession + '_>> {
+        Ok(Box::new(BufferedSession { stt: self, sample_rate, samples: Vec::new() }))
+    }
+
     /// Human-readable name for logs and UI.
     fn name(&self) -> &str;
     /// Check if the backend is reachable / functional.
@@ -65,6 +97,51 @@ pub trait Transcriber: Send + Sync {
     fn is_available(&self) -> bool;
 }
 
+// ── Streaming sessions ──────────────────────────────────────────────────
+
+/// Incremental result from a [`TranscriptionSession`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionUpdate {
+    /// Tentative text for audio not yet finalized — replaces the previous partial.
+    Partial(String),
+    /// Stable text that will not be revised — follows earlier finals.
+    Final(String),
+}
+
+/// Incremental transcription: push PCM chunks as they are captured and
+/// receive partial and finalized hypotheses along the way.
+pub trait TranscriptionSession: Send {
+    /// Append mono PCM at the session's sample rate.
+    fn push_pcm(&mut self, samples: &[f32]) -> anyhow::Result<Vec<SessionUpdate>>;
+    /// Decode any remaining audio. The returned updates end with the last
+    /// `Final`; the session should not be used afterwards.
+    fn finish(&mut self) -> anyhow::Result<Vec<SessionUpdate>>;
+}
+
+/// Session used by backends without incremental decoding: buffers all audio
+/// and emits a single `Final` on `finish()`.
+struct BufferedSession<'a, T: Transcriber + ?Sized> {
+    stt: &'a T,
+    sample_rate: u32,
+    samples: Vec<f32>,
+}
+
+impl<T: Transcriber + ?Sized> TranscriptionSession for BufferedSession<'_, T> {
+    fn push_pcm(&mut self, samples: &[f32]) -> anyhow::Result<Vec<SessionUpdate>> {
+        self.samples.extend_from_slice(samples);
+        Ok(vec![])
+    }
+
+    fn finish(&mut self) -> anyhow::Result<Vec<SessionUpdate>> {
+        if self.samples.is_empty() {
+            return Ok(vec![]);
+        }
+        let samples = std::mem::take(&mut self.samples);
+        let text = self.stt.transcribe_pcm(&samples, self.sample_rate)?;
+        Ok(vec![SessionUpdate::Final(text)])
+    }
+}
+
 /// Function signature for an external factory that can create heavy STT backends.
 ///
 /// Called by `create_transcriber()` for backend names it doesn't know.
@@ -259,6 +336,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn load_wav_pcm_downmixes_stereo() {
+        let tmp = tempfile::Builder::new().suffix(".wav").tempfile().unwrap();
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 48000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut w = hound::WavWriter::create(tmp.path(), spec).unwrap();
+        for s in [0.5f32, -0.5, 1.0, 0.0] {
+            w.write_sample(s).unwrap();
+        }
+        w.finalize().unwrap();
+
+        let (pcm, rate) = load_wav_pcm(tmp.path()).unwrap();
+        assert_eq!(rate, 48000);
+        assert_eq!(pcm, vec![0.0, 0.5]);
+    }
+
     #[test]
     fn load_wav_pcm_nonexistent_file_returns_error() {
         let result = load_wav_pcm(Path::new("/tmp/nonexistent_wav_file_12345.wav"));
@@ -286,6 +383,32 @@ mod tests {
         assert_eq!(result, "5@16000");
     }
 
+    #[test]
+    fn transcribe_detailed_default_wraps_text() {
+        let t = MockWavTranscriber;
+        let transcript = t.transcribe_detailed(&[0.1; 8000], 16000).unwrap();
+        assert_eq!(transcript.text, "8000@16000");
+        assert_eq!(transcript.segments.len(), 1);
+        assert_eq!(transcript.segments[0].end_secs, 0.5);
+        assert_eq!(transcript.language, None);
+    }
+
+    #[test]
+    fn default_session_buffers_until_finish() {
+        let t = MockWavTranscriber;
+        let mut session = t.start_session(16000).unwrap();
+        assert!(session.push_pcm(&[0.1; 3]).unwrap().is_empty());
+        assert!(session.push_pcm(&[0.2; 2]).unwrap().is_empty());
+        assert_eq!(session.finish().unwrap(), vec![SessionUpdate::Final("5@16000".into())]);
+    }
+
+    #[test]
+    fn default_session_without_audio_emits_nothing() {
+        let t = MockWavTranscriber;
+        let mut session = t.start_session(16000).unwrap();
+        assert!(session.finish().unwrap().is_empty());
+    }
+
     #[test]
     fn transcribe_pcm_default_preserves_sample_values() {
         use std::sync::Mutex;
@@ -0,0 +1,117 @@
+//! Structured transcription result: text plus timing, language and confidence.
+
+/// A transcription with per-segment detail.
+///
+/// Backends that only produce text return a single segment spanning the
+/// whole audio with no confidence figures (see [`Transcript::from_text`]).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    /// The full text, as returned by [`super::Transcriber::transcribe_pcm`].
+    pub text: String,
+    /// Timed segments in order; their texts joined with spaces make `text`.
+    pub segments: Vec<TranscriptSegment>,
+    /// Language code (e.g. `"en"`), detected or configured, if known.
+    pub language: Option<String>,
+}
+
+/// One timed stretch of a [`Transcript`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranscriptSegment {
+    pub text: String,
+    /// Start of the segment, in seconds from the start of the audio.
+    pub start_secs: f64,
+    /// End of the segment, in seconds from the start of the audio.
+    pub end_secs: f64,
+    /// Mean log-probability of the decoded tokens (closer to 0 is more
+    /// confident), if the backend reports it.
+    pub avg_logprob: Option<f32>,
+    /// Model's probability that the audio holds no speech, if reported.
+    pub no_speech_prob: Option<f32>,
+}
+
+impl Transcript {
+    /// Wrap plain text from a backend without timing detail: one segment
+    /// covering `duration_secs`, or none if `text` is empty.
+    pub fn from_text(text: String, duration_secs: f64) -> Self {
+        let segments = if text.is_empty() {
+            Vec::new()
+        } else {
+            vec![TranscriptSegment { text: text.clone(), end_secs: duration_secs, ..Default::default() }]
+        };
+        Self { text, segments, language: None }
+    }
+
+    /// Build a transcript from segments, joining their texts.
+    pub fn from_segments(segments: Vec<TranscriptSegment>, language: Option<String>) -> Self {
+        let text = segments
+            .iter()
+            .map(|s| s.text.trim())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Self { text, segments, language }
+    }
+
+    /// Mean log-probability over the segments that report one, weighted by
+    /// segment duration.
+    pub fn avg_logprob(&self) -> Option<f32> {
+        let (sum, weight) = self
+            .segments
+            .iter()
+            .filter_map(|s| s.avg_logprob.map(|p| (p as f64, (s.end_secs - s.start_secs).max(0.01))))
+            .fold((0.0, 0.0), |(sum, weight), (p, w)| (sum + p * w, weight + w));
+        (weight > 0.0).then(|| (sum / weight) as f32)
+    }
+
+    /// Shift all segment times by `offset_secs`, for audio that started later
+    /// in a longer recording.
+    pub fn offset(&mut self, offset_secs: f64) {
+        for s in &mut self.segments {
+            s.start_secs += offset_secs;
+            s.end_secs += offset_secs;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(text: &str, start: f64, end: f64, logprob: Option<f32>) -> TranscriptSegment {
+        TranscriptSegment { text: text.into(), start_secs: start, end_secs: end, avg_logprob: logprob, no_speech_prob: None }
+    }
+
+    #[test]
+    fn from_text_spans_the_audio() {
+        let t = Transcript::from_text("hello world".into(), 2.5);
+        assert_eq!(t.segments, vec![seg("hello world", 0.0, 2.5, None)]);
+        assert_eq!(t.avg_logprob(), None);
+        assert!(Transcript::from_text(String::new(), 1.0).segments.is_empty());
+    }
+
+    #[test]
+    fn from_segments_joins_text() {
+        let t = Transcript::from_segments(
+            vec![seg(" Hello there.", 0.0, 1.0, None), seg("", 1.0, 1.5, None), seg(" How are you?", 1.5, 3.0, None)],
+            Some("en".into()),
+        );
+        assert_eq!(t.text, "Hello there. How are you?");
+        assert_eq!(t.language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn avg_logprob_is_duration_weighted() {
+        let t = Transcript::from_segments(
+            vec![seg("a", 0.0, 3.0, Some(-0.2)), seg("b", 3.0, 4.0, Some(-1.0)), seg("c", 4.0, 9.0, None)],
+            None,
+        );
+        assert!((t.avg_logprob().unwrap() - -0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn offset_shifts_segments() {
+        let mut t = Transcript::from_segments(vec![seg("a", 0.5, 2.0, None)], None);
+        t.offset(30.0);
+        assert_eq!((t.segments[0].start_secs, t.segments[0].end_secs), (30.5, 32.0));
+    }
+}
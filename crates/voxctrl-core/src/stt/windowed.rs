@@ -0,0 +1,278 @@
+//! Windowed re-decode session — progressive transcription for backends that
+//! only decode whole buffers.
+//!
+//! Every `step` of new audio the current window is re-decoded. Words on which
+//! two consecutive hypotheses agree are finalized (local agreement); the rest
+//! is reported as a partial. Once the window reaches its maximum length it is
+//! cut at the quietest point near its end, the head is finalized, and
+//! decoding continues on the remainder.
+
+use super::{SessionUpdate, Transcriber, TranscriptionSession};
+
+/// Length of the frames compared when looking for a quiet cut point.
+pub(crate) const CUT_FRAME_MS: u32 = 50;
+
+/// Timing parameters for a [`WindowedSession`].
+#[derive(Debug, Clone, Copy)]
+pub struct WindowParams {
+    /// New audio required before re-decoding the window.
+    pub step_ms: u32,
+    /// Maximum window length before it is cut and finalized.
+    pub max_window_ms: u32,
+    /// Tail of the window searched for the quietest cut point.
+    pub cut_search_ms: u32,
+}
+
+impl Default for WindowParams {
+    fn default() -> Self {
+        Self {
+            step_ms: 1000,
+            max_window_ms: 24_000,
+            cut_search_ms: 4000,
+        }
+    }
+}
+
+pub struct WindowedSession<'a, T: Transcriber + ?Sized> {
+    stt: &'a T,
+    sample_rate: u32,
+    step: usize,
+    max_window: usize,
+    cut_search: usize,
+    window: Vec<f32>,
+    since_decode: usize,
+    /// Words of the previous hypothesis for the current window.
+    prev_words: Vec<String>,
+    /// Leading words of the current window already emitted as `Final`.
+    committed: usize,
+}
+
+impl<'a, T: Transcriber + ?Sized> WindowedSession<'a, T> {
+    pub fn new(stt: &'a T, sample_rate: u32, params: WindowParams) -> Self {
+        let samples = |ms: u32| ((sample_rate as u64 * ms as u64) / 1000) as usize;
+        let max_window = samples(params.max_window_ms).max(1);
+        Self {
+            stt,
+            sample_rate,
+            step: samples(params.step_ms).max(1),
+            max_window,
+            cut_search: samples(params.cut_search_ms).min(max_window),
+            window: Vec::new(),
+            since_decode: 0,
+            prev_words: Vec::new(),
+            committed: 0,
+        }
+    }
+
+    fn decode_words(&self, samples: &[f32]) -> anyhow::Result<Vec<String>> {
+        let text = self.stt.transcribe_pcm(samples, self.sample_rate)?;
+        Ok(text.split_whitespace().map(str::to_string).collect())
+    }
+
+    /// Cut the full window at its quietest point and finalize the head.
+    fn cut_window(&mut self, updates: &mut Vec<SessionUpdate>) -> anyhow::Result<()> {
+        let frame = ((self.sample_rate * CUT_FRAME_MS) / 1000).max(1) as usize;
+        let cut = quietest_cut(&self.window, self.cut_search, frame);
+        let words = self.decode_words(&self.window[..cut])?;
+        if words.len() > self.committed {
+            updates.push(SessionUpdate::Final(words[self.committed..].join(" ")));
+        }
+        log::debug!(
+            "Windowed session: cut {:.1}s window at {:.1}s",
+            self.window.len() as f64 / self.sample_rate as f64,
+            cut as f64 / self.sample_rate as f64,
+        );
+        self.window.drain(..cut);
+        self.prev_words.clear();
+        self.committed = 0;
+        Ok(())
+    }
+}
+
+impl<T: Transcriber + ?Sized> TranscriptionSession for WindowedSession<'_, T> {
+    fn push_pcm(&mut self, samples: &[f32]) -> anyhow::Result<Vec<SessionUpdate>> {
+        self.window.extend_from_slice(samples);
+        self.since_decode += samples.len();
+        if self.since_decode < self.step {
+            return Ok(vec![]);
+        }
+        self.since_decode = 0;
+
+        let mut updates = Vec::new();
+        if self.window.len() >= self.max_window {
+            self.cut_window(&mut updates)?;
+        }
+
+        let words = self.decode_words(&self.window)?;
+        let agreed = words
+            .iter()
+            .zip(&self.prev_words)
+            .take_while(|(a, b)| a == b)
+            .count();
+        if agreed > self.committed {
+            updates.push(SessionUpdate::Final(words[self.committed..agreed].join(" ")));
+            self.committed = agreed;
+        }
+        let partial_from = self.committed.min(words.len());
+        updates.push(SessionUpdate::Partial(words[partial_from..].join(" ")));
+        self.prev_words = words;
+        Ok(updates)
+    }
+
+    fn finish(&mut self) -> anyhow::Result<Vec<SessionUpdate>> {
+        let mut updates = Vec::new();
+        if !self.window.is_empty() {
+            let window = std::mem::take(&mut self.window);
+            let words = self.decode_words(&window)?;
+            if words.len() > self.committed {
+                updates.push(SessionUpdate::Final(words[self.committed..].join(" ")));
+            }
+        }
+        updates.push(SessionUpdate::Partial(String::new()));
+        self.prev_words.clear();
+        self.committed = 0;
+        self.since_decode = 0;
+        Ok(updates)
+    }
+}
+
+/// Index at which to cut `samples`: the start of the lowest-energy frame
+/// within the last `search` samples (or the end if there is nothing to search).
+pub(crate) fn quietest_cut(samples: &[f32], search: usize, frame: usize) -> usize {
+    let start = samples.len().saturating_sub(search);
+    samples[start..]
+        .chunks_exact(frame)
+        .enumerate()
+        .map(|(i, f)| (start + i * frame, f.iter().map(|s| s * s).sum::<f32>()))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(idx, _)| idx.max(1))
+        .unwrap_or(samples.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    /// Returns scripted hypotheses in order and records decoded lengths.
+    struct ScriptedTranscriber {
+        script: Mutex<VecDeque<&'static str>>,
+        lengths: Mutex<Vec<usize>>,
+    }
+
+    impl ScriptedTranscriber {
+        fn new(script: &[&'static str]) -> Self {
+            Self {
+                script: Mutex::new(script.iter().copied().collect()),
+                lengths: Mutex::new(vec![]),
+            }
+        }
+    }
+
+    impl Transcriber for ScriptedTranscriber {
+        fn transcribe(&self, _: &Path) -> anyhow::Result<String> {
+            unreachable!()
+        }
+        fn transcribe_pcm(&self, samples: &[f32], _: u32) -> anyhow::Result<String> {
+            self.lengths.lock().unwrap().push(samples.len());
+            Ok(self.script.lock().unwrap().pop_front().unwrap_or_default().into())
+        }
+        fn name(&self) -> &str { "scripted" }
+        fn is_available(&self) -> bool { true }
+    }
+
+    /// 1 kHz: 100 ms step, 1 s max window, 300 ms cut search.
+    fn params() -> WindowParams {
+        WindowParams { step_ms: 100, max_window_ms: 1000, cut_search_ms: 300 }
+    }
+
+    fn finals(updates: &[SessionUpdate]) -> Vec<&str> {
+        updates
+            .iter()
+            .filter_map(|u| match u {
+                SessionUpdate::Final(t) => Some(t.as_str()),
+                SessionUpdate::Partial(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn no_decode_before_step() {
+        let stt = ScriptedTranscriber::new(&["hello"]);
+        let mut session = WindowedSession::new(&stt, 1000, params());
+        assert!(session.push_pcm(&[0.1; 50]).unwrap().is_empty());
+        assert!(stt.lengths.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn first_decode_is_partial_only() {
+        let stt = ScriptedTranscriber::new(&["hello wor"]);
+        let mut session = WindowedSession::new(&stt, 1000, params());
+        let updates = session.push_pcm(&[0.1; 100]).unwrap();
+        assert_eq!(updates, vec![SessionUpdate::Partial("hello wor".into())]);
+    }
+
+    #[test]
+    fn agreed_prefix_is_finalized() {
+        let stt = ScriptedTranscriber::new(&["hello wor", "hello world how", "hello world how are"]);
+        let mut session = WindowedSession::new(&stt, 1000, params());
+        session.push_pcm(&[0.1; 100]).unwrap();
+
+        let updates = session.push_pcm(&[0.1; 100]).unwrap();
+        assert_eq!(
+            updates,
+            vec![
+                SessionUpdate::Final("hello".into()),
+                SessionUpdate::Partial("world how".into()),
+            ]
+        );
+
+        let updates = session.push_pcm(&[0.1; 100]).unwrap();
+        assert_eq!(
+            updates,
+            vec![
+                SessionUpdate::Final("world how".into()),
+                SessionUpdate::Partial("are".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn finish_finalizes_remaining_words() {
+        let stt = ScriptedTranscriber::new(&["hello wor", "hello world", "hello world again"]);
+        let mut session = WindowedSession::new(&stt, 1000, params());
+        session.push_pcm(&[0.1; 100]).unwrap();
+        session.push_pcm(&[0.1; 100]).unwrap();
+        let updates = session.finish().unwrap();
+        assert_eq!(
+            updates,
+            vec![
+                SessionUpdate::Final("world again".into()),
+                SessionUpdate::Partial(String::new()),
+            ]
+        );
+        assert_eq!(*stt.lengths.lock().unwrap(), vec![100, 200, 200]);
+    }
+
+    #[test]
+    fn full_window_is_cut_at_quietest_point() {
+        let stt = ScriptedTranscriber::new(&["one two", "three"]);
+        let mut session = WindowedSession::new(&stt, 1000, WindowParams { step_ms: 1000, ..params() });
+        // Loud audio with a silent gap at 800..850 ms
+        let mut audio = vec![0.5f32; 1000];
+        audio[800..850].iter_mut().for_each(|s| *s = 0.0);
+
+        let updates = session.push_pcm(&audio).unwrap();
+        assert_eq!(finals(&updates), vec!["one two"]);
+        assert_eq!(updates.last(), Some(&SessionUpdate::Partial("three".into())));
+        // Head decoded up to the gap, then the remainder
+        assert_eq!(*stt.lengths.lock().unwrap(), vec![800, 200]);
+    }
+
+    #[test]
+    fn quietest_cut_without_search_region_cuts_at_end() {
+        assert_eq!(quietest_cut(&[0.1; 100], 0, 10), 100);
+    }
+}
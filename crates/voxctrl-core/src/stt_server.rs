@@ -76,7 +76,8 @@ fn handle_connection(mut stream: impl Read + Write, pipeline: &Pipeline) -> Resu
         .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
         .collect();
 
-    let result = pipeline.stt.transcribe_pcm(&samples, sample_rate);
+    let samples = crate::audio::resample::to_16k(&samples, sample_rate);
+    let result = pipeline.stt.transcribe_pcm(&samples, crate::audio::resample::STT_SAMPLE_RATE);
 
     match result {
         Ok(text) => send_ok(&mut stream, &text),
@@ -164,9 +165,12 @@ mod tests {
 
     fn make_pipeline(stt: Box<dyn Transcriber>) -> Pipeline {
         Pipeline {
-            stt,
+            stt: stt.into(),
+            postprocess: vec![],
             router: Box::new(NoopRouter),
             action: Box::new(NoopAction),
+            hallucination: None,
+            history: None,
         }
     }
 
@@ -240,6 +244,21 @@ mod tests {
         let response = &data[8..]; // 8 header + 0 PCM
         let (status, text) = decode_response(response);
         assert_eq!(status, 0);
-        assert_eq!(text, "n=0,sr=44100");
+        assert_eq!(text, "n=0,sr=16000");
+    }
+
+    #[test]
+    fn wire_protocol_resamples_to_16k() {
+        let pipeline = make_pipeline(Box::new(EchoTranscriber));
+        let request = encode_request(44100, &[0.0; 4410]);
+
+        let mut stream = Cursor::new(request);
+        handle_connection(&mut stream, &pipeline).unwrap();
+
+        let data = stream.into_inner();
+        let response = &data[8 + 4410 * 4..];
+        let (status, text) = decode_response(response);
+        assert_eq!(status, 0);
+        assert_eq!(text, "n=1600,sr=16000");
     }
 }
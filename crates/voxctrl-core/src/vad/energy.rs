@@ -1,16 +1,30 @@
-//! Energy-based Voice Activity Detection — simple RMS threshold.
+//! Energy-based Voice Activity Detection — a fixed RMS threshold, or an
+//! adaptive detector that follows the room's noise floor.
 
 use super::VoiceDetector;
+use crate::audio::filter::db_to_gain;
+use crate::config::VadConfig;
+
+/// Lowest noise floor tracked (-60 dBFS), so digital silence doesn't make
+/// the thresholds vanish.
+const MIN_FLOOR: f64 = 1e-3;
+/// Time constant for the floor falling to a quieter room.
+const FLOOR_FALL_TAU_SECS: f64 = 0.2;
+/// How fast the floor may rise towards louder audio. Slow enough that the
+/// pauses in speech pull it back down, fast enough to learn a fan switched
+/// on within seconds. It also rises during speech, or a steady noise that
+/// started as "speech" would never end.
+const FLOOR_RISE_DB_PER_SEC: f64 = 1.0;
+/// Block length analysed by [`calibrate`].
+const CALIBRATION_BLOCK_MS: u32 = 20;
 
 /// Simple energy-based VAD that computes the RMS of audio samples
 /// and compares against a configurable threshold.
-#[allow(dead_code)]
 pub struct EnergyVad {
     threshold: f64,
 }
 
 impl EnergyVad {
-    #[allow(dead_code)]
     pub fn new(threshold: f64) -> Self {
         Self { threshold }
     }
@@ -21,9 +35,7 @@ impl VoiceDetector for EnergyVad {
         if samples.is_empty() {
             return false;
         }
-        let sum_sq: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
-        let rms = (sum_sq / samples.len() as f64).sqrt();
-        rms > self.threshold
+        rms(samples) > self.threshold
     }
 
     fn name(&self) -> &str {
@@ -31,9 +43,121 @@ impl VoiceDetector for EnergyVad {
     }
 }
 
+/// Energy VAD that tracks the noise floor and decides with hysteresis:
+/// speech starts once the level stays `onset_margin_db` above the floor for
+/// `min_speech_ms`, and ends once it stays under `offset_margin_db` above
+/// the floor for `min_silence_ms`.
+pub struct AdaptiveEnergyVad {
+    /// Noise floor RMS, seeded from `energy_threshold`.
+    floor: f64,
+    onset_gain: f64,
+    offset_gain: f64,
+    min_speech_secs: f64,
+    min_silence_secs: f64,
+    speaking: bool,
+    /// Seconds the level has disagreed with `speaking`.
+    pending_secs: f64,
+}
+
+impl AdaptiveEnergyVad {
+    pub fn new(cfg: &VadConfig) -> Self {
+        let onset_gain = db_to_gain(cfg.onset_margin_db) as f64;
+        // Hysteresis needs the offset at or below the onset
+        let offset_gain = (db_to_gain(cfg.offset_margin_db) as f64).min(onset_gain);
+        Self {
+            floor: (cfg.energy_threshold / onset_gain).max(MIN_FLOOR),
+            onset_gain,
+            offset_gain,
+            min_speech_secs: cfg.min_speech_ms as f64 / 1000.0,
+            min_silence_secs: cfg.min_silence_ms as f64 / 1000.0,
+            speaking: false,
+            pending_secs: 0.0,
+        }
+    }
+
+    /// Current noise floor estimate (RMS).
+    pub fn noise_floor(&self) -> f64 {
+        self.floor
+    }
+
+    fn track_floor(&mut self, rms: f64, secs: f64) {
+        if rms < self.floor {
+            // Geometric approach, so the fall is even in dB
+            let k = 1.0 - (-secs / FLOOR_FALL_TAU_SECS).exp();
+            self.floor *= (rms.max(MIN_FLOOR) / self.floor).powf(k);
+        } else {
+            let rise = db_to_gain((FLOOR_RISE_DB_PER_SEC * secs) as f32) as f64;
+            self.floor = rms.min(self.floor * rise);
+        }
+        self.floor = self.floor.max(MIN_FLOOR);
+    }
+}
+
+impl VoiceDetector for AdaptiveEnergyVad {
+    fn is_speech(&mut self, samples: &[f32], sample_rate: u32) -> bool {
+        if samples.is_empty() {
+            return self.speaking;
+        }
+        let rms = rms(samples);
+        let secs = samples.len() as f64 / sample_rate.max(1) as f64;
+
+        let disagrees = if self.speaking {
+            rms < self.floor * self.offset_gain
+        } else {
+            rms > self.floor * self.onset_gain
+        };
+        if disagrees {
+            self.pending_secs += secs;
+            let needed = if self.speaking { self.min_silence_secs } else { self.min_speech_secs };
+            if self.pending_secs >= needed {
+                self.speaking = !self.speaking;
+                self.pending_secs = 0.0;
+            }
+        } else {
+            self.pending_secs = 0.0;
+        }
+
+        self.track_floor(rms, secs);
+        self.speaking
+    }
+
+    fn name(&self) -> &str {
+        "energy-adaptive"
+    }
+}
+
+/// Result of measuring the ambient noise with [`calibrate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    /// Ambient noise level (RMS), taken from the louder blocks so brief lulls
+    /// don't understate it.
+    pub noise_rms: f64,
+    /// Proposed `energy_threshold`: the noise level plus the onset margin.
+    pub threshold: f64,
+}
+
+/// Measure a few seconds of `ambient` audio (nobody talking) and propose an
+/// energy threshold `onset_margin_db` above it. `None` for under one block.
+pub fn calibrate(ambient: &[f32], sample_rate: u32, onset_margin_db: f32) -> Option<Calibration> {
+    let block = (sample_rate * CALIBRATION_BLOCK_MS / 1000).max(1) as usize;
+    let mut levels: Vec<f64> = ambient.chunks_exact(block).map(rms).collect();
+    if levels.is_empty() {
+        return None;
+    }
+    levels.sort_by(f64::total_cmp);
+    let noise_rms = levels[(levels.len() - 1) * 9 / 10].max(MIN_FLOOR);
+    Some(Calibration { noise_rms, threshold: noise_rms * db_to_gain(onset_margin_db) as f64 })
+}
+
+fn rms(samples: &[f32]) -> f64 {
+    let sum_sq: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+    (sum_sq / samples.len() as f64).sqrt()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audio::test_util;
 
     #[test]
     fn silence_is_not_speech() {
@@ -78,4 +202,73 @@ mod tests {
             .collect();
         assert!(vad.is_speech(&samples, 16000));
     }
+
+    const RATE: u32 = 16_000;
+    /// 20 ms frames, as the segmenter feeds them.
+    const FRAME: usize = 320;
+
+    fn adaptive() -> AdaptiveEnergyVad {
+        AdaptiveEnergyVad::new(&VadConfig { energy_adaptive: true, ..VadConfig::default() })
+    }
+
+    /// Deterministic noise with the given RMS.
+    fn noise(len: usize, rms: f32, seed: u32) -> Vec<f32> {
+        // Uniform in [-a, a] has RMS a/sqrt(3)
+        test_util::noise(len, rms * 3f32.sqrt(), seed)
+    }
+
+    /// Run `secs` of noise at `level` through the VAD, returning per-frame decisions.
+    fn feed(vad: &mut AdaptiveEnergyVad, secs: f32, level: f32) -> Vec<bool> {
+        let samples = noise((secs * RATE as f32) as usize, level, 42);
+        samples.chunks(FRAME).map(|f| vad.is_speech(f, RATE)).collect()
+    }
+
+    #[test]
+    fn adaptive_learns_louder_room() {
+        let mut vad = adaptive();
+        assert!(!feed(&mut vad, 2.0, 0.003).iter().any(|&s| s));
+        // A fan switches on well above the fixed default threshold
+        let fan = feed(&mut vad, 30.0, 0.04);
+        assert!(!fan[fan.len() - 50..].iter().any(|&s| s), "floor {}", vad.noise_floor());
+        // Speech over the fan is still detected
+        assert!(feed(&mut vad, 0.5, 0.3).iter().any(|&s| s));
+    }
+
+    #[test]
+    fn adaptive_holds_speech_between_thresholds() {
+        let mut vad = adaptive();
+        feed(&mut vad, 2.0, 0.003);
+        assert!(*feed(&mut vad, 0.3, 0.1).last().unwrap());
+        // Dropped below the onset but still above the offset threshold
+        let floor = vad.noise_floor() as f32;
+        let between = floor * db_to_gain(8.0);
+        assert!(feed(&mut vad, 0.5, between).iter().all(|&s| s));
+    }
+
+    #[test]
+    fn adaptive_ignores_clicks_and_bridges_short_gaps() {
+        let mut vad = adaptive();
+        feed(&mut vad, 2.0, 0.003);
+        // One 20 ms click is shorter than min_speech_ms
+        assert!(!feed(&mut vad, 0.02, 0.3).iter().any(|&s| s));
+        assert!(!feed(&mut vad, 0.5, 0.003).iter().any(|&s| s));
+
+        assert!(*feed(&mut vad, 0.3, 0.3).last().unwrap());
+        // A 100 ms pause is shorter than min_silence_ms
+        assert!(feed(&mut vad, 0.1, 0.003).iter().all(|&s| s));
+        assert!(*feed(&mut vad, 0.2, 0.3).last().unwrap());
+        assert!(!*feed(&mut vad, 1.0, 0.003).last().unwrap());
+    }
+
+    #[test]
+    fn calibration_proposes_threshold_above_ambient() {
+        let ambient = noise(3 * RATE as usize, 0.01, 7);
+        let cal = calibrate(&ambient, RATE, 10.0).unwrap();
+        assert!((cal.noise_rms - 0.01).abs() < 0.003, "{cal:?}");
+        assert!((cal.threshold / cal.noise_rms - 10f64.powf(0.5)).abs() < 1e-3);
+        // The proposed threshold rejects the ambient noise it was measured on
+        let mut vad = EnergyVad::new(cal.threshold);
+        assert!(!ambient.chunks(FRAME).any(|f| vad.is_speech(f, RATE)));
+        assert_eq!(calibrate(&[0.0; 10], RATE, 10.0), None);
+    }
 }
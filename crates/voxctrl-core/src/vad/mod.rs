@@ -4,32 +4,64 @@
 pub mod energy;
 #[cfg(feature = "vad-silero")]
 pub mod silero;
+#[cfg(feature = "vad-spectral")]
+pub mod spectral;
+pub mod segmenter;
 
-use crate::config::VadConfig;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, VadConfig};
+use crate::models::{catalog, ModelRegistry};
 
 /// Trait for voice activity detection backends.
-#[allow(dead_code)]
 pub trait VoiceDetector: Send {
     /// Returns true if the audio chunk likely contains speech.
     fn is_speech(&mut self, samples: &[f32], sample_rate: u32) -> bool;
+    /// Forget state carried across chunks, once an utterance has ended.
+    fn reset(&mut self) {}
     fn name(&self) -> &str;
 }
 
-/// Create a VAD backend based on config.
-#[allow(dead_code)]
-pub fn create_vad(cfg: &VadConfig) -> anyhow::Result<Box<dyn VoiceDetector>> {
+/// Local directory of the model the configured VAD backend needs, from the
+/// model registry; `None` if it needs none or it isn't downloaded.
+pub fn vad_model_dir(cfg: &Config) -> Option<PathBuf> {
+    let id = catalog::required_vad_model_id(&cfg.vad.backend)?;
+    let mut registry = ModelRegistry::new(catalog::all_models());
+    registry.scan_cache(&cfg.models);
+    let dir = registry.model_path(&id);
+    if dir.is_none() {
+        log::warn!("VAD model '{id}' not found in the model registry");
+    }
+    dir
+}
+
+/// Create a VAD backend based on config. `model_dir` comes from [`vad_model_dir`].
+pub fn create_vad(cfg: &VadConfig, model_dir: Option<&Path>) -> anyhow::Result<Box<dyn VoiceDetector>> {
     match cfg.backend.as_str() {
         "energy" => {
             #[cfg(feature = "vad-energy")]
-            return Ok(Box::new(energy::EnergyVad::new(cfg.energy_threshold)));
+            return Ok(if cfg.energy_adaptive {
+                Box::new(energy::AdaptiveEnergyVad::new(cfg))
+            } else {
+                Box::new(energy::EnergyVad::new(cfg.energy_threshold))
+            });
             #[cfg(not(feature = "vad-energy"))]
             anyhow::bail!("vad-energy feature not compiled in");
         }
         "silero" => {
             #[cfg(feature = "vad-silero")]
-            return Ok(Box::new(silero::SileroVad::new(cfg.silero_threshold, None)?));
+            return Ok(Box::new(silero::SileroVad::new(cfg.silero_threshold, model_dir)?));
             #[cfg(not(feature = "vad-silero"))]
-            anyhow::bail!("vad-silero feature not compiled in");
+            {
+                let _ = model_dir;
+                anyhow::bail!("vad-silero feature not compiled in");
+            }
+        }
+        "spectral" => {
+            #[cfg(feature = "vad-spectral")]
+            return Ok(Box::new(spectral::SpectralVad::new(cfg.spectral_threshold)));
+            #[cfg(not(feature = "vad-spectral"))]
+            anyhow::bail!("vad-spectral feature not compiled in");
         }
         "none" => Ok(Box::new(NullVad)),
         other => anyhow::bail!("Unknown VAD backend: {other}"),
@@ -37,7 +69,6 @@ pub fn create_vad(cfg: &VadConfig) -> anyhow::Result<Box<dyn VoiceDetector>> {
 }
 
 /// No-op VAD that considers everything as speech.
-#[allow(dead_code)]
 struct NullVad;
 impl VoiceDetector for NullVad {
     fn is_speech(&mut self, _samples: &[f32], _sample_rate: u32) -> bool {
@@ -0,0 +1,273 @@
+//! Utterance segmenter — turns a continuous sample stream into discrete
+//! utterances using a `VoiceDetector`, for hands-free dictation.
+
+use std::collections::VecDeque;
+
+use super::VoiceDetector;
+use crate::config::VadConfig;
+
+/// Splits incoming audio into fixed-size frames, runs the detector on each,
+/// and emits complete utterances.
+///
+/// - An utterance opens on the first speech frame and includes up to
+///   `pre_roll_ms` of audio captured before it.
+/// - It closes once `trailing_silence_ms` of consecutive non-speech frames
+///   have been seen; the trailing silence is kept in the utterance.
+pub struct UtteranceSegmenter {
+    vad: Box<dyn VoiceDetector>,
+    sample_rate: u32,
+    frame_len: usize,
+    pre_roll_frames: usize,
+    hangover_frames: usize,
+    /// Samples not yet forming a full frame.
+    pending: Vec<f32>,
+    /// Most recent non-speech frames, kept while idle for pre-roll.
+    pre_roll: VecDeque<Vec<f32>>,
+    utterance: Vec<f32>,
+    in_speech: bool,
+    silent_frames: usize,
+}
+
+impl UtteranceSegmenter {
+    /// Build a segmenter for a stream at `sample_rate`, analysing `frame_ms` frames.
+    pub fn new(
+        vad: Box<dyn VoiceDetector>,
+        cfg: &VadConfig,
+        sample_rate: u32,
+        frame_ms: u32,
+    ) -> Self {
+        let frame_ms = frame_ms.max(1);
+        let frame_len = ((sample_rate as u64 * frame_ms as u64) / 1000).max(1) as usize;
+        Self {
+            vad,
+            sample_rate,
+            frame_len,
+            pre_roll_frames: cfg.pre_roll_ms.div_ceil(frame_ms) as usize,
+            hangover_frames: cfg.trailing_silence_ms.div_ceil(frame_ms).max(1) as usize,
+            pending: Vec::with_capacity(frame_len),
+            pre_roll: VecDeque::new(),
+            utterance: Vec::new(),
+            in_speech: false,
+            silent_frames: 0,
+        }
+    }
+
+    /// True while an utterance is open.
+    pub fn in_speech(&self) -> bool {
+        self.in_speech
+    }
+
+    /// Feed mono samples; returns any utterances completed by this call.
+    pub fn push(&mut self, samples: &[f32]) -> Vec<Vec<f32>> {
+        let mut done = Vec::new();
+        let mut rest = samples;
+        while !rest.is_empty() {
+            let take = (self.frame_len - self.pending.len()).min(rest.len());
+            self.pending.extend_from_slice(&rest[..take]);
+            rest = &rest[take..];
+            if self.pending.len() == self.frame_len {
+                let frame = std::mem::replace(&mut self.pending, Vec::with_capacity(self.frame_len));
+                if let Some(utt) = self.process_frame(frame) {
+                    done.push(utt);
+                }
+            }
+        }
+        done
+    }
+
+    /// Close any open utterance (e.g. when the stream stops).
+    pub fn flush(&mut self) -> Option<Vec<f32>> {
+        if !self.in_speech {
+            self.pending.clear();
+            return None;
+        }
+        self.utterance.append(&mut self.pending);
+        Some(self.finish())
+    }
+
+    fn process_frame(&mut self, frame: Vec<f32>) -> Option<Vec<f32>> {
+        let speech = self.vad.is_speech(&frame, self.sample_rate);
+
+        if !self.in_speech {
+            if !speech {
+                if self.pre_roll_frames > 0 {
+                    if self.pre_roll.len() == self.pre_roll_frames {
+                        self.pre_roll.pop_front();
+                    }
+                    self.pre_roll.push_back(frame);
+                }
+                return None;
+            }
+            log::debug!("VAD: speech onset ({})", self.vad.name());
+            self.in_speech = true;
+            self.silent_frames = 0;
+            for f in self.pre_roll.drain(..) {
+                self.utterance.extend_from_slice(&f);
+            }
+            self.utterance.extend_from_slice(&frame);
+            return None;
+        }
+
+        self.utterance.extend_from_slice(&frame);
+        if speech {
+            self.silent_frames = 0;
+            return None;
+        }
+        self.silent_frames += 1;
+        if self.silent_frames >= self.hangover_frames {
+            log::debug!("VAD: speech offset after {} silent frames", self.silent_frames);
+            return Some(self.finish());
+        }
+        None
+    }
+
+    fn finish(&mut self) -> Vec<f32> {
+        self.in_speech = false;
+        self.silent_frames = 0;
+        self.vad.reset();
+        std::mem::take(&mut self.utterance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Treats any frame whose peak exceeds 0.1 as speech.
+    struct PeakVad;
+    impl VoiceDetector for PeakVad {
+        fn is_speech(&mut self, samples: &[f32], _sample_rate: u32) -> bool {
+            samples.iter().any(|s| s.abs() > 0.1)
+        }
+        fn name(&self) -> &str {
+            "peak"
+        }
+    }
+
+    /// 1 kHz rate with 10 ms frames → 10 samples per frame.
+    fn segmenter(pre_roll_ms: u32, trailing_silence_ms: u32) -> UtteranceSegmenter {
+        let cfg = VadConfig {
+            pre_roll_ms,
+            trailing_silence_ms,
+            ..VadConfig::default()
+        };
+        UtteranceSegmenter::new(Box::new(PeakVad), &cfg, 1000, 10)
+    }
+
+    fn frames(value: f32, n: usize) -> Vec<f32> {
+        vec![value; n * 10]
+    }
+
+    #[test]
+    fn silence_produces_nothing() {
+        let mut seg = segmenter(20, 30);
+        assert!(seg.push(&frames(0.0, 50)).is_empty());
+        assert!(!seg.in_speech());
+        assert!(seg.flush().is_none());
+    }
+
+    #[test]
+    fn utterance_closes_after_trailing_silence() {
+        let mut seg = segmenter(0, 30);
+        assert!(seg.push(&frames(0.5, 4)).is_empty());
+        assert!(seg.in_speech());
+        // Two silent frames are not enough to close a 30 ms window
+        assert!(seg.push(&frames(0.0, 2)).is_empty());
+        let done = seg.push(&frames(0.0, 1));
+        assert_eq!(done.len(), 1);
+        // 4 speech frames + 3 trailing silent frames
+        assert_eq!(done[0].len(), 70);
+        assert!(!seg.in_speech());
+    }
+
+    #[test]
+    fn pre_roll_is_prepended() {
+        let mut seg = segmenter(20, 10);
+        let mut input = frames(0.01, 5);
+        input.extend(frames(0.5, 1));
+        input.extend(frames(0.0, 1));
+        let done = seg.push(&input);
+        assert_eq!(done.len(), 1);
+        // 2 pre-roll frames + 1 speech frame + 1 trailing frame
+        assert_eq!(done[0].len(), 40);
+        assert!(done[0][..20].iter().all(|&s| s == 0.01));
+        assert!(done[0][20..30].iter().all(|&s| s == 0.5));
+    }
+
+    #[test]
+    fn brief_pause_does_not_split_utterance() {
+        let mut seg = segmenter(0, 30);
+        let mut input = frames(0.5, 2);
+        input.extend(frames(0.0, 2));
+        input.extend(frames(0.5, 2));
+        input.extend(frames(0.0, 3));
+        let done = seg.push(&input);
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0].len(), 90);
+    }
+
+    #[test]
+    fn multiple_utterances_in_one_push() {
+        let mut seg = segmenter(0, 10);
+        let mut input = frames(0.5, 1);
+        input.extend(frames(0.0, 3));
+        input.extend(frames(0.5, 1));
+        input.extend(frames(0.0, 1));
+        assert_eq!(seg.push(&input).len(), 2);
+    }
+
+    #[test]
+    fn partial_frames_accumulate_across_pushes() {
+        let mut seg = segmenter(0, 10);
+        // 15 + 5 samples of speech = two full frames
+        assert!(seg.push(&[0.5; 15]).is_empty());
+        assert!(seg.push(&[0.5; 5]).is_empty());
+        let done = seg.push(&[0.0; 10]);
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0].len(), 30);
+    }
+
+    #[test]
+    fn flush_returns_open_utterance() {
+        let mut seg = segmenter(0, 100);
+        seg.push(&frames(0.5, 3));
+        seg.push(&[0.5; 4]);
+        let utt = seg.flush().expect("open utterance");
+        assert_eq!(utt.len(), 34);
+        assert!(!seg.in_speech());
+        assert!(seg.flush().is_none());
+    }
+
+    /// Counts resets, sharing the count with the test.
+    struct ResetCountingVad(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+    impl VoiceDetector for ResetCountingVad {
+        fn is_speech(&mut self, samples: &[f32], sample_rate: u32) -> bool {
+            PeakVad.is_speech(samples, sample_rate)
+        }
+        fn reset(&mut self) {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+        fn name(&self) -> &str {
+            "counting"
+        }
+    }
+
+    #[test]
+    fn vad_is_reset_after_each_utterance() {
+        use std::sync::atomic::Ordering;
+        let resets = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let cfg = VadConfig { pre_roll_ms: 0, trailing_silence_ms: 20, ..VadConfig::default() };
+        let vad = Box::new(ResetCountingVad(resets.clone()));
+        let mut seg = UtteranceSegmenter::new(vad, &cfg, 1000, 10);
+
+        seg.push(&frames(0.0, 5));
+        assert_eq!(resets.load(Ordering::Relaxed), 0);
+        let mut input = frames(0.5, 2);
+        input.extend(frames(0.0, 2));
+        assert_eq!(seg.push(&input).len(), 1);
+        assert_eq!(resets.load(Ordering::Relaxed), 1);
+        seg.push(&frames(0.5, 1));
+        seg.flush();
+        assert_eq!(resets.load(Ordering::Relaxed), 2);
+    }
+}
@@ -5,33 +5,62 @@
 //!
 //! The model is normally resolved from the HuggingFace cache via the model
 //! registry. You can override the path with `$SILERO_VAD_MODEL`.
+//!
+//! The model runs on fixed windows: 512 samples at 16 kHz or 256 at 8 kHz,
+//! each preceded by the last 64 (or 32) samples of the window before it.
+//! 8 kHz audio is used as is; any other rate is resampled to 16 kHz as one
+//! continuous stream. Chunks of any size are buffered into whole windows.
+
+use std::path::{Path, PathBuf};
 
-use std::path::PathBuf;
+use anyhow::Context;
 
 use super::VoiceDetector;
+use crate::audio::resample::Resampler;
 use ort::{session::Session, value::Tensor};
 
-/// Chunk size expected by Silero VAD at 16 kHz.
+/// Window size expected by Silero VAD at 16 kHz.
 const CHUNK_16K: usize = 512;
-/// LSTM hidden/cell state dimensions: [2, batch=1, 64].
-const STATE_SHAPE: [usize; 3] = [2, 1, 64];
+/// Window size expected by Silero VAD at 8 kHz.
+const CHUNK_8K: usize = 256;
+/// Context carried over from the previous window at 16 kHz.
+const CONTEXT_16K: usize = 64;
+/// Context carried over from the previous window at 8 kHz.
+const CONTEXT_8K: usize = 32;
+/// The model file within the `silero/vad-v5` repo.
+const MODEL_FILE: &str = "onnx/model.onnx";
+/// Recurrent state dimensions: [2, batch=1, 128].
+const STATE_SHAPE: [usize; 3] = [2, 1, 128];
 const STATE_LEN: usize = STATE_SHAPE[0] * STATE_SHAPE[1] * STATE_SHAPE[2];
 
 pub struct SileroVad {
     session: Session,
     threshold: f32,
-    h: Vec<f32>,
-    c: Vec<f32>,
+    state: Vec<f32>,
+    /// Tail of the previous window, prepended to the next one.
+    context: Vec<f32>,
+    /// Model rate of the buffered audio.
+    rate: u32,
+    /// Capture rate `resampler` converts from.
+    input_rate: u32,
+    /// Converts captured audio to the model rate across chunks.
+    resampler: Resampler,
+    /// Audio at the model rate not yet forming a full window.
+    pending: Vec<f32>,
+    /// Speech probability of the last window, reported while a chunk is too
+    /// short to complete another.
+    last_prob: f32,
 }
 
 impl SileroVad {
     /// Create a new Silero VAD instance.
     ///
-    /// If `model_path` is provided it is used directly. Otherwise falls back
-    /// to `$SILERO_VAD_MODEL` or `silero_vad.onnx` next to the executable.
-    pub fn new(threshold: f32, model_path: Option<PathBuf>) -> anyhow::Result<Self> {
-        let resolved = match model_path {
-            Some(p) => p,
+    /// `model_dir` is the `silero/vad-v5` directory from the model registry.
+    /// Without it, falls back to `$SILERO_VAD_MODEL` or `silero_vad.onnx`
+    /// next to the executable.
+    pub fn new(threshold: f32, model_dir: Option<&Path>) -> anyhow::Result<Self> {
+        let resolved = match model_dir {
+            Some(dir) => dir.join(MODEL_FILE),
             None => PathBuf::from(std::env::var("SILERO_VAD_MODEL").unwrap_or_else(|_| {
                 std::env::current_exe()
                     .ok()
@@ -42,100 +71,155 @@ impl SileroVad {
             })),
         };
 
-        let session = Session::builder()?.commit_from_file(&resolved)?;
+        let session = Session::builder()?
+            .commit_from_file(&resolved)
+            .with_context(|| format!("Failed to load Silero VAD model {}", resolved.display()))?;
+        log::info!("Silero VAD model loaded from {}", resolved.display());
 
         Ok(Self {
             session,
             threshold,
-            h: vec![0.0; STATE_LEN],
-            c: vec![0.0; STATE_LEN],
+            state: vec![0.0; STATE_LEN],
+            context: vec![0.0; CONTEXT_16K],
+            rate: 16_000,
+            input_rate: 16_000,
+            resampler: Resampler::new(16_000, 16_000),
+            pending: Vec::new(),
+            last_prob: 0.0,
         })
     }
+
+    /// Run one window through the model, returning the speech probability
+    /// and carrying the recurrent state and context forward.
+    fn infer(&mut self, window: &[f32]) -> anyhow::Result<f32> {
+        let mut frame = Vec::with_capacity(self.context.len() + window.len());
+        frame.extend_from_slice(&self.context);
+        frame.extend_from_slice(window);
+        let tail = frame.len() - self.context.len();
+        self.context.copy_from_slice(&frame[tail..]);
+
+        let input = Tensor::from_array(([1usize, frame.len()], frame.into_boxed_slice()))?;
+        let state = Tensor::from_array((STATE_SHAPE, self.state.clone().into_boxed_slice()))?;
+        let sr = Tensor::from_array(((), vec![self.rate as i64].into_boxed_slice()))?;
+
+        let outputs = self.session.run(ort::inputs!["input" => input, "state" => state, "sr" => sr])?;
+
+        let prob = outputs["output"].try_extract_tensor::<f32>()?.1.first().copied().unwrap_or(0.0);
+        let (_, state) = outputs["stateN"].try_extract_tensor::<f32>()?;
+        anyhow::ensure!(state.len() == STATE_LEN, "unexpected state size {}", state.len());
+        self.state.copy_from_slice(state);
+        Ok(prob)
+    }
+}
+
+/// Model rate, window size and context size for audio captured at `sample_rate`.
+fn model_window(sample_rate: u32) -> (u32, usize, usize) {
+    if sample_rate == 8_000 {
+        (8_000, CHUNK_8K, CONTEXT_8K)
+    } else {
+        (16_000, CHUNK_16K, CONTEXT_16K)
+    }
 }
 
 impl VoiceDetector for SileroVad {
-    fn is_speech(&mut self, samples: &[f32], _sample_rate: u32) -> bool {
-        // Silero VAD expects exactly CHUNK_16K samples at 16 kHz.
-        // Pad or truncate to fit.
-        let mut chunk = vec![0.0f32; CHUNK_16K];
-        let copy_len = samples.len().min(CHUNK_16K);
-        chunk[..copy_len].copy_from_slice(&samples[..copy_len]);
-
-        let input = match Tensor::from_array(([1usize, CHUNK_16K], chunk.into_boxed_slice())) {
-            Ok(t) => t,
-            Err(e) => {
-                log::error!("silero: failed to create input tensor: {e}");
-                return false;
-            }
-        };
-        let sr = match Tensor::from_array(([1usize], vec![16000_i64].into_boxed_slice())) {
-            Ok(t) => t,
-            Err(e) => {
-                log::error!("silero: failed to create sr tensor: {e}");
-                return false;
+    fn is_speech(&mut self, samples: &[f32], sample_rate: u32) -> bool {
+        let (rate, window, context) = model_window(sample_rate);
+        if sample_rate != self.input_rate {
+            self.input_rate = sample_rate;
+            self.resampler = Resampler::new(sample_rate, rate);
+            if rate != self.rate {
+                self.rate = rate;
+                self.context = vec![0.0; context];
+                self.reset();
             }
-        };
-        let h = match Tensor::from_array((STATE_SHAPE.to_vec(), self.h.clone().into_boxed_slice()))
-        {
-            Ok(t) => t,
-            Err(e) => {
-                log::error!("silero: failed to create h tensor: {e}");
-                return false;
-            }
-        };
-        let c = match Tensor::from_array((STATE_SHAPE.to_vec(), self.c.clone().into_boxed_slice()))
-        {
-            Ok(t) => t,
-            Err(e) => {
-                log::error!("silero: failed to create c tensor: {e}");
-                return false;
+        }
+        self.resampler.process(samples, &mut self.pending);
+
+        // Speech if any window completed by this chunk is
+        let mut speech = None;
+        while self.pending.len() >= window {
+            let chunk: Vec<f32> = self.pending.drain(..window).collect();
+            match self.infer(&chunk) {
+                Ok(prob) => self.last_prob = prob,
+                Err(e) => {
+                    log::error!("silero: inference failed: {e:#}");
+                    self.last_prob = 0.0;
+                }
             }
-        };
+            *speech.get_or_insert(false) |= self.last_prob > self.threshold;
+        }
+        speech.unwrap_or(self.last_prob > self.threshold)
+    }
 
-        let session_inputs = match ort::inputs![input, sr, h, c] {
-            Ok(i) => i,
-            Err(e) => {
-                log::error!("silero: failed to build session inputs: {e}");
-                return false;
-            }
-        };
+    fn reset(&mut self) {
+        self.state.fill(0.0);
+        self.context.fill(0.0);
+        self.resampler.reset();
+        self.pending.clear();
+        self.last_prob = 0.0;
+    }
 
-        let outputs = match self.session.run(session_inputs) {
-            Ok(o) => o,
-            Err(e) => {
-                log::error!("silero: inference failed: {e}");
-                return false;
-            }
-        };
+    fn name(&self) -> &str {
+        "silero"
+    }
+}
 
-        if outputs.len() < 3 {
-            log::error!("silero: expected 3 outputs, got {}", outputs.len());
-            return false;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_follows_sample_rate() {
+        assert_eq!(model_window(16_000), (16_000, CHUNK_16K, CONTEXT_16K));
+        assert_eq!(model_window(8_000), (8_000, CHUNK_8K, CONTEXT_8K));
+        // Device rates are resampled to 16 kHz
+        assert_eq!(model_window(48_000), (16_000, CHUNK_16K, CONTEXT_16K));
+        assert_eq!(model_window(44_100), (16_000, CHUNK_16K, CONTEXT_16K));
+    }
 
-        // Extract speech probability (output 0).
-        let prob = outputs[0]
-            .try_extract_raw_tensor::<f32>()
-            .map(|(_, data)| data[0])
-            .unwrap_or(0.0);
+    /// The `silero/vad-v5` model from the HuggingFace cache or `$SILERO_VAD_MODEL`.
+    fn load() -> SileroVad {
+        let dir = crate::models::cache_scanner::find_hf_model("onnx-community/silero-vad", &[MODEL_FILE]);
+        assert!(
+            dir.is_some() || std::env::var_os("SILERO_VAD_MODEL").is_some(),
+            "download onnx-community/silero-vad or set SILERO_VAD_MODEL"
+        );
+        SileroVad::new(0.5, dir.as_deref()).unwrap()
+    }
 
-        // Update LSTM hidden state (output 1).
-        if let Ok((_, data)) = outputs[1].try_extract_raw_tensor::<f32>() {
-            if data.len() == STATE_LEN {
-                self.h.copy_from_slice(data);
-            }
-        }
-        // Update LSTM cell state (output 2).
-        if let Ok((_, data)) = outputs[2].try_extract_raw_tensor::<f32>() {
-            if data.len() == STATE_LEN {
-                self.c.copy_from_slice(data);
+    #[test]
+    #[ignore = "needs the silero/vad-v5 model"]
+    fn model_runs_on_fixture() {
+        let mut vad = load();
+        for rate in [16_000, 8_000] {
+            // Switches the model rate
+            vad.is_speech(&[], rate);
+            let (_, window, context) = model_window(rate);
+
+            let quiet = vad.infer(&vec![0.0; window]).unwrap();
+            assert!(quiet < 0.5, "silence at {rate} Hz scored {quiet}");
+
+            // Vowel-like tone: 140 Hz with decaying harmonics
+            let voiced: Vec<f32> = (0..window * 8)
+                .map(|i| {
+                    let t = i as f32 / rate as f32;
+                    (1..=8).map(|h| (std::f32::consts::TAU * 140.0 * h as f32 * t).sin() * 0.1 / h as f32).sum()
+                })
+                .collect();
+            for w in voiced.chunks(window) {
+                let prob = vad.infer(w).unwrap();
+                assert!((0.0..=1.0).contains(&prob), "probability {prob} at {rate} Hz");
             }
+            assert!(vad.state.iter().any(|&s| s != 0.0), "state is carried forward");
+            assert_eq!(vad.context, voiced[voiced.len() - context..]);
         }
 
-        prob > self.threshold
-    }
-
-    fn name(&self) -> &str {
-        "silero"
+        // A real recording, when one is provided
+        if let Some(path) = std::env::var_os("SILERO_VAD_FIXTURE") {
+            let (samples, rate) = crate::stt::load_wav_pcm(Path::new(&path)).unwrap();
+            vad.reset();
+            let speech = samples.chunks(rate as usize / 10).filter(|c| vad.is_speech(c, rate)).count();
+            assert!(speech > 0, "no speech detected in {path:?}");
+        }
     }
 }
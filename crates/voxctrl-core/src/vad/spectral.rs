@@ -0,0 +1,470 @@
+//! Spectral voice activity detection — a small pure-Rust detector in the
+//! spirit of WebRTC VAD, with no model to download.
+//!
+//! Audio is analysed in 20 ms frames. Each frame's energy in six sub-bands
+//! (80 Hz – 4 kHz) is scored against two Gaussians per band: a noise model
+//! that follows the background level, and a speech model a fixed SNR above
+//! it. Frames that score as speech must also look like speech:
+//!
+//! - a peaky spectrum (low spectral flatness), which rejects clicks such as
+//!   keystrokes, whose energy is spread evenly across the band;
+//! - syllabic modulation — within the last 600 ms the level must have
+//!   fallen between syllables, which sustained music doesn't do. A rise
+//!   alone doesn't count, so music starting isn't speech. In noise the
+//!   gaps between syllables only fall as far as the noise floor, so the
+//!   required fall shrinks with the signal-to-noise ratio.
+//!
+//! A short hangover bridges the gaps between syllables and the unvoiced
+//! sounds around them.
+
+use std::collections::VecDeque;
+
+use super::VoiceDetector;
+use crate::audio::fft::Fft;
+
+const FRAME_MS: u32 = 20;
+/// Sub-band edges in Hz, as in WebRTC VAD.
+const BANDS: [(f32, f32); 6] = [
+    (80.0, 250.0),
+    (250.0, 500.0),
+    (500.0, 1000.0),
+    (1000.0, 2000.0),
+    (2000.0, 3000.0),
+    (3000.0, 4000.0),
+];
+/// Weight of each band's likelihood ratio; formants live in the middle ones.
+const BAND_WEIGHTS: [f32; 6] = [0.5, 1.0, 1.5, 1.5, 1.0, 0.5];
+/// Spread of the noise and speech models, in dB.
+const NOISE_STD_DB: f32 = 4.0;
+const SPEECH_STD_DB: f32 = 10.0;
+/// Mean of the speech model above the noise mean.
+const SPEECH_SNR_DB: f32 = 15.0;
+/// Per-band log-likelihood ratios are clamped, so one loud band can't carry
+/// a frame on its own.
+const MAX_BAND_LLR: f32 = 5.0;
+/// Noise model update per frame: fast towards a quieter band, slow towards
+/// a louder one (and only on non-speech frames).
+const NOISE_FALL: f32 = 0.3;
+const NOISE_RISE: f32 = 0.02;
+/// Frames this flat or flatter are noise-like, not voiced speech (white
+/// noise measures about 0.56).
+const MAX_FLATNESS: f32 = 0.3;
+/// Window for syllabic modulation.
+const MODULATION_MS: u32 = 600;
+/// Level drop required for syllabic modulation: this share of the window's
+/// peak above the noise floor, clamped to the range below.
+const MODULATION_SHARE: f32 = 0.8;
+const MIN_MODULATION_DB: f32 = 3.0;
+const MAX_MODULATION_DB: f32 = 12.0;
+/// Speech continues this long after the last speech frame.
+const HANGOVER_MS: u32 = 200;
+/// Power floor before taking logs, so digital silence stays finite.
+const MIN_POWER: f32 = 1e-10;
+
+pub struct SpectralVad {
+    /// LLR score a frame needs to count as speech.
+    threshold: f32,
+    analysis: Option<Analysis>,
+    /// Samples not yet forming a full frame.
+    pending: Vec<f32>,
+    /// Per-band noise mean in dB; `None` until the first frame.
+    noise: Option<[f32; 6]>,
+    /// Speech-band level of recent frames, for the modulation check.
+    levels: VecDeque<f32>,
+    hangover: u32,
+}
+
+/// Frame size, FFT plan and band bins for one sample rate.
+struct Analysis {
+    sample_rate: u32,
+    frame: usize,
+    fft: Fft,
+    window: Vec<f32>,
+    bands: [std::ops::Range<usize>; 6],
+    /// Bins from 250 Hz to 4 kHz, for the flatness measure.
+    voice_bins: std::ops::Range<usize>,
+}
+
+impl Analysis {
+    fn new(sample_rate: u32) -> Self {
+        let frame = (sample_rate * FRAME_MS / 1000).max(1) as usize;
+        let n = frame.next_power_of_two().max(2);
+        let window = (0..frame)
+            .map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / frame as f32).cos())
+            .collect();
+        let bin = |hz: f32| ((hz * n as f32 / sample_rate as f32).round() as usize).min(n / 2);
+        let bands = BANDS.map(|(lo, hi)| bin(lo)..bin(hi).max(bin(lo) + 1));
+        Self {
+            sample_rate,
+            frame,
+            fft: Fft::new(n),
+            window,
+            bands,
+            voice_bins: bin(250.0)..bin(4000.0),
+        }
+    }
+
+    /// Band levels in dB and the spectral flatness of one frame.
+    fn features(&self, frame: &[f32]) -> ([f32; 6], f32) {
+        let n = self.fft.len();
+        let mut re = vec![0.0; n];
+        let mut im = vec![0.0; n];
+        for ((r, s), w) in re.iter_mut().zip(frame).zip(&self.window) {
+            *r = s * w;
+        }
+        self.fft.forward(&mut re, &mut im);
+        let power: Vec<f32> = (0..=n / 2).map(|k| re[k] * re[k] + im[k] * im[k] + MIN_POWER).collect();
+
+        let levels = self.bands.clone().map(|band| {
+            let sum: f32 = power[band.start..band.end.min(power.len())].iter().sum();
+            10.0 * (sum + MIN_POWER).log10()
+        });
+        let voice = &power[self.voice_bins.start..self.voice_bins.end.min(power.len())];
+        let flatness = if voice.is_empty() {
+            1.0
+        } else {
+            let log_mean = voice.iter().map(|p| p.ln()).sum::<f32>() / voice.len() as f32;
+            let mean = voice.iter().sum::<f32>() / voice.len() as f32;
+            log_mean.exp() / mean
+        };
+        (levels, flatness)
+    }
+}
+
+impl SpectralVad {
+    /// `threshold`: weighted log-likelihood ratio for speech; 0 is neutral,
+    /// higher rejects more.
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            analysis: None,
+            pending: Vec::new(),
+            noise: None,
+            levels: VecDeque::new(),
+            hangover: 0,
+        }
+    }
+
+    /// Classify one frame, updating the noise model and hangover.
+    fn process_frame(&mut self, frame: &[f32]) -> bool {
+        let analysis = self.analysis.as_ref().expect("analysis set before frames");
+        let (levels, flatness) = analysis.features(frame);
+        let noise = self.noise.get_or_insert(levels);
+
+        let mut score = 0.0;
+        for ((&x, &mean), &weight) in levels.iter().zip(noise.iter()).zip(&BAND_WEIGHTS) {
+            score += weight * log_likelihood_ratio(x, mean).clamp(-MAX_BAND_LLR, MAX_BAND_LLR);
+        }
+        score /= BAND_WEIGHTS.iter().sum::<f32>();
+
+        // Speech band (250 Hz – 3 kHz) level for the modulation window
+        let speech_level = speech_band_level(&levels);
+        let noise_level = speech_band_level(noise);
+        let window = (MODULATION_MS / FRAME_MS) as usize;
+        if self.levels.len() == window {
+            self.levels.pop_front();
+        }
+        self.levels.push_back(speech_level);
+        // Largest fall from an earlier frame to a later one
+        let mut peak = f32::MIN;
+        let mut drop = 0.0f32;
+        for &level in &self.levels {
+            peak = peak.max(level);
+            drop = drop.max(peak - level);
+        }
+
+        let required = (MODULATION_SHARE * (peak - noise_level)).clamp(MIN_MODULATION_DB, MAX_MODULATION_DB);
+
+        let speech = score > self.threshold && flatness < MAX_FLATNESS && drop >= required;
+
+        for (mean, &x) in noise.iter_mut().zip(&levels) {
+            if x < *mean {
+                *mean += NOISE_FALL * (x - *mean);
+            } else if score <= self.threshold {
+                *mean += NOISE_RISE * (x - *mean);
+            }
+        }
+
+        if speech {
+            self.hangover = HANGOVER_MS / FRAME_MS;
+            true
+        } else if self.hangover > 0 {
+            self.hangover -= 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Combined level of the 250 Hz – 3 kHz bands, in dB.
+fn speech_band_level(levels: &[f32; 6]) -> f32 {
+    10.0 * levels[1..5].iter().map(|db| 10f32.powf(db / 10.0)).sum::<f32>().log10()
+}
+
+/// Log-likelihood of speech over noise for a band at `level` dB, given the
+/// band's noise mean.
+fn log_likelihood_ratio(level: f32, noise_mean: f32) -> f32 {
+    let speech_mean = noise_mean + SPEECH_SNR_DB;
+    let speech = -(level - speech_mean).powi(2) / (2.0 * SPEECH_STD_DB * SPEECH_STD_DB);
+    let noise = -(level - noise_mean).powi(2) / (2.0 * NOISE_STD_DB * NOISE_STD_DB);
+    (NOISE_STD_DB / SPEECH_STD_DB).ln() + speech - noise
+}
+
+impl VoiceDetector for SpectralVad {
+    fn is_speech(&mut self, samples: &[f32], sample_rate: u32) -> bool {
+        if self.analysis.as_ref().is_none_or(|a| a.sample_rate != sample_rate) {
+            self.analysis = Some(Analysis::new(sample_rate));
+            self.pending.clear();
+        }
+        let frame = self.analysis.as_ref().map_or(1, |a| a.frame);
+
+        // Speech if any frame completed by this chunk is
+        let mut speech = None;
+        let mut rest = samples;
+        while !rest.is_empty() {
+            let take = (frame - self.pending.len()).min(rest.len());
+            self.pending.extend_from_slice(&rest[..take]);
+            rest = &rest[take..];
+            if self.pending.len() == frame {
+                let pending = std::mem::take(&mut self.pending);
+                *speech.get_or_insert(false) |= self.process_frame(&pending);
+                self.pending = pending;
+                self.pending.clear();
+            }
+        }
+        speech.unwrap_or(self.hangover > 0)
+    }
+
+    fn name(&self) -> &str {
+        "spectral"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::test_util::{noise, rms};
+    #[cfg(feature = "vad-energy")]
+    use crate::vad::energy::EnergyVad;
+    use std::f32::consts::TAU;
+
+    const RATE: u32 = 16_000;
+    /// Chunk fed to the detectors, as the hands-free segmenter would.
+    const CHUNK: usize = 320;
+
+    // ── Fixture audio ───────────────────────────────────────────────────
+
+    /// Two-pole resonator, the building block of a formant synthesizer.
+    fn resonate(input: &[f32], freq: f32, bandwidth: f32) -> Vec<f32> {
+        let r = (-std::f32::consts::PI * bandwidth / RATE as f32).exp();
+        let (a1, a2) = (2.0 * r * (TAU * freq / RATE as f32).cos(), -r * r);
+        let (mut y1, mut y2) = (0.0, 0.0);
+        input
+            .iter()
+            .map(|&x| {
+                let y = (1.0 - r) * x + a1 * y1 + a2 * y2;
+                y2 = y1;
+                y1 = y;
+                y
+            })
+            .collect()
+    }
+
+    fn normalize(signal: &mut [f32], level: f32) {
+        let current = rms(signal);
+        signal.iter_mut().for_each(|s| *s *= level / current.max(1e-9));
+    }
+
+    /// Vowel-like syllables: a glottal pulse train with a wandering pitch
+    /// through two formants, 200 ms on and 60 ms off.
+    fn speech(secs: f32) -> Vec<f32> {
+        let len = (secs * RATE as f32) as usize;
+        let mut phase = 0.0;
+        let pulses: Vec<f32> = (0..len)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                phase += (120.0 + 25.0 * (t * 1.7 * TAU).sin()) / RATE as f32;
+                if phase >= 1.0 {
+                    phase -= 1.0;
+                    1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let f1 = resonate(&pulses, 650.0, 90.0);
+        let f2 = resonate(&pulses, 1500.0, 120.0);
+        let syllable = RATE as usize * 260 / 1000;
+        let on = RATE as usize * 200 / 1000;
+        let mut out: Vec<f32> = f1
+            .iter()
+            .zip(&f2)
+            .enumerate()
+            .map(|(i, (a, b))| {
+                let pos = i % syllable;
+                let envelope = if pos < on { (std::f32::consts::PI * pos as f32 / on as f32).sin() } else { 0.0 };
+                envelope * (a + 0.6 * b)
+            })
+            .collect();
+        normalize(&mut out, 0.1);
+        out
+    }
+
+    /// Sustained chords with harmonics, changing smoothly every second.
+    fn music(secs: f32) -> Vec<f32> {
+        let len = (secs * RATE as f32) as usize;
+        let chords = [[220.0, 277.2, 329.6], [196.0, 246.9, 293.7], [174.6, 220.0, 261.6]];
+        let mut out: Vec<f32> = (0..len)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                let at = t % 1.0;
+                let (a, b) = (chords[t as usize % 3], chords[(t as usize + 1) % 3]);
+                // Crossfade into the next chord over the last 100 ms
+                let mix = ((at - 0.9) / 0.1).clamp(0.0, 1.0);
+                let voice = |notes: [f32; 3]| -> f32 {
+                    notes
+                        .iter()
+                        .flat_map(|f| (1..=4).map(move |h| (t * f * h as f32 * TAU).sin() / h as f32))
+                        .sum()
+                };
+                (1.0 - mix) * voice(a) + mix * voice(b)
+            })
+            .collect();
+        normalize(&mut out, 0.1);
+        out
+    }
+
+    /// Keystrokes: decaying 8 ms noise bursts every 150 ms.
+    fn keyboard(secs: f32) -> Vec<f32> {
+        let len = (secs * RATE as f32) as usize;
+        let burst = noise(len, 0.5, 5);
+        let period = RATE as usize * 150 / 1000;
+        let click = RATE as usize * 8 / 1000;
+        (0..len)
+            .map(|i| {
+                let pos = i % period;
+                if pos < click { burst[i] * (-(pos as f32) / (click as f32 / 4.0)).exp() } else { 0.0 }
+            })
+            .collect()
+    }
+
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    enum Label {
+        Silence,
+        Speech,
+        Music,
+        Keyboard,
+    }
+
+    /// Quiet room with speech, music and typing in turn, labelled per chunk.
+    fn fixture() -> (Vec<f32>, Vec<Label>) {
+        let segments: Vec<(Label, Vec<f32>)> = vec![
+            (Label::Silence, vec![0.0; RATE as usize]),
+            (Label::Speech, speech(3.0)),
+            (Label::Silence, vec![0.0; RATE as usize]),
+            (Label::Music, music(4.0)),
+            (Label::Silence, vec![0.0; RATE as usize]),
+            (Label::Keyboard, keyboard(3.0)),
+            (Label::Silence, vec![0.0; RATE as usize]),
+            (Label::Speech, speech(2.0)),
+        ];
+        let mut audio = Vec::new();
+        let mut labels = Vec::new();
+        for (label, samples) in segments {
+            labels.extend(std::iter::repeat_n(label, samples.len() / CHUNK));
+            audio.extend(samples);
+        }
+        let room = noise(audio.len(), 0.002, 9);
+        audio.iter_mut().zip(room).for_each(|(s, n)| *s += n);
+        (audio, labels)
+    }
+
+    /// Speech, then music, each between stretches of speech-shaped noise
+    /// `snr_db` below the speech level.
+    fn noisy_fixture(snr_db: f32) -> (Vec<f32>, Vec<Label>) {
+        let gap = || vec![0.0; RATE as usize * 2];
+        let segments: Vec<(Label, Vec<f32>)> = vec![
+            (Label::Silence, gap()),
+            (Label::Speech, speech(3.0)),
+            (Label::Silence, gap()),
+            (Label::Music, music(4.0)),
+            (Label::Silence, gap()),
+        ];
+        let mut audio = Vec::new();
+        let mut labels = Vec::new();
+        for (label, samples) in segments {
+            labels.extend(std::iter::repeat_n(label, samples.len() / CHUNK));
+            audio.extend(samples);
+        }
+        let mut room = resonate(&noise(audio.len(), 1.0, 77), 1000.0, 1500.0);
+        normalize(&mut room, 0.1 / 10f32.powf(snr_db / 20.0));
+        audio.iter_mut().zip(room).for_each(|(s, n)| *s += n);
+        (audio, labels)
+    }
+
+    /// Share of chunks with `label` that `vad` calls speech.
+    fn detection_rates(vad: &mut dyn VoiceDetector, (audio, labels): (Vec<f32>, Vec<Label>)) -> impl Fn(Label) -> f32 {
+        let decisions: Vec<(Label, bool)> =
+            audio.chunks_exact(CHUNK).zip(&labels).map(|(c, &l)| (l, vad.is_speech(c, RATE))).collect();
+        move |label| {
+            let of_label: Vec<bool> = decisions.iter().filter(|(l, _)| *l == label).map(|(_, d)| *d).collect();
+            of_label.iter().filter(|&&d| d).count() as f32 / of_label.len() as f32
+        }
+    }
+
+    #[test]
+    fn detects_speech_but_not_music_or_typing() {
+        let spectral = detection_rates(&mut SpectralVad::new(0.0), fixture());
+        assert!(spectral(Label::Speech) > 0.85, "speech recall {}", spectral(Label::Speech));
+        assert!(spectral(Label::Music) < 0.1, "music false alarms {}", spectral(Label::Music));
+        assert!(spectral(Label::Keyboard) < 0.1, "keyboard false alarms {}", spectral(Label::Keyboard));
+        assert!(spectral(Label::Silence) < 0.1, "silence false alarms {}", spectral(Label::Silence));
+    }
+
+    #[test]
+    fn detects_speech_at_low_snr() {
+        for snr_db in [12.0, 6.0, 3.0] {
+            let spectral = detection_rates(&mut SpectralVad::new(0.0), noisy_fixture(snr_db));
+            assert!(spectral(Label::Speech) > 0.7, "{snr_db} dB: speech recall {}", spectral(Label::Speech));
+            assert!(spectral(Label::Music) < 0.1, "{snr_db} dB: music false alarms {}", spectral(Label::Music));
+            assert!(spectral(Label::Silence) < 0.1, "{snr_db} dB: noise false alarms {}", spectral(Label::Silence));
+        }
+    }
+
+    #[cfg(feature = "vad-energy")]
+    #[test]
+    fn benchmark_against_energy_vad() {
+        let spectral = detection_rates(&mut SpectralVad::new(0.0), fixture());
+        let energy = detection_rates(&mut EnergyVad::new(0.015), fixture());
+        // Comparable recall, but the fixed threshold can't tell music or
+        // typing apart from speech
+        assert!(spectral(Label::Speech) > energy(Label::Speech) - 0.1);
+        assert!(energy(Label::Music) > 0.9);
+        assert!(spectral(Label::Music) < energy(Label::Music) - 0.8);
+        assert!(spectral(Label::Keyboard) < energy(Label::Keyboard));
+    }
+
+    #[test]
+    fn handles_device_rate_and_odd_chunks() {
+        let mut vad = SpectralVad::new(0.0);
+        let mut detected = false;
+        // 48 kHz audio in chunks that don't line up with frames
+        let quiet = noise(48_000, 0.002, 3);
+        for chunk in quiet.chunks(1234) {
+            assert!(!vad.is_speech(chunk, 48_000));
+        }
+        let voice: Vec<f32> = speech(1.0).iter().flat_map(|&s| [s; 3]).collect();
+        for chunk in voice.chunks(1234) {
+            detected |= vad.is_speech(chunk, 48_000);
+        }
+        assert!(detected);
+    }
+
+    #[test]
+    fn silence_is_not_speech() {
+        let mut vad = SpectralVad::new(0.0);
+        assert!(!vad.is_speech(&[0.0; 1600], RATE));
+        assert!(!vad.is_speech(&[], RATE));
+    }
+}
@@ -2,6 +2,7 @@
 
 use voxctrl_core::action::ActionExecutor;
 use voxctrl_core::config::ActionConfig;
+use voxctrl_core::router::goal::GOAL_COMMAND;
 use voxctrl_core::router::Intent;
 
 use crate::agent::{self, AgentConfig};
@@ -61,6 +62,13 @@ impl ActionExecutor for ComputerUseExecutor {
                 // For dictation, the goal is to type the text at the cursor
                 format!("Type the following text at the current cursor position: {text}")
             }
+            Intent::Command { action, args } if action == GOAL_COMMAND => {
+                // From the computer-use hotkey: the utterance is the goal itself
+                args["goal"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("goal command needs a \"goal\" argument"))?
+                    .to_string()
+            }
             Intent::Command { action, args } => {
                 // For commands, the goal is the action description
                 if args.is_null() || args.as_object().map_or(true, |m| m.is_empty()) {
@@ -92,4 +100,9 @@ impl ActionExecutor for ComputerUseExecutor {
     fn name(&self) -> &str {
         "computer-use"
     }
+
+    fn streams(&self) -> bool {
+        // Each execution is a separate goal, so it needs the whole utterance
+        false
+    }
 }
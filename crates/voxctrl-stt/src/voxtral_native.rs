@@ -10,7 +10,7 @@ use std::sync::Mutex;
 
 use burn::prelude::{Device, Tensor};
 use burn::tensor::TensorData;
-use voxtral_mini_realtime::audio::{load_wav, resample_to_16k, MelConfig, MelSpectrogram};
+use voxtral_mini_realtime::audio::{AudioBuffer, MelConfig, MelSpectrogram};
 use voxtral_mini_realtime::audio::{pad_audio, PadConfig};
 use voxtral_mini_realtime::hub::ModelPaths;
 use voxtral_mini_realtime::models::loader::VoxtralModelLoader;
@@ -18,13 +18,23 @@ use voxtral_mini_realtime::models::time_embedding::TimeEmbedding;
 use voxtral_mini_realtime::models::voxtral::VoxtralModel;
 use voxtral_mini_realtime::tokenizer::VoxtralTokenizer;
 
-use voxctrl_core::stt::Transcriber;
+use voxctrl_core::audio::resample::{self, STT_SAMPLE_RATE};
+use voxctrl_core::stt::chunked::{ChunkParams, ChunkedSession};
+use voxctrl_core::stt::{Transcriber, TranscriptionSession};
 
 type Backend = burn::backend::NdArray;
 
 /// Decoder model dimension (d_model for Voxtral Mini 4B).
 const DECODER_DIM: usize = 3072;
 
+/// Streaming session timing. `transcribe_streaming` runs over a whole mel
+/// buffer, so the session feeds it pause-aligned chunks of 2.5–4 s, each
+/// decoded once.
+const SESSION_PARAMS: ChunkParams = ChunkParams {
+    chunk_ms: 4000,
+    cut_search_ms: 1500,
+};
+
 struct Inner {
     model: Mutex<VoxtralModel<Backend>>,
     mel: MelSpectrogram,
@@ -93,17 +103,14 @@ impl VoxtralNativeTranscriber {
             delay,
         })
     }
-}
 
-impl Transcriber for VoxtralNativeTranscriber {
-    fn transcribe(&self, wav_path: &Path) -> anyhow::Result<String> {
+    /// Run the model on 16 kHz mono PCM.
+    fn run_inference(&self, samples: Vec<f32>) -> anyhow::Result<String> {
         let inner = self.inner.as_ref().ok_or_else(|| {
             anyhow::anyhow!("Model not downloaded — download from Settings")
         })?;
 
-        // Load and resample audio to 16kHz mono.
-        let audio = load_wav(wav_path)?;
-        let audio = resample_to_16k(&audio)?;
+        let audio = AudioBuffer::new(samples, STT_SAMPLE_RATE);
 
         // Pad audio for streaming decode.
         let padded = pad_audio(&audio, &PadConfig::voxtral());
@@ -151,6 +158,27 @@ impl Transcriber for VoxtralNativeTranscriber {
         log::debug!("VoxtralNative transcription: {text:?}");
         Ok(text)
     }
+}
+
+impl Transcriber for VoxtralNativeTranscriber {
+    fn transcribe(&self, wav_path: &Path) -> anyhow::Result<String> {
+        if self.inner.is_none() {
+            anyhow::bail!("Model not downloaded — download from Settings");
+        }
+        let (samples, sample_rate) = voxctrl_core::stt::load_wav_pcm(wav_path)?;
+        self.transcribe_pcm(&samples, sample_rate)
+    }
+
+    fn transcribe_pcm(&self, samples: &[f32], sample_rate: u32) -> anyhow::Result<String> {
+        self.run_inference(resample::to_16k(samples, sample_rate).into_owned())
+    }
+
+    fn start_session(&self, sample_rate: u32) -> anyhow::Result<Box<dyn TranscriptionSession + '_>> {
+        if self.inner.is_none() {
+            anyhow::bail!("Model not downloaded — download from Settings");
+        }
+        Ok(Box::new(ChunkedSession::new(self, sample_rate, SESSION_PARAMS)))
+    }
 
     fn name(&self) -> &str {
         if self.inner.is_some() {
@@ -185,6 +213,13 @@ mod tests {
         assert!(msg.contains("download"), "expected 'download' in error: {msg}");
     }
 
+    #[test]
+    fn pending_transcriber_refuses_session() {
+        let t = VoxtralNativeTranscriber::new(None).unwrap();
+        let err = t.start_session(16000).err().expect("session should fail");
+        assert!(err.to_string().contains("download"));
+    }
+
     #[test]
     fn new_with_nonexistent_dir_is_pending() {
         let t = VoxtralNativeTranscriber::new(Some("/nonexistent/voxtral/path".into())).unwrap();
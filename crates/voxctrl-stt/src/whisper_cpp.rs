@@ -4,18 +4,20 @@ use std::path::{Path, PathBuf};
 
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
-use voxctrl_core::stt::Transcriber;
+use voxctrl_core::audio::resample;
+use voxctrl_core::stt::{Transcriber, Transcript, TranscriptSegment};
 use voxctrl_core::config::SttConfig;
 
 /// Transcribes audio using the whisper.cpp library (via whisper-rs bindings).
 pub struct WhisperCppTranscriber {
     ctx: WhisperContext,
+    /// Configured language; `"auto"` lets whisper.cpp detect it.
     language: Option<String>,
 }
 
 impl WhisperCppTranscriber {
-    /// Run whisper.cpp inference on raw f32 PCM samples.
-    fn run_inference(&self, samples: &[f32]) -> anyhow::Result<String> {
+    /// Run whisper.cpp inference on raw 16 kHz f32 PCM samples.
+    fn run_inference(&self, samples: &[f32]) -> anyhow::Result<Transcript> {
         let mut state = self
             .ctx
             .create_state()
@@ -37,16 +39,44 @@ impl WhisperCppTranscriber {
         let n = state
             .full_n_segments()
             .map_err(|e| anyhow::anyhow!("{e}"))?;
-        let mut text = String::new();
+        let eot = self.ctx.token_eot();
+        let mut segments = Vec::with_capacity(n as usize);
         for i in 0..n {
-            let seg = state
+            let text = state
                 .full_get_segment_text(i)
                 .map_err(|e| anyhow::anyhow!("{e}"))?;
-            text.push_str(&seg);
+            // Segment times are in centiseconds
+            let t0 = state.full_get_segment_t0(i).map_err(|e| anyhow::anyhow!("{e}"))?;
+            let t1 = state.full_get_segment_t1(i).map_err(|e| anyhow::anyhow!("{e}"))?;
+
+            // Mean log-probability of the text tokens (special tokens sort after EOT)
+            let n_tokens = state.full_n_tokens(i).map_err(|e| anyhow::anyhow!("{e}"))?;
+            let mut logprobs = Vec::with_capacity(n_tokens as usize);
+            for j in 0..n_tokens {
+                let token = state.full_get_token_data(i, j).map_err(|e| anyhow::anyhow!("{e}"))?;
+                if token.id < eot {
+                    logprobs.push(token.plog);
+                }
+            }
+            let avg_logprob = (!logprobs.is_empty())
+                .then(|| logprobs.iter().sum::<f32>() / logprobs.len() as f32);
+
+            segments.push(TranscriptSegment {
+                text: text.trim().to_string(),
+                start_secs: t0 as f64 / 100.0,
+                end_secs: t1 as f64 / 100.0,
+                avg_logprob,
+                no_speech_prob: Some(state.full_get_segment_no_speech_prob(i)),
+            });
         }
-        let text = text.trim().to_string();
-        log::debug!("WhisperCpp transcription: {text:?}");
-        Ok(text)
+
+        let language = self.language.clone().filter(|lang| lang != "auto").or_else(|| {
+            let id = state.full_lang_id_from_state().ok()?;
+            whisper_rs::get_lang_str(id).map(str::to_string)
+        });
+        let transcript = Transcript::from_segments(segments, language);
+        log::debug!("WhisperCpp transcription: {:?}", transcript.text);
+        Ok(transcript)
     }
 
     pub fn new(cfg: &SttConfig) -> anyhow::Result<Self> {
@@ -107,12 +137,16 @@ fn resolve_model_path(model: &str) -> anyhow::Result<PathBuf> {
 
 impl Transcriber for WhisperCppTranscriber {
     fn transcribe(&self, wav_path: &Path) -> anyhow::Result<String> {
-        let (samples, _sample_rate) = voxctrl_core::stt::load_wav_pcm(wav_path)?;
-        self.run_inference(&samples)
+        let (samples, sample_rate) = voxctrl_core::stt::load_wav_pcm(wav_path)?;
+        self.transcribe_pcm(&samples, sample_rate)
+    }
+
+    fn transcribe_pcm(&self, samples: &[f32], sample_rate: u32) -> anyhow::Result<String> {
+        Ok(self.transcribe_detailed(samples, sample_rate)?.text)
     }
 
-    fn transcribe_pcm(&self, samples: &[f32], _sample_rate: u32) -> anyhow::Result<String> {
-        self.run_inference(samples)
+    fn transcribe_detailed(&self, samples: &[f32], sample_rate: u32) -> anyhow::Result<Transcript> {
+        self.run_inference(&resample::to_16k(samples, sample_rate))
     }
 
     fn name(&self) -> &str {
@@ -13,15 +13,48 @@ use candle_transformers::models::whisper as m;
 use hf_hub::api::sync::Api;
 use tokenizers::Tokenizer;
 
-use voxctrl_core::stt::Transcriber;
+use voxctrl_core::audio::resample::{self, STT_SAMPLE_RATE};
+use voxctrl_core::stt::longform::{self, SegmentParams};
+use voxctrl_core::stt::windowed::{WindowParams, WindowedSession};
+use voxctrl_core::stt::{Transcriber, Transcript, TranscriptSegment, TranscriptionSession};
 use voxctrl_core::config::SttConfig;
+use voxctrl_core::postprocess::hallucination::compression_ratio;
 
 const MAX_DECODE_TOKENS: usize = 224;
+/// Text tokens a second of speech can plausibly produce.
+const TOKENS_PER_SEC: f64 = 15.0;
+/// Extra budget per second in timestamp mode: each segment adds an opening
+/// and a closing timestamp, and segments can be as short as half a second.
+const TIMESTAMP_TOKENS_PER_SEC: f64 = 4.0;
+
+/// Whisper's input window: 30 s at 16 kHz. Longer audio is segmented.
+const WINDOW_SAMPLES: usize = 30 * STT_SAMPLE_RATE as usize;
 
 /// Maximum consecutive duplicates of the same token before forcing EOT.
 /// A value of 2 means: allow the original + 2 duplicates (3 total), then halt.
 const MAX_CONSECUTIVE_DUPLICATES: usize = 2;
 
+/// Seconds per timestamp token step (`<|0.00|>`, `<|0.02|>`, …).
+const TIMESTAMP_STEP_SECS: f64 = 0.02;
+/// Latest timestamp allowed for the first segment's start, in steps (1 s).
+const MAX_INITIAL_TIMESTAMP_STEPS: u32 = 50;
+
+/// Whisper's language codes, in token order (`<|en|>` follows SOT).
+/// Older vocabularies stop before `yue`; English-only models have none.
+const WHISPER_LANGUAGES: &[&str] = &[
+    "en", "zh", "de", "es", "ru", "ko", "fr", "ja", "pt", "tr", "pl", "ca", "nl", "ar", "sv", "it",
+    "id", "hi", "fi", "vi", "he", "uk", "el", "ms", "cs", "ro", "da", "hu", "ta", "no", "th", "ur",
+    "hr", "bg", "lt", "la", "mi", "ml", "cy", "sk", "te", "fa", "lv", "bn", "sr", "az", "sl", "kn",
+    "et", "mk", "br", "eu", "is", "hy", "ne", "mn", "bs", "kk", "sq", "sw", "gl", "mr", "pa", "si",
+    "km", "sn", "yo", "so", "af", "oc", "ka", "be", "tg", "sd", "gu", "am", "yi", "lo", "uz", "fo",
+    "ht", "ps", "tk", "nn", "mt", "sa", "lb", "my", "bo", "tl", "mg", "as", "tt", "haw", "ln", "ha",
+    "ba", "jw", "su", "yue",
+];
+
+/// Above this no-speech probability a low-confidence decode is taken to be
+/// silence, and no temperature fallback is tried.
+const NO_SPEECH_THRESHOLD: f32 = 0.6;
+
 // Fallback token IDs for the standard Whisper tokenizer. Used when
 // `tokenizer.token_to_id()` returns `None` (e.g. a stripped or
 // incompatible tokenizer file).
@@ -45,13 +78,27 @@ pub struct WhisperNativeTranscriber {
     device: Device,
     mel_filters: Vec<f32>,
     language_token: Option<u32>,
-    /// Whether the language is English (enables non-Latin hallucination detection).
-    language_is_english: bool,
+    /// Configured language code, reported in transcripts.
+    language: Option<String>,
+    /// Languages (code, token) to choose from per window with `"auto"`;
+    /// empty when the language is fixed or unset.
+    detect_languages: Vec<(String, u32)>,
     sot_token: u32,
     eot_token: u32,
     transcribe_token: u32,
     no_timestamps_token: u32,
+    /// First timestamp token (`<|0.00|>`); all later tokens are timestamps.
+    timestamp_begin: u32,
+    /// `<|nospeech|>` (or `<|nocaptions|>` in older vocabularies), if present.
+    no_speech_token: Option<u32>,
+    /// The configured initial prompt, trimmed (empty if none).
+    initial_prompt: String,
+    /// `<|startofprev|>` followed by the initial prompt, placed before SOT.
+    prompt_tokens: Vec<u32>,
+    decode_options: DecodeOptions,
     suppress_mask: Tensor,
+    /// Like `suppress_mask`, but allowing timestamp tokens.
+    timestamp_suppress_mask: Tensor,
     begin_suppress_mask: Tensor,
 }
 
@@ -141,13 +188,35 @@ impl WhisperNativeTranscriber {
                 FALLBACK_NO_TIMESTAMPS_TOKEN
             });
 
-        let language_is_english = cfg.whisper_language.as_deref() == Some("en");
+        let timestamp_begin = no_timestamps_token + 1;
+        let no_speech_token = tokenizer
+            .token_to_id("<|nospeech|>")
+            .or_else(|| tokenizer.token_to_id("<|nocaptions|>"));
 
-        let language_token = cfg.whisper_language.as_ref().and_then(|lang| {
+        let language_token = cfg.fixed_language().and_then(|lang| {
             let tag = format!("<|{lang}|>");
             tokenizer.token_to_id(&tag)
         });
 
+        let detect_languages = if cfg.detects_language() {
+            language_candidates(&cfg.allowed_languages, |tag| tokenizer.token_to_id(tag))
+        } else {
+            Vec::new()
+        };
+        if cfg.detects_language() {
+            if detect_languages.is_empty() {
+                log::warn!("WhisperNativeTranscriber: no language tokens for detection (English-only model?)");
+            } else {
+                log::info!("WhisperNativeTranscriber: detecting language among {} languages", detect_languages.len());
+            }
+        }
+
+        let initial_prompt = cfg.initial_prompt.as_deref().map(str::trim).unwrap_or_default().to_string();
+        let prompt_tokens = encode_prompt(&tokenizer, &initial_prompt, config.max_target_positions / 2 - 1)?;
+        if !prompt_tokens.is_empty() {
+            log::info!("WhisperNativeTranscriber: initial prompt of {} tokens", prompt_tokens.len() - 1);
+        }
+
         // Build suppress list: config.suppress_tokens + SOT + all timestamp tokens
         let suppress_tokens = build_suppress_token_list(
             &config.suppress_tokens, sot_token, no_timestamps_token, config.vocab_size,
@@ -157,6 +226,12 @@ impl WhisperNativeTranscriber {
         let suppress_mask_vec = build_token_mask(&suppress_tokens, config.vocab_size);
         let suppress_mask = Tensor::from_vec(suppress_mask_vec, config.vocab_size, &device)?;
 
+        // Timestamp mode suppresses <|notimestamps|> instead of the timestamps.
+        let mut timestamp_suppress_tokens = config.suppress_tokens.clone();
+        timestamp_suppress_tokens.extend([sot_token, no_timestamps_token]);
+        let timestamp_suppress_mask_vec = build_token_mask(&timestamp_suppress_tokens, config.vocab_size);
+        let timestamp_suppress_mask = Tensor::from_vec(timestamp_suppress_mask_vec, config.vocab_size, &device)?;
+
         // Pre-compute begin_suppress mask (applied only on the first output token).
         // Whisper's begin_suppress_tokens typically includes EOT (50257) and space (220)
         // to prevent the model from immediately predicting "no speech".
@@ -178,22 +253,30 @@ impl WhisperNativeTranscriber {
             device,
             mel_filters,
             language_token,
-            language_is_english,
+            language: cfg.fixed_language().map(str::to_string),
+            detect_languages,
             sot_token,
             eot_token,
             transcribe_token,
             no_timestamps_token,
+            timestamp_begin,
+            no_speech_token,
+            initial_prompt,
+            prompt_tokens,
+            decode_options: DecodeOptions::from_config(cfg),
             suppress_mask,
+            timestamp_suppress_mask,
             begin_suppress_mask,
         })
     }
 
     /// Core inference: takes raw f32 PCM samples at any sample rate, resamples to 16 kHz,
-    /// runs mel spectrogram + encoder + greedy decode, and returns the transcribed text.
+    /// and returns the transcript. With `timestamps`, Whisper predicts segment
+    /// boundaries; otherwise the transcript is one segment per 30 s window.
     ///
-    /// A fresh `Whisper` model is constructed from the shared `VarBuilder` on each
-    /// call, guaranteeing no mutable state carries over between inferences.
-    fn run_inference(&self, samples: &[f32], sample_rate: u32) -> anyhow::Result<String> {
+    /// Audio longer than Whisper's 30 s window is split at quiet points and
+    /// decoded segment by segment, each prompted with the previous segment's text.
+    fn run_inference(&self, samples: &[f32], sample_rate: u32, timestamps: bool) -> anyhow::Result<Transcript> {
         let duration_secs = samples.len() as f64 / sample_rate as f64;
         log::info!(
             "[whisper] inference: {} samples, {:.2}s",
@@ -201,7 +284,7 @@ impl WhisperNativeTranscriber {
         );
 
         if samples.is_empty() {
-            return Ok(String::new());
+            return Ok(Transcript::default());
         }
 
         let (amin, amax, _amean) = audio_stats(samples);
@@ -210,14 +293,40 @@ impl WhisperNativeTranscriber {
         }
 
         // ── Resample to 16 kHz if needed ─────────────────────────────
-        let samples = if sample_rate != m::SAMPLE_RATE as u32 {
-            resample(samples, sample_rate, m::SAMPLE_RATE as u32)
-        } else {
-            samples.to_vec()
-        };
+        let samples: &[f32] = &resample::to_16k(samples, sample_rate);
+
+        if samples.len() <= WINDOW_SAMPLES {
+            return self.decode_window(samples, &self.prompt_tokens, timestamps);
+        }
+        let transcript = longform::transcribe_long(samples, STT_SAMPLE_RATE, SegmentParams::default(), |segment, previous| {
+            let prompt = self.segment_prompt(previous)?;
+            self.decode_window(segment, &prompt, timestamps)
+        })?;
+        log::info!("[whisper] long-form result: {:?}", transcript.text);
+        Ok(transcript)
+    }
+
+    /// Prompt for a long-form segment: the initial prompt followed by the
+    /// previous segment's text, trimmed to the most recent tokens.
+    fn segment_prompt(&self, previous: &str) -> anyhow::Result<Vec<u32>> {
+        let prompt = format!("{} {}", self.initial_prompt, previous.trim());
+        encode_prompt(&self.tokenizer, prompt.trim(), self.config.max_target_positions / 2 - 1)
+    }
+
+    /// Decode one window of at most 30 s of 16 kHz audio: mel spectrogram +
+    /// encoder + decode, with `prompt_tokens` placed before SOT.
+    ///
+    /// Decodes greedily (or with beam search when `beam_size` > 1), then
+    /// re-decodes at each further configured temperature while the result
+    /// looks degenerate (see [`fallback_reason`]).
+    ///
+    /// A fresh `Whisper` model is constructed from the shared `VarBuilder` on each
+    /// call, guaranteeing no mutable state carries over between inferences.
+    fn decode_window(&self, samples: &[f32], prompt_tokens: &[u32], timestamps: bool) -> anyhow::Result<Transcript> {
+        let duration_secs = samples.len() as f64 / STT_SAMPLE_RATE as f64;
 
         // ── Mel spectrogram (candle reference implementation) ─────────
-        let mel = m::audio::pcm_to_mel(&self.config, &samples, &self.mel_filters);
+        let mel = m::audio::pcm_to_mel(&self.config, samples, &self.mel_filters);
         let n_mel = self.config.num_mel_bins;
         let n_frames = mel.len() / n_mel;
 
@@ -229,89 +338,273 @@ impl WhisperNativeTranscriber {
         // ── Encode ──────────────────────────────────────────────────────
         let encoder_output = model.encoder.forward(&mel_tensor, true)?;
 
-        // ── Greedy decode with hallucination guards ─────────────────────
-        let mut tokens: Vec<u32> = vec![self.sot_token];
-        if let Some(lang) = self.language_token {
+        // ── Language ───────────────────────────────────────────────────
+        let (language, language_token) = if self.detect_languages.is_empty() {
+            (self.language.clone(), self.language_token)
+        } else {
+            let (lang, token) = self.detect_language(&mut model, &encoder_output)?;
+            (Some(lang), Some(token))
+        };
+
+        // ── Decoder prefix ─────────────────────────────────────────────
+        let mut tokens: Vec<u32> = prompt_tokens.to_vec();
+        let sot_index = tokens.len();
+        tokens.push(self.sot_token);
+        if let Some(lang) = language_token {
             tokens.push(lang);
         }
         tokens.push(self.transcribe_token);
-        tokens.push(self.no_timestamps_token);
+        if !timestamps {
+            tokens.push(self.no_timestamps_token);
+        }
         let prompt_len = tokens.len();
 
-        // Duration-proportional token limit: short audio can't produce many tokens.
-        let duration_token_limit = (duration_secs * 15.0).max(10.0) as usize;
-        let token_limit = MAX_DECODE_TOKENS.min(duration_token_limit);
+        let token_limit =
+            token_budget(duration_secs, timestamps).min(self.config.max_target_positions.saturating_sub(prompt_len));
 
-        let mut consecutive_repeats: usize = 0;
-        let mut last_token: Option<u32> = None;
+        let ctx = DecodeContext {
+            encoder_output,
+            prompt_len,
+            suppress_mask: if timestamps { &self.timestamp_suppress_mask } else { &self.suppress_mask },
+            timestamps,
+            token_limit,
+            english: language.as_deref() == Some("en"),
+        };
 
-        for step in 0..token_limit {
-            let flush = step == 0;
+        // The first pass also fills the cross-attention cache reused below.
+        let logits = self.forward(&mut model, &ctx, &tokens, true)?;
+        let no_speech_prob = match self.no_speech_token {
+            Some(no_speech) => {
+                // Probability of "no speech" predicted at the SOT position.
+                let sot_logits: Vec<f32> = logits.i((0, sot_index))?.to_vec1()?;
+                Some((sot_logits[no_speech as usize] - logsumexp(&sot_logits)).exp())
+            }
+            None => None,
+        };
+
+        // ── Decode, falling back to higher temperatures ────────────────
+        let opts = &self.decode_options;
+        let mut rng = Rng::seeded();
+        let mut decoded = Decoded::default();
+        for (i, &temperature) in opts.temperatures.iter().enumerate() {
+            decoded = if temperature <= 0.0 && opts.beam_size > 1 {
+                self.decode_beam(&mut model, &ctx, &tokens)?
+            } else {
+                self.decode_sampled(&mut model, &ctx, &tokens, temperature, &mut rng)?
+            };
+            let text_tokens: Vec<u32> = decoded.tokens.iter().copied().filter(|&t| t < self.timestamp_begin).collect();
+            let text = self
+                .tokenizer
+                .decode(&text_tokens, true)
+                .map_err(|e| anyhow::anyhow!("tokenizer decode: {e}"))?;
+            let Some(reason) = fallback_reason(&text, mean(&decoded.logprobs), no_speech_prob, opts) else {
+                break;
+            };
+            match opts.temperatures.get(i + 1) {
+                Some(next) => log::info!("[whisper] {reason} at temperature {temperature}; re-decoding at {next}"),
+                None => log::info!("[whisper] {reason} at temperature {temperature}; keeping this result"),
+            }
+        }
+
+        let timed = if timestamps {
+            split_timestamped(&decoded.tokens, &decoded.logprobs, self.timestamp_begin, duration_secs)
+        } else {
+            vec![TimedTokens::untimed(&decoded.tokens, &decoded.logprobs, duration_secs)]
+        };
+
+        let mut segments = Vec::with_capacity(timed.len());
+        for t in timed {
+            let text = self
+                .tokenizer
+                .decode(&t.tokens, true)
+                .map_err(|e| anyhow::anyhow!("tokenizer decode: {e}"))?;
+            let text = text.trim().to_string();
+            if text.is_empty() {
+                continue;
+            }
+            segments.push(TranscriptSegment {
+                text,
+                start_secs: t.start_secs,
+                end_secs: t.end_secs,
+                avg_logprob: t.avg_logprob,
+                no_speech_prob,
+            });
+        }
+        let transcript = Transcript::from_segments(segments, language);
+
+        log::info!("[whisper] result: {:?}", transcript.text);
+        Ok(transcript)
+    }
+
+    /// Language-ID pass: the decoder's prediction right after SOT, narrowed
+    /// to the candidate languages. Returns the language code and its token.
+    fn detect_language(&self, model: &mut m::model::Whisper, encoder_output: &Tensor) -> anyhow::Result<(String, u32)> {
+        let token_t = Tensor::new(&[self.sot_token], &self.device)?.unsqueeze(0)?;
+        let hidden = model.decoder.forward(&token_t, encoder_output, true)?;
+        let logits: Vec<f32> = model.decoder.final_linear(&hidden)?.i((0, 0))?.to_vec1()?;
+        let (best, prob) = pick_language(&logits, &self.detect_languages)
+            .ok_or_else(|| anyhow::anyhow!("no candidate languages"))?;
+        let (lang, token) = &self.detect_languages[best];
+        log::info!("[whisper] detected language {lang} (p={prob:.2})");
+        Ok((lang.clone(), *token))
+    }
+
+    /// Run the decoder over `tokens`, returning logits for every position.
+    fn forward(&self, model: &mut m::model::Whisper, ctx: &DecodeContext, tokens: &[u32], flush: bool) -> anyhow::Result<Tensor> {
+        let token_t = Tensor::new(tokens, &self.device)?.unsqueeze(0)?;
+        let hidden = model.decoder.forward(&token_t, &ctx.encoder_output, flush)?;
+        Ok(model.decoder.final_linear(&hidden)?)
+    }
 
-            let token_t = Tensor::new(tokens.as_slice(), &self.device)?.unsqueeze(0)?;
-            let hidden = model.decoder.forward(&token_t, &encoder_output, flush)?;
-            let logits = model.decoder.final_linear(&hidden)?;
+    /// Logits for the token following `tokens`, with suppression masks and
+    /// (in timestamp mode) the timestamp grammar applied.
+    fn next_token_logits(&self, model: &mut m::model::Whisper, ctx: &DecodeContext, tokens: &[u32]) -> anyhow::Result<Vec<f32>> {
+        let logits = self.forward(model, ctx, tokens, false)?;
+        let seq_len = logits.dims()[1];
+        let last_logits = logits.i((0, seq_len - 1))?;
 
-            let seq_len = logits.dims()[1];
-            let last_logits = logits.i((0, seq_len - 1))?;
+        let mut last_logits = (last_logits + ctx.suppress_mask)?;
 
-            let mut last_logits = (last_logits + &self.suppress_mask)?;
+        let output = &tokens[ctx.prompt_len..];
+        if output.is_empty() {
+            last_logits = (last_logits + &self.begin_suppress_mask)?;
+        }
 
-            if step == 0 {
-                last_logits = (last_logits + &self.begin_suppress_mask)?;
+        let mut last_logits: Vec<f32> = last_logits.to_vec1()?;
+        if ctx.timestamps {
+            apply_timestamp_rules(&mut last_logits, output, self.timestamp_begin, self.eot_token);
+        }
+        Ok(last_logits)
+    }
+
+    /// Hallucination guards: why appending `next` to `output` should end
+    /// the sequence instead, if it should.
+    fn guard(&self, ctx: &DecodeContext, output: &[u32], next: u32) -> Option<String> {
+        // Timestamps are exempt: a segment's end and the next one's start
+        // are normally the same token.
+        if next >= self.timestamp_begin {
+            return None;
+        }
+
+        // ── Repetition detector ──────────────────────────────────────
+        let repeats = trailing_repeats(output, next, self.timestamp_begin);
+        if repeats >= MAX_CONSECUTIVE_DUPLICATES {
+            return Some(format!("token {next} seen {} times consecutively", repeats + 1));
+        }
+
+        // ── Non-Latin for English ────────────────────────────────────
+        if ctx.english {
+            if let Ok(text) = self.tokenizer.decode(&[next], false) {
+                if contains_non_latin(&text) {
+                    return Some(format!("non-Latin token {text:?} (lang=en)"));
+                }
             }
+        }
+        None
+    }
 
-            let next_token = last_logits
-                .argmax(0)?
-                .to_dtype(DType::U32)?
-                .to_scalar::<u32>()?;
+    /// Greedy decode (`temperature` 0) or sampling at `temperature`.
+    fn decode_sampled(
+        &self,
+        model: &mut m::model::Whisper,
+        ctx: &DecodeContext,
+        prefix: &[u32],
+        temperature: f32,
+        rng: &mut Rng,
+    ) -> anyhow::Result<Decoded> {
+        let mut tokens = prefix.to_vec();
+        let mut logprobs = Vec::new();
+
+        for step in 0..ctx.token_limit {
+            let logits = self.next_token_logits(model, ctx, &tokens)?;
+            let next_token = if temperature > 0.0 {
+                sample(&logits, temperature, rng.next_f32())
+            } else {
+                argmax(&logits)
+            };
 
             if next_token == self.eot_token {
                 log::debug!("[whisper] EOT at step {}", step);
                 break;
             }
+            if let Some(reason) = self.guard(ctx, &tokens[ctx.prompt_len..], next_token) {
+                log::warn!("[whisper] halting: {reason} at step {step}");
+                break;
+            }
 
-            // ── Hallucination guard: repetition detector ─────────────
-            if last_token == Some(next_token) {
-                consecutive_repeats += 1;
-                if consecutive_repeats >= MAX_CONSECUTIVE_DUPLICATES {
-                    log::warn!(
-                        "[whisper] halting: token {} seen {} times consecutively at step {}",
-                        next_token, consecutive_repeats + 1, step
-                    );
-                    break;
+            logprobs.push(logits[next_token as usize] - logsumexp(&logits));
+            tokens.push(next_token);
+        }
+
+        let sum_logprob = logprobs.iter().sum();
+        Ok(Decoded { tokens: tokens.split_off(ctx.prompt_len), logprobs, sum_logprob })
+    }
+
+    /// Beam search keeping `beam_size` live sequences, as in OpenAI's
+    /// reference decoder. Sequences end at EOT or when a hallucination guard
+    /// trips; the best finished one is picked by [`sequence_score`].
+    fn decode_beam(&self, model: &mut m::model::Whisper, ctx: &DecodeContext, prefix: &[u32]) -> anyhow::Result<Decoded> {
+        let beam_size = self.decode_options.beam_size;
+        let mut live = vec![Decoded { tokens: prefix.to_vec(), ..Default::default() }];
+        let mut finished: Vec<Decoded> = Vec::new();
+
+        for _ in 0..ctx.token_limit {
+            // (cumulative log-prob, beam, token, token log-prob)
+            let mut candidates = Vec::with_capacity(live.len() * (beam_size + 1));
+            for (i, beam) in live.iter().enumerate() {
+                let logits = self.next_token_logits(model, ctx, &beam.tokens)?;
+                let norm = logsumexp(&logits);
+                for token in top_k(&logits, beam_size + 1) {
+                    let logprob = logits[token as usize] - norm;
+                    candidates.push((beam.sum_logprob + logprob, i, token, logprob));
                 }
-            } else {
-                consecutive_repeats = 0;
             }
-            last_token = Some(next_token);
-
-            // ── Hallucination guard: non-Latin for English ───────────
-            if self.language_is_english {
-                if let Ok(text) = self.tokenizer.decode(&[next_token], false) {
-                    if contains_non_latin(&text) {
-                        log::warn!(
-                            "[whisper] halting: non-Latin token {:?} at step {} (lang=en)",
-                            text, step
-                        );
-                        break;
+            candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+            let mut next_live = Vec::with_capacity(beam_size);
+            for (sum_logprob, i, token, logprob) in candidates {
+                if next_live.len() >= beam_size {
+                    break;
+                }
+                let beam = &live[i];
+                let output = &beam.tokens[ctx.prompt_len..];
+                let ends = token == self.eot_token;
+                let halted = !ends && self.guard(ctx, output, token).is_some();
+                if ends || halted {
+                    // A guard drops the offending token; EOT counts toward the score.
+                    let sum_logprob = if ends { sum_logprob } else { beam.sum_logprob };
+                    if finished.len() < beam_size && !finished.iter().any(|f| f.tokens == beam.tokens) {
+                        finished.push(Decoded { sum_logprob, ..beam.clone() });
                     }
+                } else {
+                    let mut tokens = beam.tokens.clone();
+                    tokens.push(token);
+                    let mut logprobs = beam.logprobs.clone();
+                    logprobs.push(logprob);
+                    next_live.push(Decoded { tokens, logprobs, sum_logprob });
                 }
             }
-
-            tokens.push(next_token);
+            live = next_live;
+            if finished.len() >= beam_size || live.is_empty() {
+                break;
+            }
         }
 
-        let output_tokens: Vec<u32> = tokens[prompt_len..].to_vec();
-
-        let text = self
-            .tokenizer
-            .decode(&output_tokens, true)
-            .map_err(|e| anyhow::anyhow!("tokenizer decode: {e}"))?;
-        let text = text.trim().to_string();
-
-        log::info!("[whisper] result: {:?}", text);
-        Ok(text)
+        // Sequences cut off by the token limit compete too.
+        finished.extend(live);
+        let best = finished
+            .into_iter()
+            .map(|mut d| {
+                d.tokens.drain(..ctx.prompt_len);
+                d
+            })
+            .max_by(|a, b| {
+                let score = |d: &Decoded| sequence_score(d.sum_logprob, d.tokens.len(), self.decode_options.length_penalty);
+                score(a).total_cmp(&score(b))
+            })
+            .unwrap_or_default();
+        log::debug!("[whisper] beam search picked {} tokens (sum log-prob {:.2})", best.tokens.len(), best.sum_logprob);
+        Ok(best)
     }
 
     /// Download model files via hf_hub API.
@@ -327,6 +620,84 @@ impl WhisperNativeTranscriber {
     }
 }
 
+/// Beam search and temperature fallback settings from `SttConfig`.
+#[derive(Debug, Clone)]
+struct DecodeOptions {
+    beam_size: usize,
+    length_penalty: Option<f32>,
+    temperatures: Vec<f32>,
+    fallback_compression_ratio: f32,
+    fallback_logprob: f32,
+}
+
+impl DecodeOptions {
+    fn from_config(cfg: &SttConfig) -> Self {
+        let temperatures = if cfg.temperatures.is_empty() { vec![0.0] } else { cfg.temperatures.clone() };
+        if cfg.beam_size > 1 || temperatures.len() > 1 {
+            log::info!(
+                "WhisperNativeTranscriber: beam size {}, temperatures {:?}",
+                cfg.beam_size.max(1), temperatures
+            );
+        }
+        Self {
+            beam_size: cfg.beam_size.max(1),
+            length_penalty: cfg.length_penalty,
+            temperatures,
+            fallback_compression_ratio: cfg.fallback_compression_ratio,
+            fallback_logprob: cfg.fallback_logprob,
+        }
+    }
+}
+
+/// Per-window state shared by every decode attempt.
+struct DecodeContext<'a> {
+    encoder_output: Tensor,
+    /// Length of the decoder prefix (prompt, SOT, language and task tokens).
+    prompt_len: usize,
+    suppress_mask: &'a Tensor,
+    timestamps: bool,
+    token_limit: usize,
+    /// The window is in English: non-Latin tokens are taken as hallucinations.
+    english: bool,
+}
+
+/// A decoded sequence: the output tokens (during beam search, still behind
+/// the decoder prefix) with the log-probability of each.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Decoded {
+    tokens: Vec<u32>,
+    logprobs: Vec<f32>,
+    /// Total log-probability, including the closing EOT when there was one.
+    sum_logprob: f32,
+}
+
+/// Languages (code, token) detection may choose from: every Whisper language
+/// the tokenizer has a token for, narrowed to `allowed` unless it is empty.
+fn language_candidates(allowed: &[String], token_id: impl Fn(&str) -> Option<u32>) -> Vec<(String, u32)> {
+    for lang in allowed {
+        if !WHISPER_LANGUAGES.iter().any(|l| l.eq_ignore_ascii_case(lang)) {
+            log::warn!("WhisperNativeTranscriber: unknown language {lang:?} in allowed_languages");
+        }
+    }
+    WHISPER_LANGUAGES
+        .iter()
+        .filter(|l| allowed.is_empty() || allowed.iter().any(|a| a.eq_ignore_ascii_case(l)))
+        .filter_map(|&l| Some((l.to_string(), token_id(&format!("<|{l}|>"))?)))
+        .collect()
+}
+
+/// The most likely of `candidates` given the logits after SOT: its index and
+/// its probability under a softmax over the candidates alone.
+fn pick_language(logits: &[f32], candidates: &[(String, u32)]) -> Option<(usize, f32)> {
+    let scores: Vec<f32> = candidates
+        .iter()
+        .map(|&(_, token)| logits.get(token as usize).copied().unwrap_or(f32::NEG_INFINITY))
+        .collect();
+    let norm = logsumexp(&scores);
+    let (best, &score) = scores.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1))?;
+    Some((best, (score - norm).exp()))
+}
+
 /// Map a short model name to a Hugging Face repo ID.
 fn model_to_repo(model: &str) -> String {
     if model.contains('/') {
@@ -353,11 +724,20 @@ fn load_mel_filters(num_mel_bins: usize) -> anyhow::Result<Vec<f32>> {
 impl Transcriber for WhisperNativeTranscriber {
     fn transcribe(&self, wav_path: &Path) -> anyhow::Result<String> {
         let (samples, sample_rate) = voxctrl_core::stt::load_wav_pcm(wav_path)?;
-        self.run_inference(&samples, sample_rate)
+        self.transcribe_pcm(&samples, sample_rate)
     }
 
     fn transcribe_pcm(&self, samples: &[f32], sample_rate: u32) -> anyhow::Result<String> {
-        self.run_inference(samples, sample_rate)
+        Ok(self.run_inference(samples, sample_rate, false)?.text)
+    }
+
+    fn transcribe_detailed(&self, samples: &[f32], sample_rate: u32) -> anyhow::Result<Transcript> {
+        self.run_inference(samples, sample_rate, true)
+    }
+
+    fn start_session(&self, sample_rate: u32) -> anyhow::Result<Box<dyn TranscriptionSession + '_>> {
+        // Whisper decodes ≤30 s windows from scratch — re-decode a sliding window.
+        Ok(Box::new(WindowedSession::new(self, sample_rate, WindowParams::default())))
     }
 
     fn name(&self) -> &str {
@@ -369,6 +749,28 @@ impl Transcriber for WhisperNativeTranscriber {
     }
 }
 
+/// Tokenize a prompt as `<|startofprev|>` + text, keeping at most `max_len`
+/// text tokens (the most recent ones, as Whisper does). An empty prompt
+/// yields no tokens.
+fn encode_prompt(tokenizer: &Tokenizer, prompt: &str, max_len: usize) -> anyhow::Result<Vec<u32>> {
+    if prompt.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(prev_token) = tokenizer.token_to_id("<|startofprev|>") else {
+        log::warn!("Tokenizer missing <|startofprev|>, ignoring initial prompt");
+        return Ok(Vec::new());
+    };
+    let encoding = tokenizer
+        .encode(format!(" {prompt}"), false)
+        .map_err(|e| anyhow::anyhow!("failed to tokenize initial prompt: {e}"))?;
+    let ids = encoding.get_ids();
+    let ids = &ids[ids.len().saturating_sub(max_len)..];
+    let mut tokens = Vec::with_capacity(ids.len() + 1);
+    tokens.push(prev_token);
+    tokens.extend_from_slice(ids);
+    Ok(tokens)
+}
+
 /// Build the sorted, deduplicated list of tokens to suppress during decoding.
 ///
 /// Includes the config's `suppress_tokens`, the SOT token, and all timestamp
@@ -403,36 +805,229 @@ fn build_token_mask(suppressed: &[u32], vocab_size: usize) -> Vec<f32> {
         .collect()
 }
 
-/// Resample audio from `from_rate` to `to_rate` using rubato's FFT resampler
-/// with proper polyphase anti-aliasing.
-fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
-    if from_rate == to_rate || samples.is_empty() {
-        return samples.to_vec();
-    }
-    use audioadapter_buffers::owned::InterleavedOwned;
-    use rubato::{Fft, FixedSync, Resampler};
-
-    let mut resampler = Fft::<f32>::new(
-        from_rate as usize,
-        to_rate as usize,
-        1024, // chunk size
-        2,    // sub-chunks
-        1,    // channels (mono)
-        FixedSync::Input,
-    )
-    .expect("failed to create resampler");
-
-    let output_len = resampler.process_all_needed_output_len(samples.len());
-    let input_buf = InterleavedOwned::new_from(samples.to_vec(), 1, samples.len())
-        .expect("failed to create input buffer");
-    let mut output_buf = InterleavedOwned::new(0.0f32, 1, output_len);
-
-    let (_, actual_output_len) = resampler
-        .process_all_into_buffer(&input_buf, &mut output_buf, samples.len(), None)
-        .expect("resampler failed");
-
-    let output = output_buf.take_data();
-    output[..actual_output_len].to_vec()
+/// Index of the largest logit.
+fn argmax(logits: &[f32]) -> u32 {
+    let mut best = 0;
+    for (i, &l) in logits.iter().enumerate() {
+        if l > logits[best] {
+            best = i;
+        }
+    }
+    best as u32
+}
+
+/// `ln(Σ exp(x))`, computed stably. `-inf` entries contribute nothing.
+fn logsumexp(values: &[f32]) -> f32 {
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if max == f32::NEG_INFINITY {
+        return max;
+    }
+    max + values.iter().map(|&v| (v - max).exp()).sum::<f32>().ln()
+}
+
+/// Indices of the `k` largest finite logits, in no particular order.
+fn top_k(logits: &[f32], k: usize) -> Vec<u32> {
+    let mut indices: Vec<u32> = (0..logits.len() as u32).filter(|&i| logits[i as usize].is_finite()).collect();
+    let k = k.min(indices.len());
+    if k > 0 && k < indices.len() {
+        indices.select_nth_unstable_by(k - 1, |&a, &b| logits[b as usize].total_cmp(&logits[a as usize]));
+    }
+    indices.truncate(k);
+    indices
+}
+
+/// Sample a token from `softmax(logits / temperature)`, using `r` drawn
+/// uniformly from `[0, 1)`.
+fn sample(logits: &[f32], temperature: f32, r: f32) -> u32 {
+    let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let weights: Vec<f32> = logits.iter().map(|&l| ((l - max) / temperature).exp()).collect();
+    let mut target = r * weights.iter().sum::<f32>();
+    for (i, &w) in weights.iter().enumerate() {
+        if w > 0.0 {
+            if target < w {
+                return i as u32;
+            }
+            target -= w;
+        }
+    }
+    argmax(logits)
+}
+
+/// xorshift64* generator for temperature sampling, seeded from the clock.
+struct Rng(u64);
+
+impl Rng {
+    fn seeded() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Self(nanos | 1)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Tokens to decode at most for `duration_secs` of audio. Short audio can't
+/// produce many tokens; timestamps come on top of the text.
+fn token_budget(duration_secs: f64, timestamps: bool) -> usize {
+    let text = ((duration_secs * TOKENS_PER_SEC).max(10.0) as usize).min(MAX_DECODE_TOKENS);
+    if timestamps {
+        // Plus the first segment's opening timestamp and the last closing one
+        text + (duration_secs * TIMESTAMP_TOKENS_PER_SEC) as usize + 2
+    } else {
+        text
+    }
+}
+
+/// How many times `next` already ends `output`, ignoring timestamp tokens
+/// (those from `timestamp_begin` on).
+fn trailing_repeats(output: &[u32], next: u32, timestamp_begin: u32) -> usize {
+    output.iter().rev().filter(|&&t| t < timestamp_begin).take_while(|&&t| t == next).count()
+}
+
+/// Ranking score of a finished beam, as in OpenAI's reference decoder: the
+/// mean log-probability per token, or with `length_penalty` α the total
+/// divided by `((5 + length) / 6)^α`.
+fn sequence_score(sum_logprob: f32, len: usize, length_penalty: Option<f32>) -> f32 {
+    let len = len.max(1) as f32;
+    let penalty = match length_penalty {
+        Some(alpha) => ((5.0 + len) / 6.0).powf(alpha),
+        None => len,
+    };
+    sum_logprob / penalty
+}
+
+/// Why a decode should be retried at a higher temperature, if it should:
+/// its text compresses too well (a repetition loop) or its tokens were too
+/// unlikely. A low-confidence decode of audio the model marks as silence is
+/// kept, since no temperature will find speech in it.
+fn fallback_reason(text: &str, avg_logprob: Option<f32>, no_speech_prob: Option<f32>, opts: &DecodeOptions) -> Option<String> {
+    let low_logprob = avg_logprob.filter(|&p| p < opts.fallback_logprob);
+    if low_logprob.is_some() && no_speech_prob.is_some_and(|p| p > NO_SPEECH_THRESHOLD) {
+        return None;
+    }
+    let ratio = compression_ratio(text);
+    if ratio > opts.fallback_compression_ratio {
+        return Some(format!("compression ratio {ratio:.1}"));
+    }
+    low_logprob.map(|p| format!("avg log-prob {p:.2}"))
+}
+
+/// Constrain `logits` (in place) to Whisper's timestamp grammar, given the
+/// tokens decoded so far in this window, as in OpenAI's reference decoder:
+///
+/// - the first token is a timestamp no later than 1 s;
+/// - a segment-opening timestamp is followed by text, and text by a
+///   closing timestamp or more text (or EOT);
+/// - timestamps never decrease;
+/// - a timestamp is forced when timestamps as a whole outweigh any text token.
+fn apply_timestamp_rules(logits: &mut [f32], decoded: &[u32], timestamp_begin: u32, eot_token: u32) {
+    let ts = timestamp_begin as usize;
+    let is_ts = |t: &u32| *t >= timestamp_begin;
+    let last_was_ts = decoded.last().is_some_and(is_ts);
+    let penultimate_was_ts = decoded.len() < 2 || is_ts(&decoded[decoded.len() - 2]);
+
+    if last_was_ts {
+        if penultimate_was_ts {
+            // Segment just opened: must be text
+            logits[ts..].fill(f32::NEG_INFINITY);
+        } else {
+            // Closing a segment: no more text
+            logits[..eot_token as usize].fill(f32::NEG_INFINITY);
+        }
+    }
+
+    if let Some(&last_ts) = decoded.iter().rev().find(|t| is_ts(t)) {
+        // A closing timestamp may repeat the last one only when it ends a segment
+        let min = if last_was_ts && !penultimate_was_ts { last_ts } else { last_ts + 1 };
+        let min = (min as usize).min(logits.len());
+        logits[ts..min].fill(f32::NEG_INFINITY);
+    }
+
+    if decoded.is_empty() {
+        logits[..ts].fill(f32::NEG_INFINITY);
+        let last_allowed = (ts + MAX_INITIAL_TIMESTAMP_STEPS as usize + 1).min(logits.len());
+        logits[last_allowed..].fill(f32::NEG_INFINITY);
+    }
+
+    let norm = logsumexp(logits);
+    let timestamp_logprob = logsumexp(&logits[ts..]) - norm;
+    let max_text_logprob = logits[..ts].iter().copied().fold(f32::NEG_INFINITY, f32::max) - norm;
+    if timestamp_logprob > max_text_logprob {
+        logits[..ts].fill(f32::NEG_INFINITY);
+    }
+}
+
+/// Text tokens of one decoded segment with their timing and confidence.
+#[derive(Debug, PartialEq)]
+struct TimedTokens {
+    tokens: Vec<u32>,
+    start_secs: f64,
+    end_secs: f64,
+    avg_logprob: Option<f32>,
+}
+
+impl TimedTokens {
+    /// All of a window's tokens as one segment spanning `duration_secs`.
+    fn untimed(tokens: &[u32], logprobs: &[f32], duration_secs: f64) -> Self {
+        Self {
+            tokens: tokens.to_vec(),
+            start_secs: 0.0,
+            end_secs: duration_secs,
+            avg_logprob: mean(logprobs),
+        }
+    }
+}
+
+fn mean(values: &[f32]) -> Option<f32> {
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+}
+
+/// Split timestamped output (`<|0.00|> text <|2.40|><|2.40|> text <|5.00|>`)
+/// into segments. `logprobs[i]` is the log-probability of `tokens[i]`; a
+/// segment's average covers its text tokens. Times are clamped to
+/// `duration_secs`, and a segment left open by EOT ends there.
+fn split_timestamped(tokens: &[u32], logprobs: &[f32], timestamp_begin: u32, duration_secs: f64) -> Vec<TimedTokens> {
+    let time = |t: u32| ((t - timestamp_begin) as f64 * TIMESTAMP_STEP_SECS).min(duration_secs);
+    let mut segments = Vec::new();
+    let mut start = 0.0;
+    let mut text: Vec<u32> = Vec::new();
+    let mut text_logprobs: Vec<f32> = Vec::new();
+
+    for (&token, &logprob) in tokens.iter().zip(logprobs) {
+        if token < timestamp_begin {
+            text.push(token);
+            text_logprobs.push(logprob);
+        } else if text.is_empty() {
+            start = time(token);
+        } else {
+            let end = time(token);
+            segments.push(TimedTokens {
+                tokens: std::mem::take(&mut text),
+                start_secs: start,
+                end_secs: end,
+                avg_logprob: mean(&text_logprobs),
+            });
+            text_logprobs.clear();
+            start = end;
+        }
+    }
+    if !text.is_empty() {
+        segments.push(TimedTokens {
+            tokens: text,
+            start_secs: start,
+            end_secs: duration_secs.max(start),
+            avg_logprob: mean(&text_logprobs),
+        });
+    }
+    segments
 }
 
 /// Returns `true` if `text` contains CJK, Hangul, or other non-Latin script
@@ -466,48 +1061,19 @@ fn audio_stats(data: &[f32]) -> (f32, f32, f32) {
 mod tests {
     use super::*;
 
-    // ── resample tests ───────────────────────────────────────────────────
+    // ── token_budget tests ───────────────────────────────────────────────
 
     #[test]
-    fn resample_identity() {
-        let input = vec![1.0, 2.0, 3.0, 4.0];
-        let out = resample(&input, 16000, 16000);
-        assert_eq!(out, input);
+    fn token_budget_scales_with_duration() {
+        assert_eq!(token_budget(0.2, false), 10);
+        assert_eq!(token_budget(4.0, false), 60);
+        assert_eq!(token_budget(30.0, false), MAX_DECODE_TOKENS);
     }
 
     #[test]
-    fn resample_empty() {
-        let out = resample(&[], 44100, 16000);
-        assert!(out.is_empty());
-    }
-
-    #[test]
-    fn resample_downsample_2x() {
-        // 32 kHz -> 16 kHz: output should be approximately half the length.
-        // Use a larger input so the FFT resampler has enough data to process.
-        let input: Vec<f32> = (0..32000).map(|i| (i as f32 / 32000.0).sin()).collect();
-        let out = resample(&input, 32000, 16000);
-        let expected_len = 16000i64;
-        assert!(
-            (out.len() as i64 - expected_len).abs() < 100,
-            "Expected ~{} samples, got {}",
-            expected_len,
-            out.len()
-        );
-    }
-
-    #[test]
-    fn resample_upsample_2x() {
-        // 8 kHz -> 16 kHz: output should be approximately double the length.
-        let input: Vec<f32> = (0..8000).map(|i| (i as f32 / 8000.0).sin()).collect();
-        let out = resample(&input, 8000, 16000);
-        let expected_len = 16000i64;
-        assert!(
-            (out.len() as i64 - expected_len).abs() < 100,
-            "Expected ~{} samples, got {}",
-            expected_len,
-            out.len()
-        );
+    fn token_budget_leaves_room_for_timestamps() {
+        assert_eq!(token_budget(4.0, true), 60 + 16 + 2);
+        assert_eq!(token_budget(30.0, true), MAX_DECODE_TOKENS + 120 + 2);
     }
 
     // ── audio_stats tests ────────────────────────────────────────────────
@@ -823,26 +1389,17 @@ mod tests {
 
     // ── repetition detector tests ───────────────────────────────────────
 
-    /// Simulate the repetition detection logic from the decode loop.
+    /// Feed tokens through the decode loop's repetition check.
     /// Returns the number of tokens accepted before the detector halts.
     fn run_repetition_detector(token_sequence: &[u32]) -> usize {
-        let mut consecutive_repeats: usize = 0;
-        let mut last_token: Option<u32> = None;
-        let mut accepted = 0;
-
+        let mut accepted = Vec::new();
         for &next_token in token_sequence {
-            if last_token == Some(next_token) {
-                consecutive_repeats += 1;
-                if consecutive_repeats >= MAX_CONSECUTIVE_DUPLICATES {
-                    break;
-                }
-            } else {
-                consecutive_repeats = 0;
+            if trailing_repeats(&accepted, next_token, u32::MAX) >= MAX_CONSECUTIVE_DUPLICATES {
+                break;
             }
-            last_token = Some(next_token);
-            accepted += 1;
+            accepted.push(next_token);
         }
-        accepted
+        accepted.len()
     }
 
     #[test]
@@ -901,4 +1458,207 @@ mod tests {
         assert_eq!(model_to_repo("openai/whisper-large-v3"), "openai/whisper-large-v3");
         assert_eq!(model_to_repo("custom-org/my-model"), "custom-org/my-model");
     }
+
+    // ── timestamp decoding tests ────────────────────────────────────────
+
+    // Toy vocabulary: text 0..5, EOT 5, specials 6..8, timestamps 8..70
+    const TS: u32 = 8;
+    const EOT: u32 = 5;
+    const VOCAB: usize = 70;
+
+    fn allowed(logits: &[f32]) -> Vec<u32> {
+        (0..logits.len() as u32).filter(|&i| logits[i as usize].is_finite()).collect()
+    }
+
+    #[test]
+    fn timestamp_rules_start_with_early_timestamp() {
+        let mut logits = vec![0.0; VOCAB];
+        apply_timestamp_rules(&mut logits, &[], TS, EOT);
+        let expected: Vec<u32> = (TS..=TS + MAX_INITIAL_TIMESTAMP_STEPS).collect();
+        assert_eq!(allowed(&logits), expected);
+    }
+
+    #[test]
+    fn timestamp_rules_follow_segment_grammar() {
+        // After an opening timestamp: text only (uniform logits, so text
+        // would otherwise lose to the many timestamps)
+        let mut logits = vec![0.0; VOCAB];
+        apply_timestamp_rules(&mut logits, &[TS + 10], TS, EOT);
+        assert!(allowed(&logits).iter().all(|&t| t < TS));
+
+        // After text: no timestamp earlier than the segment start
+        let mut logits = vec![0.0; VOCAB];
+        logits[..TS as usize].fill(5.0);
+        apply_timestamp_rules(&mut logits, &[TS + 10, 1], TS, EOT);
+        let allowed_now = allowed(&logits);
+        assert!(allowed_now.contains(&1));
+        assert!(!allowed_now.contains(&(TS + 10)));
+        assert!(allowed_now.contains(&(TS + 11)));
+
+        // After a closing timestamp: another timestamp (from the same time) or EOT
+        let mut logits = vec![0.0; VOCAB];
+        apply_timestamp_rules(&mut logits, &[TS + 10, 1, TS + 20], TS, EOT);
+        let allowed_now = allowed(&logits);
+        assert!(allowed_now.iter().all(|&t| t == EOT || (6..TS).contains(&t) || t >= TS + 20));
+        assert!(allowed_now.contains(&(TS + 20)));
+    }
+
+    #[test]
+    fn timestamp_rules_force_timestamp_when_timestamps_dominate() {
+        let mut logits = vec![0.0; VOCAB];
+        logits[2] = 1.0; // best single token, but timestamps hold more mass
+        apply_timestamp_rules(&mut logits, &[TS, 1], TS, EOT);
+        assert!(allowed(&logits).iter().all(|&t| t >= TS));
+    }
+
+    #[test]
+    fn split_timestamped_segments() {
+        let tokens = [TS, 1, 2, TS + 50, TS + 50, 3, TS + 100, TS + 100, 4];
+        let logprobs = [-0.1, -0.2, -0.4, -0.1, -0.1, -1.0, -0.1, -0.1, -0.5];
+        let segments = split_timestamped(&tokens, &logprobs, TS, 2.5);
+        assert_eq!(segments.len(), 3);
+
+        assert_eq!(segments[0].tokens, vec![1, 2]);
+        assert_eq!((segments[0].start_secs, segments[0].end_secs), (0.0, 1.0));
+        assert!((segments[0].avg_logprob.unwrap() - -0.3).abs() < 1e-6);
+
+        assert_eq!(segments[1].tokens, vec![3]);
+        assert_eq!((segments[1].start_secs, segments[1].end_secs), (1.0, 2.0));
+
+        // Left open by EOT: ends with the audio
+        assert_eq!(segments[2].tokens, vec![4]);
+        assert_eq!((segments[2].start_secs, segments[2].end_secs), (2.0, 2.5));
+    }
+
+    #[test]
+    fn untimed_segment_spans_window() {
+        let t = TimedTokens::untimed(&[1, 2], &[-0.5, -1.5], 3.0);
+        assert_eq!((t.start_secs, t.end_secs), (0.0, 3.0));
+        assert_eq!(t.avg_logprob, Some(-1.0));
+        assert_eq!(TimedTokens::untimed(&[], &[], 1.0).avg_logprob, None);
+    }
+
+    #[test]
+    fn logsumexp_and_argmax() {
+        let values = [0.0, f32::NEG_INFINITY, 0.0];
+        assert!((logsumexp(&values) - 2f32.ln()).abs() < 1e-6);
+        assert_eq!(logsumexp(&[f32::NEG_INFINITY; 3]), f32::NEG_INFINITY);
+        assert_eq!(argmax(&[0.1, 3.0, f32::NEG_INFINITY, 2.0]), 1);
+    }
+
+    #[test]
+    fn repetition_detector_ignores_timestamps() {
+        // Timestamps between repeats neither break nor extend a run
+        assert_eq!(trailing_repeats(&[3, TS + 3, 3], 3, TS), 2);
+        assert_eq!(trailing_repeats(&[3, 4, TS], 3, TS), 0);
+    }
+
+    // ── beam search and temperature fallback tests ─────────────────────
+
+    fn options() -> DecodeOptions {
+        DecodeOptions {
+            beam_size: 5,
+            length_penalty: None,
+            temperatures: vec![0.0, 0.2, 0.4],
+            fallback_compression_ratio: 2.4,
+            fallback_logprob: -1.0,
+        }
+    }
+
+    #[test]
+    fn top_k_picks_largest_finite_logits() {
+        let logits = [0.5, f32::NEG_INFINITY, 3.0, 1.0, 2.0];
+        let mut top = top_k(&logits, 3);
+        top.sort_unstable();
+        assert_eq!(top, vec![2, 3, 4]);
+        assert_eq!(top_k(&[f32::NEG_INFINITY, 1.0], 3), vec![1]);
+    }
+
+    #[test]
+    fn sampling_follows_temperature() {
+        let logits = [2.0, 0.0, f32::NEG_INFINITY];
+        // softmax([2, 0]) ≈ [0.88, 0.12]
+        assert_eq!(sample(&logits, 1.0, 0.5), 0);
+        assert_eq!(sample(&logits, 1.0, 0.95), 1);
+        // Hotter: the split moves towards 50/50
+        assert_eq!(sample(&logits, 10.0, 0.6), 1);
+        // Masked tokens are never drawn
+        let mut rng = Rng(12345);
+        for _ in 0..1000 {
+            assert_ne!(sample(&logits, 1.0, rng.next_f32()), 2);
+        }
+    }
+
+    #[test]
+    fn rng_is_uniform_in_unit_interval() {
+        let mut rng = Rng(42);
+        let draws: Vec<f32> = (0..10_000).map(|_| rng.next_f32()).collect();
+        assert!(draws.iter().all(|&r| (0.0..1.0).contains(&r)));
+        let mean = draws.iter().sum::<f32>() / draws.len() as f32;
+        assert!((mean - 0.5).abs() < 0.02, "mean {mean}");
+    }
+
+    #[test]
+    fn sequence_score_normalizes_length() {
+        // Without a penalty, the mean log-prob decides: the longer beam wins
+        assert!(sequence_score(-3.0, 10, None) > sequence_score(-1.0, 2, None));
+        // α = 0 ranks by total log-prob: the shorter beam wins
+        assert!(sequence_score(-3.0, 10, Some(0.0)) < sequence_score(-1.0, 2, Some(0.0)));
+        assert_eq!(sequence_score(-2.0, 1, Some(1.0)), -2.0);
+    }
+
+    #[test]
+    fn fallback_on_repetition_or_low_confidence() {
+        let opts = options();
+        let looped = "and then ".repeat(12);
+        assert!(fallback_reason(&looped, Some(-0.2), Some(0.1), &opts).unwrap().starts_with("compression ratio"));
+        assert!(fallback_reason("Hello there.", Some(-1.4), Some(0.1), &opts).unwrap().starts_with("avg log-prob"));
+        assert_eq!(fallback_reason("Hello there.", Some(-0.3), Some(0.1), &opts), None);
+        // Silence decoded with low confidence: no fallback
+        assert_eq!(fallback_reason(&looped, Some(-1.4), Some(0.9), &opts), None);
+        assert_eq!(fallback_reason("", None, None, &opts), None);
+    }
+
+    #[test]
+    fn decode_options_default_to_greedy_without_fallback() {
+        let opts = DecodeOptions::from_config(&SttConfig::default());
+        assert_eq!(opts.beam_size, 1);
+        assert_eq!(opts.temperatures, vec![0.0]);
+
+        let cfg = SttConfig { beam_size: 0, temperatures: vec![], ..Default::default() };
+        let opts = DecodeOptions::from_config(&cfg);
+        assert_eq!(opts.beam_size, 1);
+        assert_eq!(opts.temperatures, vec![0.0]);
+    }
+
+    /// Token ids in Whisper's layout: `<|en|>` is 50259 and languages follow in order.
+    fn language_token(tag: &str) -> Option<u32> {
+        let code = tag.strip_prefix("<|")?.strip_suffix("|>")?;
+        WHISPER_LANGUAGES.iter().position(|&l| l == code).map(|i| 50259 + i as u32)
+    }
+
+    #[test]
+    fn language_candidates_follow_allowed_list() {
+        let all = language_candidates(&[], language_token);
+        assert_eq!(all.len(), WHISPER_LANGUAGES.len());
+        assert_eq!(all[0], ("en".to_string(), 50259));
+
+        let allowed = vec!["de".to_string(), "EN".to_string(), "klingon".to_string()];
+        let some = language_candidates(&allowed, language_token);
+        assert_eq!(some, vec![("en".to_string(), 50259), ("de".to_string(), 50261)]);
+
+        // English-only vocabulary: nothing to detect
+        assert!(language_candidates(&[], |_| None).is_empty());
+    }
+
+    #[test]
+    fn pick_language_softmaxes_over_candidates() {
+        let candidates = vec![("en".to_string(), 1), ("de".to_string(), 3)];
+        // Token 2 scores highest overall but isn't a candidate
+        let logits = [0.0, 1.0, 9.0, 1.0 + 3f32.ln()];
+        let (best, prob) = pick_language(&logits, &candidates).unwrap();
+        assert_eq!(best, 1);
+        assert!((prob - 0.75).abs() < 1e-5);
+        assert_eq!(pick_language(&logits, &[]), None);
+    }
 }
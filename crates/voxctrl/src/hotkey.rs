@@ -1,24 +1,31 @@
-//! Global hotkey — configurable toggle with graceful fallback.
+//! Global hotkey — configurable toggle / push-to-talk with graceful fallback.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use global_hotkey::hotkey::{Code, HotKey, Modifiers};
 use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
 
-use voxctrl_core::config::{Config, HotkeyConfig};
+use voxctrl_core::config::{Config, HotkeyConfig, HotkeyMode};
+use voxctrl_core::recording;
 use voxctrl_core::pipeline::SharedPipeline;
 use voxctrl_core::SharedState;
 
+/// Delay before undoing, so the hotkey's modifiers are released and don't
+/// turn the backspaces into word deletions.
+const UNDO_DELAY: Duration = Duration::from_millis(300);
+
 /// Registered global hotkeys (HotKey is Copy; derive IDs via `.id()`).
 pub struct HotkeyIds {
     pub dictation: Option<HotKey>,
     pub computer_use: Option<HotKey>,
+    pub undo: Option<HotKey>,
 }
 
 impl HotkeyIds {
     pub fn none() -> Self {
-        Self { dictation: None, computer_use: None }
+        Self { dictation: None, computer_use: None, undo: None }
     }
 }
 
@@ -192,15 +199,35 @@ pub fn setup_hotkeys(cfg: &HotkeyConfig) -> Result<Option<(GlobalHotKeyManager,
         None
     };
 
+    // Register undo hotkey if configured
+    let undo_hotkey = match cfg.undo_shortcut.as_deref().map(|s| (s, parse_shortcut(s))) {
+        Some((s, Ok(hk))) => match manager.register(hk) {
+            Ok(()) => {
+                log::info!("Global undo hotkey registered: {s}");
+                Some(hk)
+            }
+            Err(e) => {
+                log::warn!("Failed to register undo hotkey {s:?}: {e}. Undo hotkey disabled.");
+                None
+            }
+        },
+        Some((s, Err(e))) => {
+            log::warn!("Invalid undo hotkey {s:?}: {e}. Undo hotkey disabled.");
+            None
+        }
+        None => None,
+    };
+
     Ok(Some((manager, HotkeyIds {
         dictation: Some(hotkey),
         computer_use: cu_hotkey,
+        undo: undo_hotkey,
     })))
 }
 
 /// Unregister all active hotkeys (e.g. before opening Settings subprocess).
 pub fn unregister_hotkeys(manager: &GlobalHotKeyManager, ids: &HotkeyIds) {
-    let hotkeys: Vec<HotKey> = [ids.dictation, ids.computer_use].into_iter().flatten().collect();
+    let hotkeys: Vec<HotKey> = [ids.dictation, ids.computer_use, ids.undo].into_iter().flatten().collect();
     if hotkeys.is_empty() { return; }
     if let Err(e) = manager.unregister_all(&hotkeys) {
         log::warn!("Failed to unregister hotkeys: {e}");
@@ -209,22 +236,64 @@ pub fn unregister_hotkeys(manager: &GlobalHotKeyManager, ids: &HotkeyIds) {
     }
 }
 
-/// Handle a hotkey event: toggle Idle → Recording → Transcribing.
+/// Handle a hotkey event: Idle → Recording → Transcribing.
+///
+/// The dictation hotkey sends the utterance through `pipeline`, the
+/// computer-use hotkey through `cu_pipeline` (`None` when computer use is
+/// unavailable). Both share one recorder, so the key that stops a recording
+/// picks the pipeline.
 pub fn handle_hotkey_event(
     event: &GlobalHotKeyEvent,
     ids: &HotkeyIds,
     state: &Arc<SharedState>,
     cfg: &Config,
     pipeline: &Arc<SharedPipeline>,
+    cu_pipeline: Option<&Arc<SharedPipeline>>,
 ) {
-    if event.state != HotKeyState::Pressed {
-        return;
-    }
     if ids.dictation.map(|hk| hk.id()) == Some(event.id) {
-        voxctrl_core::recording::toggle_recording(state, cfg, pipeline);
+        drive_recording(cfg.hotkey.dict_mode, event.state, state, cfg, pipeline);
     } else if ids.computer_use.map(|hk| hk.id()) == Some(event.id) {
-        log::info!("Computer-use hotkey pressed");
-        // TODO: Route to CU pipeline when connected
+        match cu_pipeline {
+            Some(cu_pipeline) => drive_recording(cfg.hotkey.cu_mode, event.state, state, cfg, cu_pipeline),
+            None if event.state == HotKeyState::Pressed => {
+                log::warn!("Computer-use hotkey pressed, but the computer-use pipeline is unavailable");
+            }
+            None => {}
+        }
+    } else if ids.undo.map(|hk| hk.id()) == Some(event.id) {
+        if event.state != HotKeyState::Pressed {
+            return;
+        }
+        let pipeline = pipeline.get();
+        std::thread::spawn(move || {
+            std::thread::sleep(UNDO_DELAY);
+            if let Err(e) = pipeline.undo() {
+                log::error!("Undo failed: {e:#}");
+            }
+        });
+    }
+}
+
+/// Advance recording for one hotkey event.
+///
+/// In toggle mode each press advances the state and releases are ignored;
+/// in hold mode (push-to-talk) press starts recording and release stops it.
+fn drive_recording(
+    mode: HotkeyMode,
+    key_state: HotKeyState,
+    state: &Arc<SharedState>,
+    cfg: &Config,
+    pipeline: &Arc<SharedPipeline>,
+) {
+    match (mode, key_state) {
+        (HotkeyMode::Toggle, HotKeyState::Pressed) => {
+            recording::toggle_recording(state, cfg, pipeline);
+        }
+        (HotkeyMode::Toggle, HotKeyState::Released) => {}
+        (HotkeyMode::Hold, HotKeyState::Pressed) => recording::start_recording(state, cfg, pipeline),
+        (HotkeyMode::Hold, HotKeyState::Released) => {
+            recording::stop_recording(state, cfg, pipeline);
+        }
     }
 }
 
@@ -277,42 +346,60 @@ mod tests {
 
     // ── handle_hotkey_event tests ────────────────────────────────────
 
+    use std::sync::Mutex;
     use voxctrl_core::{AppStatus, SharedState};
 
     fn make_test_ids() -> (HotkeyIds, u32) {
         let hk = HotKey::new(Some(Modifiers::CONTROL), Code::Space);
         let id = hk.id();
-        let ids = HotkeyIds { dictation: Some(hk), computer_use: None };
+        let ids = HotkeyIds { dictation: Some(hk), computer_use: None, undo: None };
         (ids, id)
     }
 
     fn make_test_pipeline() -> Arc<SharedPipeline> {
+        make_recording_pipeline().0
+    }
+
+    /// Pipeline transcribing any audio as "hello", with the dictated text it executed.
+    fn make_recording_pipeline() -> (Arc<SharedPipeline>, Arc<Mutex<Vec<String>>>) {
         use voxctrl_core::action::ActionExecutor;
         use voxctrl_core::pipeline::Pipeline;
         use voxctrl_core::router::{Intent, IntentRouter};
         use voxctrl_core::stt::Transcriber;
 
-        struct Noop;
-        impl Transcriber for Noop {
-            fn transcribe(&self, _: &std::path::Path) -> anyhow::Result<String> { Ok(String::new()) }
-            fn transcribe_pcm(&self, _: &[f32], _: u32) -> anyhow::Result<String> { Ok(String::new()) }
-            fn name(&self) -> &str { "noop" }
+        struct Hello;
+        impl Transcriber for Hello {
+            fn transcribe(&self, _: &std::path::Path) -> anyhow::Result<String> { Ok("hello".into()) }
+            fn transcribe_pcm(&self, _: &[f32], _: u32) -> anyhow::Result<String> { Ok("hello".into()) }
+            fn name(&self) -> &str { "hello" }
             fn is_available(&self) -> bool { true }
         }
-        impl IntentRouter for Noop {
+        struct Dictate;
+        impl IntentRouter for Dictate {
             fn route(&self, t: &str) -> anyhow::Result<Intent> { Ok(Intent::Dictate(t.into())) }
-            fn name(&self) -> &str { "noop" }
+            fn name(&self) -> &str { "dictate" }
         }
-        impl ActionExecutor for Noop {
-            fn execute(&self, _: &Intent) -> anyhow::Result<()> { Ok(()) }
-            fn name(&self) -> &str { "noop" }
+        struct Record(Arc<Mutex<Vec<String>>>);
+        impl ActionExecutor for Record {
+            fn execute(&self, intent: &Intent) -> anyhow::Result<()> {
+                if let Intent::Dictate(t) = intent {
+                    self.0.lock().unwrap().push(t.clone());
+                }
+                Ok(())
+            }
+            fn name(&self) -> &str { "record" }
         }
 
-        Arc::new(SharedPipeline::new(Pipeline {
-            stt: Box::new(Noop),
-            router: Box::new(Noop),
-            action: Box::new(Noop),
-        }))
+        let executed = Arc::new(Mutex::new(vec![]));
+        let pipeline = Arc::new(SharedPipeline::new(Pipeline {
+            stt: Arc::new(Hello),
+            postprocess: vec![],
+            router: Box::new(Dictate),
+            action: Box::new(Record(executed.clone())),
+            hallucination: None,
+            history: None,
+        }));
+        (pipeline, executed)
     }
 
     #[test]
@@ -323,9 +410,9 @@ mod tests {
         let pipeline = make_test_pipeline();
 
         let event = GlobalHotKeyEvent { id, state: HotKeyState::Pressed };
-        handle_hotkey_event(&event, &ids, &state, &cfg, &pipeline);
+        handle_hotkey_event(&event, &ids, &state, &cfg, &pipeline, None);
 
-        assert_eq!(*state.status.lock().unwrap(), AppStatus::Recording);
+        assert_eq!(state.status.load(), AppStatus::Recording);
     }
 
     #[test]
@@ -336,9 +423,45 @@ mod tests {
         let pipeline = make_test_pipeline();
 
         let event = GlobalHotKeyEvent { id, state: HotKeyState::Released };
-        handle_hotkey_event(&event, &ids, &state, &cfg, &pipeline);
+        handle_hotkey_event(&event, &ids, &state, &cfg, &pipeline, None);
+
+        assert_eq!(state.status.load(), AppStatus::Idle);
+    }
+
+    #[test]
+    fn hold_mode_press_starts_and_release_stops() {
+        let (ids, id) = make_test_ids();
+        let state = Arc::new(SharedState::new());
+        let mut cfg = voxctrl_core::config::Config::default();
+        cfg.hotkey.dict_mode = HotkeyMode::Hold;
+        let pipeline = make_test_pipeline();
+
+        let press = GlobalHotKeyEvent { id, state: HotKeyState::Pressed };
+        handle_hotkey_event(&press, &ids, &state, &cfg, &pipeline, None);
+        assert_eq!(state.status.load(), AppStatus::Recording);
+
+        // Auto-repeat while held keeps recording
+        handle_hotkey_event(&press, &ids, &state, &cfg, &pipeline, None);
+        assert_eq!(state.status.load(), AppStatus::Recording);
+
+        // No audio captured → release returns straight to idle
+        let release = GlobalHotKeyEvent { id, state: HotKeyState::Released };
+        handle_hotkey_event(&release, &ids, &state, &cfg, &pipeline, None);
+        assert_eq!(state.status.load(), AppStatus::Idle);
+    }
+
+    #[test]
+    fn hold_mode_release_while_idle_is_ignored() {
+        let (ids, id) = make_test_ids();
+        let state = Arc::new(SharedState::new());
+        let mut cfg = voxctrl_core::config::Config::default();
+        cfg.hotkey.dict_mode = HotkeyMode::Hold;
+        let pipeline = make_test_pipeline();
+
+        let release = GlobalHotKeyEvent { id, state: HotKeyState::Released };
+        handle_hotkey_event(&release, &ids, &state, &cfg, &pipeline, None);
 
-        assert_eq!(*state.status.lock().unwrap(), AppStatus::Idle);
+        assert_eq!(state.status.load(), AppStatus::Idle);
     }
 
     #[test]
@@ -349,8 +472,57 @@ mod tests {
         let pipeline = make_test_pipeline();
 
         let unrelated = GlobalHotKeyEvent { id: 99999, state: HotKeyState::Pressed };
-        handle_hotkey_event(&unrelated, &ids, &state, &cfg, &pipeline);
+        handle_hotkey_event(&unrelated, &ids, &state, &cfg, &pipeline, None);
+
+        assert_eq!(state.status.load(), AppStatus::Idle);
+    }
+
+    fn make_cu_ids() -> (HotkeyIds, u32) {
+        let hk = HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::KeyC);
+        let id = hk.id();
+        let ids = HotkeyIds { dictation: None, computer_use: Some(hk), undo: None };
+        (ids, id)
+    }
+
+    #[test]
+    fn cu_hotkey_records_through_cu_pipeline() {
+        let (ids, id) = make_cu_ids();
+        let state = Arc::new(SharedState::new());
+        let mut cfg = voxctrl_core::config::Config::default();
+        cfg.hotkey.cu_mode = HotkeyMode::Hold;
+        let (pipeline, dictated) = make_recording_pipeline();
+        let (cu_pipeline, cu_executed) = make_recording_pipeline();
+
+        let press = GlobalHotKeyEvent { id, state: HotKeyState::Pressed };
+        handle_hotkey_event(&press, &ids, &state, &cfg, &pipeline, Some(&cu_pipeline));
+        assert_eq!(state.status.load(), AppStatus::Recording);
+        // Stand in for the recorder thread
+        state.chunks.lock().unwrap().extend_from_slice(&[0.1; 160]);
+
+        let release = GlobalHotKeyEvent { id, state: HotKeyState::Released };
+        handle_hotkey_event(&release, &ids, &state, &cfg, &pipeline, Some(&cu_pipeline));
+        for _ in 0..200 {
+            if state.status.load() == AppStatus::Idle {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(state.status.load(), AppStatus::Idle);
+        assert_eq!(&*cu_executed.lock().unwrap(), &["hello"]);
+        assert!(dictated.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn cu_hotkey_without_cu_pipeline_is_ignored() {
+        let (ids, id) = make_cu_ids();
+        let state = Arc::new(SharedState::new());
+        let cfg = voxctrl_core::config::Config::default();
+        let pipeline = make_test_pipeline();
+
+        let press = GlobalHotKeyEvent { id, state: HotKeyState::Pressed };
+        handle_hotkey_event(&press, &ids, &state, &cfg, &pipeline, None);
 
-        assert_eq!(*state.status.lock().unwrap(), AppStatus::Idle);
+        assert_eq!(state.status.load(), AppStatus::Idle);
     }
 }
@@ -19,7 +19,7 @@ use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::Result;
 
-use voxctrl_core::SharedState;
+use voxctrl_core::{AppStatus, SharedState};
 use voxctrl_core::config;
 use voxctrl_core::models;
 use voxctrl_core::pipeline;
@@ -38,9 +38,14 @@ enum UiMode {
     Gui,
     #[cfg(feature = "tui")]
     Tui,
+    /// No UI: process the audio source until it ends (`--headless`).
+    Headless,
 }
 
 fn pick_ui_mode() -> UiMode {
+    if std::env::args().any(|a| a == "--headless") {
+        return UiMode::Headless;
+    }
     let wants_tui = std::env::args().any(|a| a == "--tui" || a == "-t");
 
     #[cfg(all(feature = "gui", feature = "tui"))]
@@ -73,7 +78,7 @@ fn run_gui(
     state: Arc<SharedState>,
     cfg: config::Config,
     pipeline: Arc<pipeline::SharedPipeline>,
-    audio_stream: cpal::Stream,
+    audio_stream: audio::CaptureStream,
     registry: Arc<Mutex<models::ModelRegistry>>,
     action_factory: Option<Box<voxctrl_core::action::ActionFactory>>,
 ) -> Result<()> {
@@ -82,21 +87,22 @@ fn run_gui(
     use tray_icon::TrayIconEvent;
     use winit::application::ApplicationHandler;
     use winit::event::WindowEvent;
-    use winit::event_loop::{ActiveEventLoop, EventLoop};
+    use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 
     /// How often to poll config.json mtime for hot-reload.
     const CONFIG_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
     struct App {
         state: Arc<SharedState>,
-        #[allow(dead_code)]
         tray: Option<tray_icon::TrayIcon>,
         #[allow(dead_code)]
         hotkey_manager: Option<global_hotkey::GlobalHotKeyManager>,
         hotkey_ids: hotkey::HotkeyIds,
         cfg: config::Config,
         pipeline: Arc<pipeline::SharedPipeline>,
-        _audio_stream: Option<cpal::Stream>,
+        /// Driven by the computer-use hotkey; `None` when CU is unavailable.
+        cu_pipeline: Option<Arc<pipeline::SharedPipeline>>,
+        audio: audio::device::CaptureSupervisor,
         #[allow(dead_code)]
         registry: Arc<Mutex<models::ModelRegistry>>,
         menu_ids: tray::TrayMenuIds,
@@ -108,44 +114,69 @@ fn run_gui(
     }
 
     impl App {
+        /// Show the active microphone, or that it's being reconnected, in the tray.
+        fn update_device_tooltip(&self) {
+            if let Some(tray) = &self.tray {
+                if let Err(e) = tray::update_tray_device(tray, self.audio.device_name()) {
+                    log::warn!("Failed to update tray tooltip: {e}");
+                }
+            }
+        }
+
         /// Diff old vs new config and apply changes without restart.
         fn apply_config_changes(&mut self, new_cfg: config::Config) {
             let old = &self.cfg;
 
-            // Audio section changed → recreate audio stream
-            if new_cfg.audio != old.audio {
+            // Audio or VAD section changed → recreate audio stream
+            // (hands-free segmentation lives alongside the stream)
+            if new_cfg.audio != old.audio || new_cfg.vad != old.vad {
                 log::info!("Audio config changed — recreating stream");
                 // Drop old stream first
-                self._audio_stream = None;
-                match audio::start_capture(self.state.clone(), &new_cfg) {
+                self.audio.replace(None);
+                match audio::start_capture(self.state.clone(), &new_cfg, self.pipeline.clone()) {
                     Ok(stream) => {
-                        self._audio_stream = Some(stream);
+                        self.audio.replace(Some(stream));
                         log::info!("Audio stream recreated");
                     }
                     Err(e) => log::error!("Failed to recreate audio stream: {e}"),
                 }
+                self.update_device_tooltip();
             }
 
             // Pipeline-affecting sections changed → rebuild pipeline
-            if new_cfg.stt != old.stt
+            let rebuild = new_cfg.stt != old.stt
                 || new_cfg.vad != old.vad
+                || new_cfg.postprocess != old.postprocess
                 || new_cfg.router != old.router
                 || new_cfg.action != old.action
-                || new_cfg.gpu != old.gpu
-            {
+                || new_cfg.history != old.history
+                || new_cfg.gpu != old.gpu;
+            let cu_toggled =
+                new_cfg.hotkey.cu_shortcut.is_some() != old.hotkey.cu_shortcut.is_some();
+            let hotkeys_changed = new_cfg.hotkey != old.hotkey;
+
+            if rebuild {
                 self.rebuild_pipeline(&new_cfg);
+            } else if cu_toggled {
+                // CU hotkey set or cleared → build or drop the CU pipeline
+                self.cu_pipeline = build_cu_pipeline(
+                    &new_cfg,
+                    &self.pipeline,
+                    self.action_factory.as_deref(),
+                );
             }
 
             // Hotkeys changed AND settings not open → reapply
-            if new_cfg.hotkey != old.hotkey && self.settings_child.is_none() {
+            if hotkeys_changed && self.settings_child.is_none() {
                 self.reapply_hotkeys(&new_cfg.hotkey);
             }
 
             self.cfg = new_cfg;
         }
 
-        /// Build a new pipeline from config and swap it in.
-        fn rebuild_pipeline(&self, cfg: &config::Config) {
+        /// Build a new pipeline from config and swap it in, along with the
+        /// computer-use pipeline that shares its STT backend.
+        fn rebuild_pipeline(&mut self, cfg: &config::Config) {
             let gpus = voxctrl_core::gpu::detect_gpus();
             let gpu_mode = voxctrl_core::gpu::resolve_gpu_mode(&cfg.gpu, &gpus);
             let whisper_device = voxctrl_core::gpu::gpu_mode_to_whisper_device(gpu_mode);
@@ -175,6 +206,9 @@ fn run_gui(
                 }
                 Err(e) => log::error!("Failed to rebuild pipeline: {e}"),
             }
+
+            self.cu_pipeline =
+                build_cu_pipeline(cfg, &self.pipeline, self.action_factory.as_deref());
         }
 
         /// Unregister old hotkeys, set up new ones from config.
@@ -209,6 +243,9 @@ fn run_gui(
         }
 
         fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+            // Wake periodically for config polling and device recovery
+            _event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now() + CONFIG_POLL_INTERVAL));
+
             if let Ok(event) = TrayIconEvent::receiver().try_recv() {
                 log::trace!("Tray event: {:?}", event);
             }
@@ -285,6 +322,15 @@ fn run_gui(
                 }
             }
 
+            // Reopen capture if the microphone was unplugged
+            let (state, cfg, pipeline) = (&self.state, &self.cfg, &self.pipeline);
+            let event = self.audio.poll(Instant::now(), || {
+                audio::start_capture(state.clone(), cfg, pipeline.clone())
+            });
+            if event.is_some() {
+                self.update_device_tooltip();
+            }
+
             if let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
                 hotkey::handle_hotkey_event(
                     &event,
@@ -292,6 +338,7 @@ fn run_gui(
                     &self.state,
                     &self.cfg,
                     &self.pipeline,
+                    self.cu_pipeline.as_ref(),
                 );
             }
         }
@@ -322,6 +369,8 @@ fn run_gui(
         }
     }
 
+    let cu_pipeline = build_cu_pipeline(&cfg, &pipeline, action_factory.as_deref());
+
     let mut app = App {
         state,
         tray: Some(tray),
@@ -329,7 +378,8 @@ fn run_gui(
         hotkey_ids,
         cfg,
         pipeline,
-        _audio_stream: Some(audio_stream),
+        cu_pipeline,
+        audio: audio::device::CaptureSupervisor::new(Some(audio_stream)),
         registry,
         menu_ids,
         settings_child: None,
@@ -347,6 +397,74 @@ fn run_gui(
     Ok(())
 }
 
+// ── Headless mode ─────────────────────────────────────────────────────────
+
+/// Apply `--audio-file PATH`, `--audio-fifo PATH` and `--audio-stdin`, which
+/// override `audio.source` for this run.
+fn apply_audio_source_args(audio: &mut config::AudioConfig, args: &[String]) {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--audio-file" | "--audio-fifo" => match args.next() {
+                Some(path) => {
+                    audio.source = arg.trim_start_matches("--audio-").into();
+                    audio.source_path = Some(path.into());
+                }
+                None => log::warn!("{arg} needs a path; ignoring"),
+            },
+            "--audio-stdin" => audio.source = "stdin".into(),
+            _ => {}
+        }
+    }
+}
+
+/// Run the pipeline on the audio source without a UI, until the source ends.
+///
+/// Hands-free mode segments utterances with the VAD; otherwise the whole
+/// input is transcribed as one recording, so audio past
+/// `audio.max_recording_secs` is ignored.
+fn run_headless(
+    state: Arc<SharedState>,
+    cfg: &config::Config,
+    pipeline: Arc<pipeline::SharedPipeline>,
+) -> Result<()> {
+    // Recording must be open before the source starts delivering
+    if !cfg.vad.hands_free {
+        log::info!(
+            "Headless: transcribing the input as one recording of at most {}s (enable vad.hands_free for longer input)",
+            cfg.audio.max_recording_secs,
+        );
+        voxctrl_core::recording::start_recording(&state, cfg, &pipeline);
+    }
+    log::info!("Headless: reading audio from {}", cfg.audio.source);
+    audio::start_capture(state.clone(), cfg, pipeline.clone())?.wait();
+
+    if !cfg.vad.hands_free {
+        voxctrl_core::recording::stop_recording(&state, cfg, &pipeline);
+        while state.status.load() != AppStatus::Idle {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+    }
+    Ok(())
+}
+
+/// Open the always-on capture stream and the STT server for the GUI or TUI.
+#[cfg(any(feature = "gui", feature = "tui"))]
+fn start_interactive(
+    state: &Arc<SharedState>,
+    cfg: &config::Config,
+    pipeline: &Arc<pipeline::SharedPipeline>,
+) -> Result<audio::CaptureStream> {
+    log::info!("Starting audio capture...");
+    let audio_stream = audio::start_capture(state.clone(), cfg, pipeline.clone())?;
+    log::info!("Audio stream open (always-on)");
+
+    log::info!("Starting STT server...");
+    stt_server::start(pipeline.clone())?;
+    log::info!("STT server started");
+    Ok(audio_stream)
+}
+
 // ── Entry point ────────────────────────────────────────────────────────────
 
 fn main() -> Result<()> {
@@ -398,6 +516,30 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Build the computer-use pipeline when a CU hotkey is configured and the
+/// computer-use action backend is compiled in.
+///
+/// Shares the dictation pipeline's loaded STT backend and history.
+#[cfg(feature = "gui")]
+fn build_cu_pipeline(
+    cfg: &config::Config,
+    pipeline: &pipeline::SharedPipeline,
+    action_factory: Option<&voxctrl_core::action::ActionFactory>,
+) -> Option<Arc<pipeline::SharedPipeline>> {
+    cfg.hotkey.cu_shortcut.as_ref()?;
+    let Some(factory) = action_factory else {
+        log::warn!("Computer-use hotkey configured, but computer use is not compiled in");
+        return None;
+    };
+    match pipeline::Pipeline::computer_use_from_config(cfg, &pipeline.get(), Some(factory)) {
+        Ok(p) => Some(Arc::new(pipeline::SharedPipeline::new(p))),
+        Err(e) => {
+            log::warn!("Computer-use pipeline unavailable: {e}");
+            None
+        }
+    }
+}
+
 /// Build the action factory that chains platform-specific computer-use providers.
 ///
 /// Returns None if no cu-* features are enabled (action module falls back to core builtins).
@@ -443,7 +585,8 @@ fn run() -> Result<()> {
     let ui_mode = pick_ui_mode();
     log::info!("UI mode: {:?}", ui_mode);
 
-    let cfg = config::load_config();
+    let mut cfg = config::load_config();
+    apply_audio_source_args(&mut cfg.audio, &std::env::args().collect::<Vec<_>>());
     log::info!("Config: stt={}, vad={}, router={}, action={}, gpu={}",
         cfg.stt.backend, cfg.vad.backend, cfg.router.backend, cfg.action.backend, cfg.gpu.backend);
 
@@ -515,7 +658,6 @@ fn run() -> Result<()> {
     }
 
     // Override whisper_device based on resolved GPU mode
-    let mut cfg = cfg;
     let whisper_device = voxctrl_core::gpu::gpu_mode_to_whisper_device(gpu_mode);
     if cfg.stt.whisper_device != whisper_device {
         log::info!("Overriding whisper_device: {} → {}", cfg.stt.whisper_device, whisper_device);
@@ -555,19 +697,18 @@ fn run() -> Result<()> {
     ));
     log::info!("Pipeline created");
 
-    log::info!("Starting audio capture...");
-    let audio_stream = audio::start_capture(state.clone(), &cfg)?;
-    log::info!("Audio stream open (always-on)");
-
-    log::info!("Starting STT server...");
-    stt_server::start(pipeline.clone())?;
-    log::info!("STT server started");
-
     match ui_mode {
         #[cfg(feature = "gui")]
-        UiMode::Gui => run_gui(state, cfg, pipeline, audio_stream, registry, action_factory)?,
+        UiMode::Gui => {
+            let audio_stream = start_interactive(&state, &cfg, &pipeline)?;
+            run_gui(state, cfg, pipeline, audio_stream, registry, action_factory)?
+        }
         #[cfg(feature = "tui")]
-        UiMode::Tui => tui::run_tui(state, cfg, pipeline, audio_stream)?,
+        UiMode::Tui => {
+            let audio_stream = start_interactive(&state, &cfg, &pipeline)?;
+            tui::run_tui(state, cfg, pipeline, audio_stream, action_factory)?
+        }
+        UiMode::Headless => run_headless(state, &cfg, pipeline)?,
     }
 
     log::info!("─── voxctrl stopped ───");
@@ -70,6 +70,17 @@ pub fn build_tray() -> Result<(TrayIcon, TrayMenuIds)> {
     Ok((tray, menu_ids))
 }
 
+/// Update the tray tooltip with the active input device (`None` while
+/// capture is being reopened).
+pub fn update_tray_device(tray: &TrayIcon, device: Option<&str>) -> Result<()> {
+    let tooltip = match device {
+        Some(name) => format!("voxctrl Dictation — mic: {name}"),
+        None => "voxctrl Dictation — microphone lost, reconnecting…".into(),
+    };
+    tray.set_tooltip(Some(&tooltip)).context("set tray tooltip")?;
+    Ok(())
+}
+
 /// Update the tray icon color to reflect current status.
 #[allow(dead_code)]
 pub fn update_tray_icon(tray: &TrayIcon, status: AppStatus) -> Result<()> {
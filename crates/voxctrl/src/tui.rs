@@ -1,34 +1,165 @@
 //! TUI mode — ratatui + crossterm terminal interface.
 
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use crossterm::ExecutableCommand;
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
 
-use voxctrl_core::config::Config;
+use voxctrl_core::action::ActionFactory;
+use voxctrl_core::audio::device::{CaptureSupervisor, DeviceEvent};
+use voxctrl_core::audio::{self, CaptureStream};
+use voxctrl_core::config::{ActionConfig, Config};
+use voxctrl_core::history::{self, HistoryEntry, RETYPE_DELAY};
 use voxctrl_core::pipeline::SharedPipeline;
 use voxctrl_core::{AppStatus, SharedState};
 
+/// History panel: entries from the history file, filtered by a search query.
+struct HistoryPanel {
+    path: PathBuf,
+    entries: Vec<HistoryEntry>,
+    /// File length at the last load, to detect appends.
+    file_len: Option<u64>,
+    query: String,
+    searching: bool,
+    list: ListState,
+    notice: Option<String>,
+}
+
+impl HistoryPanel {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            entries: Vec::new(),
+            file_len: None,
+            query: String::new(),
+            searching: false,
+            list: ListState::default(),
+            notice: None,
+        }
+    }
+
+    /// Reload the file if its length changed since the last load.
+    fn refresh(&mut self) {
+        let len = std::fs::metadata(&self.path).ok().map(|m| m.len());
+        if len == self.file_len {
+            return;
+        }
+        self.file_len = len;
+        match history::load(&self.path) {
+            Ok(entries) => self.entries = entries,
+            Err(e) => log::warn!("Failed to load history from {:?}: {e}", self.path),
+        }
+        self.clamp_selection();
+    }
+
+    fn matches(&self) -> Vec<&HistoryEntry> {
+        history::search(&self.entries, &self.query)
+    }
+
+    fn selected(&self) -> Option<&HistoryEntry> {
+        self.list.selected().and_then(|i| self.matches().get(i).copied())
+    }
+
+    fn clamp_selection(&mut self) {
+        let n = self.matches().len();
+        let selected = match self.list.selected() {
+            _ if n == 0 => None,
+            Some(i) => Some(i.min(n - 1)),
+            None => Some(0),
+        };
+        self.list.select(selected);
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let n = self.matches().len();
+        if n == 0 {
+            return;
+        }
+        let i = self.list.selected().unwrap_or(0);
+        self.list.select(Some(if down { (i + 1).min(n - 1) } else { i.saturating_sub(1) }));
+    }
+
+    fn set_query(&mut self, query: String) {
+        self.query = query;
+        self.list.select(None);
+        self.clamp_selection();
+    }
+
+    fn retype_selected(&mut self, cfg: &ActionConfig, action_factory: Option<&ActionFactory>) {
+        let Some(text) = self.selected().map(|e| e.text.clone()) else {
+            return;
+        };
+        self.notice = Some(match history::retype(text, cfg, action_factory) {
+            Ok(_) => format!("Re-typing in {}s — focus the target window", RETYPE_DELAY.as_secs()),
+            Err(e) => format!("Re-type failed: {e}"),
+        });
+    }
+
+    fn items(&self) -> Vec<ListItem<'static>> {
+        self.matches()
+            .into_iter()
+            .map(|e| {
+                let marker = if e.error.is_some() {
+                    Span::styled("✗ ", Style::default().fg(Color::Red))
+                } else {
+                    Span::raw("  ")
+                };
+                let text = e.text.lines().next().unwrap_or_default().to_string();
+                ListItem::new(Line::from(vec![
+                    marker,
+                    Span::styled(format!("{:>10}  ", e.age()), Style::default().fg(Color::DarkGray)),
+                    Span::raw(text),
+                ]))
+            })
+            .collect()
+    }
+}
+
 pub fn run_tui(
     state: Arc<SharedState>,
     cfg: Config,
     pipeline: Arc<SharedPipeline>,
-    _audio_stream: cpal::Stream,
+    audio_stream: CaptureStream,
+    action_factory: Option<Box<ActionFactory>>,
 ) -> Result<()> {
     // Setup terminal
     terminal::enable_raw_mode()?;
     std::io::stdout().execute(EnterAlternateScreen)?;
     let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
 
-    log::info!("TUI mode — Space=toggle, q/Ctrl-C=quit");
+    log::info!("TUI mode — Space=toggle, /=search history, Enter=re-type, q/Ctrl-C=quit");
+    let help = if cfg.vad.hands_free {
+        " Hands-free  /=search  ↑↓ Enter=re-type  q/Ctrl-C=quit "
+    } else {
+        " Space=toggle  /=search  ↑↓ Enter=re-type  q/Ctrl-C=quit "
+    };
+    let mut panel = HistoryPanel::new(history::history_path(&cfg.history));
+    let mut capture = CaptureSupervisor::new(Some(audio_stream));
 
     loop {
-        let status = *state.status.lock().unwrap();
+        let status = state.status.load();
+        panel.refresh();
+
+        // Reopen capture if the microphone was unplugged
+        match capture.poll(Instant::now(), || audio::start_capture(state.clone(), &cfg, pipeline.clone())) {
+            Some(DeviceEvent::Lost(name)) => {
+                panel.notice = Some(format!("Microphone '{name}' lost — reconnecting…"));
+            }
+            Some(DeviceEvent::Reconnected(name)) => {
+                panel.notice = Some(format!("Microphone switched to '{name}'"));
+            }
+            None => {}
+        }
+        let heading = match capture.device_name() {
+            Some(name) => format!(" voxctrl — {name} "),
+            None => " voxctrl — no microphone ".to_string(),
+        };
 
         terminal.draw(|frame| {
             let area = frame.area();
@@ -54,22 +185,38 @@ pub fn run_tui(
                 .block(
                     Block::default()
                         .borders(Borders::ALL)
-                        .title(" voxctrl ")
-                        .title_bottom(" Space=toggle  q/Ctrl-C=quit "),
+                        .title(heading)
+                        .title_bottom(help),
                 );
 
-            // Center vertically
-            let v_pad = area.height.saturating_sub(3) / 2;
-            let centered = Layout::default()
+            // Status on top, history below, search / notice line at the bottom
+            let rows = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
-                    Constraint::Length(v_pad),
                     Constraint::Length(3),
-                    Constraint::Min(0),
+                    Constraint::Min(3),
+                    Constraint::Length(1),
                 ])
                 .split(area);
 
-            frame.render_widget(para, centered[1]);
+            frame.render_widget(para, rows[0]);
+
+            let title = if panel.query.is_empty() {
+                format!(" History ({}) ", panel.entries.len())
+            } else {
+                format!(" History — {:?} ({}) ", panel.query, panel.matches().len())
+            };
+            let list = List::new(panel.items())
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            frame.render_stateful_widget(list, rows[1], &mut panel.list);
+
+            let bottom = if panel.searching {
+                Line::from(format!("/{}", panel.query))
+            } else {
+                Line::from(panel.notice.clone().unwrap_or_default())
+            };
+            frame.render_widget(Paragraph::new(bottom), rows[2]);
         })?;
 
         // Poll for input with 100ms timeout for status refresh
@@ -78,12 +225,40 @@ pub fn run_tui(
                 if key.kind != KeyEventKind::Press {
                     continue;
                 }
+                if panel.searching {
+                    match (key.code, key.modifiers) {
+                        (KeyCode::Char('c'), m) if m.contains(KeyModifiers::CONTROL) => break,
+                        (KeyCode::Char(c), _) => {
+                            let query = format!("{}{c}", panel.query);
+                            panel.set_query(query);
+                        }
+                        (KeyCode::Backspace, _) => {
+                            let mut query = panel.query.clone();
+                            query.pop();
+                            panel.set_query(query);
+                        }
+                        (KeyCode::Enter, _) => panel.searching = false,
+                        (KeyCode::Esc, _) => {
+                            panel.searching = false;
+                            panel.set_query(String::new());
+                        }
+                        (_, _) => {}
+                    }
+                    continue;
+                }
                 match (key.code, key.modifiers) {
                     (KeyCode::Char('c'), m) if m.contains(KeyModifiers::CONTROL) => break,
                     (KeyCode::Char('q'), _) | (KeyCode::Esc, _) => break,
                     (KeyCode::Char(' '), _) => {
                         voxctrl_core::recording::toggle_recording(&state, &cfg, &pipeline);
                     }
+                    (KeyCode::Char('/'), _) => {
+                        panel.searching = true;
+                        panel.notice = None;
+                    }
+                    (KeyCode::Up, _) | (KeyCode::Char('k'), _) => panel.move_selection(false),
+                    (KeyCode::Down, _) | (KeyCode::Char('j'), _) => panel.move_selection(true),
+                    (KeyCode::Enter, _) => panel.retype_selected(&cfg.action, action_factory.as_deref()),
                     (_, _) => {}
                 }
             }
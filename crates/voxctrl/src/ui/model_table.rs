@@ -6,7 +6,8 @@ use std::time::{Duration, Instant};
 use global_hotkey::hotkey::HotKey;
 use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
 
-use voxctrl_core::config::{self, GpuBackend};
+use voxctrl_core::config::{self, GpuBackend, HotkeyMode, InjectionMode};
+use voxctrl_core::history::{self, HistoryEntry, RETYPE_DELAY};
 use voxctrl_core::models::{DownloadStatus, ModelCategory, ModelRegistry};
 use voxctrl_core::models::catalog::ModelInfo;
 
@@ -14,6 +15,9 @@ use voxctrl_core::models::catalog::ModelInfo;
 const FLASH_DURATION: Duration = Duration::from_millis(1500);
 /// Same value as fractional seconds, for the alpha fade calculation.
 const FLASH_DURATION_SECS: f32 = FLASH_DURATION.as_millis() as f32 / 1000.0;
+/// Ambient noise recorded by the VAD calibration in the Test tab.
+#[cfg(feature = "vad-energy")]
+const CALIBRATION_DURATION: Duration = Duration::from_secs(3);
 
 // ── Option tables for combo boxes ─────────────────────────────────────────
 
@@ -33,6 +37,7 @@ const WHISPER_MODELS: &[(&str, &str)] = &[
 
 const VAD_BACKENDS: &[(&str, &str)] = &[
     ("energy", "Energy (built-in)"),
+    ("spectral", "Spectral (built-in)"),
     ("silero", "Silero VAD v5"),
 ];
 
@@ -42,6 +47,17 @@ const CU_PROVIDER_TYPES: &[(&str, &str)] = &[
     ("local", "Local LLM"),
 ];
 
+const HOTKEY_MODES: &[(HotkeyMode, &str)] = &[
+    (HotkeyMode::Toggle, "Toggle (press to start / stop)"),
+    (HotkeyMode::Hold, "Hold (push-to-talk)"),
+];
+
+const INJECTION_MODES: &[(InjectionMode, &str)] = &[
+    (InjectionMode::Keystrokes, "Keystrokes"),
+    (InjectionMode::ClipboardPaste, "Clipboard paste"),
+    (InjectionMode::Auto, "Auto (paste long text)"),
+];
+
 const GPU_BACKENDS: &[(GpuBackend, &str)] = &[
     (GpuBackend::Auto, "Auto-detect"),
     (GpuBackend::Cuda, "CUDA (NVIDIA)"),
@@ -248,6 +264,24 @@ fn draw_hotkey_capture(
     ui.end_row();
 }
 
+/// Grid row with a toggle / hold combo box for the hotkey above it.
+fn draw_hotkey_mode(ui: &mut egui::Ui, mode: &mut HotkeyMode, id_salt: &str) {
+    ui.label("");
+    let selected_label = HOTKEY_MODES
+        .iter()
+        .find(|(v, _)| v == mode)
+        .map(|(_, label)| *label)
+        .unwrap_or("Unknown");
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(selected_label)
+        .show_ui(ui, |ui| {
+            for &(value, label) in HOTKEY_MODES {
+                ui.selectable_value(mode, value, label);
+            }
+        });
+    ui.end_row();
+}
+
 // ── App state ─────────────────────────────────────────────────────────────
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -261,12 +295,14 @@ enum CaptureState {
 enum CaptureTarget {
     Dictation,
     ComputerUse,
+    Undo,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum Tab {
     Settings,
     Test,
+    History,
     Models,
 }
 
@@ -282,6 +318,70 @@ struct SttTiming {
     method: String, // "server" or "direct"
 }
 
+/// History tab: entries from the history file, filtered by a search query.
+struct HistoryState {
+    path: PathBuf,
+    entries: Vec<HistoryEntry>,
+    query: String,
+    /// Index into the current search results.
+    selected: Option<usize>,
+    notice: Option<String>,
+    /// Backend for re-transcribing retained audio.
+    retranscribe_backend: String,
+    /// Result slot of the running re-transcription ("Error: …" on failure).
+    retranscribed: Option<Arc<Mutex<Option<String>>>>,
+}
+
+impl HistoryState {
+    fn new(path: PathBuf, backend: String) -> Self {
+        Self {
+            path,
+            entries: Vec::new(),
+            query: String::new(),
+            selected: None,
+            notice: None,
+            retranscribe_backend: backend,
+            retranscribed: None,
+        }
+    }
+
+    fn select(&mut self, selected: Option<usize>) {
+        self.selected = selected;
+        self.notice = None;
+        self.retranscribed = None;
+    }
+
+    fn reload(&mut self) {
+        match history::load(&self.path) {
+            Ok(entries) => self.entries = entries,
+            Err(e) => log::warn!("Failed to load history from {:?}: {e}", self.path),
+        }
+        self.select(None);
+    }
+
+    /// Re-run an entry's retained audio through the chosen backend on a background thread.
+    fn start_retranscribe(&mut self, entry: &HistoryEntry) {
+        let slot = Arc::new(Mutex::new(None));
+        self.retranscribed = Some(slot.clone());
+        let path = self.path.clone();
+        let entry = entry.clone();
+        let mut stt_cfg = config::load_config().stt;
+        stt_cfg.backend = self.retranscribe_backend.clone();
+        std::thread::spawn(move || {
+            let result = voxctrl_core::stt::create_transcriber(&stt_cfg, None, Some(&voxctrl_stt::stt_factory))
+                .and_then(|stt| history::retranscribe(&path, &entry, stt.as_ref()));
+            let text = match result {
+                Ok(text) => text,
+                Err(e) => {
+                    log::error!("Re-transcription with {} failed: {e:#}", stt_cfg.backend);
+                    format!("Error: {e:#}")
+                }
+            };
+            *slot.lock().unwrap() = Some(text);
+        });
+    }
+}
+
 struct TestState {
     // Step 1: Mic test
     mic_active: bool,
@@ -297,6 +397,11 @@ struct TestState {
     // Step 3: VAD test
     vad_bypass: bool,
     vad_detecting: bool,
+    /// Ambient noise is being recorded for calibration until this time.
+    #[cfg(feature = "vad-energy")]
+    calibrating_until: Option<Instant>,
+    #[cfg(feature = "vad-energy")]
+    calibration: Option<voxctrl_core::vad::energy::Calibration>,
 
     // Step 4: STT test
     stt_status: String,
@@ -348,6 +453,10 @@ impl Default for TestState {
             hotkey_detected: false,
             vad_bypass: true,
             vad_detecting: false,
+            #[cfg(feature = "vad-energy")]
+            calibrating_until: None,
+            #[cfg(feature = "vad-energy")]
+            calibration: None,
             stt_status: String::new(),
             stt_result: String::new(),
             stt_result_slot: None,
@@ -379,10 +488,16 @@ impl Default for TestState {
 struct SettingsSnapshot {
     selected_device: String,
     hotkey_dict_shortcut: String,
+    hotkey_dict_mode: HotkeyMode,
     hotkey_cu_shortcut: String,
+    hotkey_cu_mode: HotkeyMode,
+    hotkey_undo_shortcut: String,
     stt_backend: String,
     whisper_model: String,
+    spoken_punctuation: bool,
     vad_backend: String,
+    vad_hands_free: bool,
+    injection: InjectionMode,
     gpu_backend: GpuBackend,
     cu_provider_type: String,
     cu_model: String,
@@ -400,18 +515,25 @@ impl SettingsSnapshot {
             sections.push("Input");
         }
         if self.hotkey_dict_shortcut != other.hotkey_dict_shortcut
+            || self.hotkey_dict_mode != other.hotkey_dict_mode
             || self.hotkey_cu_shortcut != other.hotkey_cu_shortcut
+            || self.hotkey_cu_mode != other.hotkey_cu_mode
+            || self.hotkey_undo_shortcut != other.hotkey_undo_shortcut
         {
             sections.push("Hotkeys");
         }
         if self.stt_backend != other.stt_backend
             || self.whisper_model != other.whisper_model
+            || self.spoken_punctuation != other.spoken_punctuation
         {
             sections.push("Speech-to-Text");
         }
-        if self.vad_backend != other.vad_backend {
+        if self.vad_backend != other.vad_backend || self.vad_hands_free != other.vad_hands_free {
             sections.push("Voice Activity Detection");
         }
+        if self.injection != other.injection {
+            sections.push("Text Entry");
+        }
         if self.gpu_backend != other.gpu_backend {
             sections.push("GPU / Acceleration");
         }
@@ -434,14 +556,21 @@ pub struct SettingsApp {
     prev_tab: Tab,
     model_tab: ModelCategory,
     test: TestState,
+    history: HistoryState,
     // Editable config fields
     available_devices: Vec<String>,
     selected_device: String,
     hotkey_dict_shortcut: String,
+    hotkey_dict_mode: HotkeyMode,
     hotkey_cu_shortcut: String,
+    hotkey_cu_mode: HotkeyMode,
+    hotkey_undo_shortcut: String,
     stt_backend: String,
     whisper_model: String,
+    spoken_punctuation: bool,
     vad_backend: String,
+    vad_hands_free: bool,
+    injection: InjectionMode,
     gpu_backend: GpuBackend,
     gpu_detected: String,
     gpu_mode: String,
@@ -476,6 +605,7 @@ pub struct SettingsApp {
     capture_target: CaptureTarget,
     hotkey_include_super: bool,
     hotkey_cu_include_super: bool,
+    hotkey_undo_include_super: bool,
     // Test tab
     dict_hotkey_toggled: bool,
     test_hotkey: Option<TestHotkeyState>,
@@ -541,13 +671,20 @@ pub fn run_settings_standalone() -> anyhow::Result<()> {
         prev_tab: Tab::Settings,
         model_tab: ModelCategory::Stt,
         test: TestState::default(),
+        history: HistoryState::new(history::history_path(&cfg.history), cfg.stt.backend.clone()),
         available_devices: voxctrl_core::audio::list_input_devices(),
         selected_device: cfg.audio.device_pattern.clone(),
         hotkey_dict_shortcut: cfg.hotkey.dict_shortcut.clone(),
+        hotkey_dict_mode: cfg.hotkey.dict_mode,
         hotkey_cu_shortcut: cfg.hotkey.cu_shortcut.clone().unwrap_or_default(),
+        hotkey_cu_mode: cfg.hotkey.cu_mode,
+        hotkey_undo_shortcut: cfg.hotkey.undo_shortcut.clone().unwrap_or_default(),
         stt_backend: cfg.stt.backend.clone(),
         whisper_model: cfg.stt.whisper_model.clone(),
+        spoken_punctuation: cfg.postprocess.spoken_punctuation,
         vad_backend: cfg.vad.backend.clone(),
+        vad_hands_free: cfg.vad.hands_free,
+        injection: cfg.action.injection,
         gpu_backend: cfg.gpu.backend,
         gpu_detected: {
             let gpus = voxctrl_core::gpu::detect_gpus();
@@ -591,6 +728,9 @@ pub fn run_settings_standalone() -> anyhow::Result<()> {
         hotkey_cu_include_super: cfg.hotkey.cu_shortcut.as_deref().map_or(false, |s| {
             s.to_lowercase().split('+').any(|t| matches!(t.trim(), "super" | "win" | "meta" | "cmd"))
         }),
+        hotkey_undo_include_super: cfg.hotkey.undo_shortcut.as_deref().is_some_and(|s| {
+            s.to_lowercase().split('+').any(|t| matches!(t.trim(), "super" | "win" | "meta" | "cmd"))
+        }),
         dict_hotkey_toggled: false,
         test_hotkey: None,
         test_hotkey_error: None,
@@ -599,10 +739,16 @@ pub fn run_settings_standalone() -> anyhow::Result<()> {
         prev_snapshot: SettingsSnapshot {
             selected_device: cfg.audio.device_pattern.clone(),
             hotkey_dict_shortcut: cfg.hotkey.dict_shortcut.clone(),
+            hotkey_dict_mode: cfg.hotkey.dict_mode,
             hotkey_cu_shortcut: cfg.hotkey.cu_shortcut.clone().unwrap_or_default(),
+            hotkey_cu_mode: cfg.hotkey.cu_mode,
+            hotkey_undo_shortcut: cfg.hotkey.undo_shortcut.clone().unwrap_or_default(),
             stt_backend: cfg.stt.backend.clone(),
             whisper_model: cfg.stt.whisper_model.clone(),
+            spoken_punctuation: cfg.postprocess.spoken_punctuation,
             vad_backend: cfg.vad.backend.clone(),
+            vad_hands_free: cfg.vad.hands_free,
+            injection: cfg.action.injection,
             gpu_backend: cfg.gpu.backend,
             cu_provider_type: cfg.action.cu_provider_type.clone(),
             cu_model: cfg.action.cu_model.clone().unwrap_or_default(),
@@ -637,6 +783,7 @@ impl eframe::App for SettingsApp {
             let include_super = match self.capture_target {
                 CaptureTarget::Dictation => self.hotkey_include_super,
                 CaptureTarget::ComputerUse => self.hotkey_cu_include_super,
+                CaptureTarget::Undo => self.hotkey_undo_include_super,
             };
             let captured = ctx.input(|i| {
                 for event in &i.events {
@@ -669,6 +816,7 @@ impl eframe::App for SettingsApp {
                     match self.capture_target {
                         CaptureTarget::Dictation => self.hotkey_dict_shortcut = shortcut,
                         CaptureTarget::ComputerUse => self.hotkey_cu_shortcut = shortcut,
+                        CaptureTarget::Undo => self.hotkey_undo_shortcut = shortcut,
                     }
                     self.capture_state = CaptureState::Idle;
                 }
@@ -684,6 +832,9 @@ impl eframe::App for SettingsApp {
             if self.tab == Tab::Test {
                 self.register_test_hotkey();
             }
+            if self.tab == Tab::History {
+                self.history.reload();
+            }
             self.prev_tab = self.tab;
         }
 
@@ -742,6 +893,7 @@ impl eframe::App for SettingsApp {
             ui.horizontal(|ui| {
                 ui.selectable_value(&mut self.tab, Tab::Settings, "Settings");
                 ui.selectable_value(&mut self.tab, Tab::Test, "Test");
+                ui.selectable_value(&mut self.tab, Tab::History, "History");
                 ui.selectable_value(&mut self.tab, Tab::Models, "Models");
             });
             ui.separator();
@@ -749,6 +901,7 @@ impl eframe::App for SettingsApp {
             match self.tab {
                 Tab::Settings => self.draw_settings_tab(ui),
                 Tab::Test => self.draw_test_tab(ui),
+                Tab::History => self.draw_history_tab(ui),
                 Tab::Models => self.draw_models_tab(ui),
             }
         });
@@ -757,10 +910,16 @@ impl eframe::App for SettingsApp {
         let current = SettingsSnapshot {
             selected_device: self.selected_device.clone(),
             hotkey_dict_shortcut: self.hotkey_dict_shortcut.clone(),
+            hotkey_dict_mode: self.hotkey_dict_mode,
             hotkey_cu_shortcut: self.hotkey_cu_shortcut.clone(),
+            hotkey_cu_mode: self.hotkey_cu_mode,
+            hotkey_undo_shortcut: self.hotkey_undo_shortcut.clone(),
             stt_backend: self.stt_backend.clone(),
             whisper_model: self.whisper_model.clone(),
+            spoken_punctuation: self.spoken_punctuation,
             vad_backend: self.vad_backend.clone(),
+            vad_hands_free: self.vad_hands_free,
+            injection: self.injection,
             gpu_backend: self.gpu_backend,
             cu_provider_type: self.cu_provider_type.clone(),
             cu_model: self.cu_model.clone(),
@@ -874,6 +1033,17 @@ impl SettingsApp {
                         &mut self.hotkey_include_super,
                         "Dictation",
                     );
+                    draw_hotkey_mode(ui, &mut self.hotkey_dict_mode, "hotkey_dict_mode");
+
+                    draw_hotkey_capture(
+                        ui,
+                        &mut self.capture_state,
+                        &mut self.capture_target,
+                        CaptureTarget::Undo,
+                        &mut self.hotkey_undo_shortcut,
+                        &mut self.hotkey_undo_include_super,
+                        "Undo Hotkey",
+                    );
 
                     #[cfg(any(feature = "cu-windows", feature = "cu-macos", feature = "cu-linux"))]
                     {
@@ -886,6 +1056,7 @@ impl SettingsApp {
                             &mut self.hotkey_cu_include_super,
                             "CU Hotkey",
                         );
+                        draw_hotkey_mode(ui, &mut self.hotkey_cu_mode, "hotkey_cu_mode");
                     }
                 });
             });
@@ -926,6 +1097,11 @@ impl SettingsApp {
                             });
                         ui.end_row();
                     }
+
+                    ui.label("Punctuation");
+                    ui.checkbox(&mut self.spoken_punctuation, "Spoken punctuation")
+                        .on_hover_text("Convert \"comma\", \"new paragraph\", \"open quote\" etc. into symbols");
+                    ui.end_row();
                 });
             });
 
@@ -949,6 +1125,36 @@ impl SettingsApp {
                         }
                     });
                     ui.end_row();
+
+                    ui.label("Hands-free");
+                    ui.checkbox(&mut self.vad_hands_free, "Auto-segment speech (no hotkey)")
+                        .on_hover_text("Start and stop dictation automatically when speech is detected");
+                    ui.end_row();
+                });
+            });
+
+            ui.add_space(4.0);
+
+            // ── Text Entry section ──
+            ui.group(|ui| {
+                self.draw_section_header(ui, "Text Entry");
+                egui::Grid::new("settings_text_entry").num_columns(2).spacing([12.0, 8.0]).show(ui, |ui| {
+                    ui.label("Injection");
+                    let selected_label = INJECTION_MODES
+                        .iter()
+                        .find(|(v, _)| *v == self.injection)
+                        .map(|(_, label)| *label)
+                        .unwrap_or("Unknown");
+                    egui::ComboBox::from_id_salt("injection_mode")
+                        .selected_text(selected_label)
+                        .show_ui(ui, |ui| {
+                            for &(value, label) in INJECTION_MODES {
+                                ui.selectable_value(&mut self.injection, value, label);
+                            }
+                        })
+                        .response
+                        .on_hover_text("Clipboard paste restores the previous clipboard text; with an image or files on the clipboard, text is typed instead");
+                    ui.end_row();
                 });
             });
 
@@ -1092,14 +1298,24 @@ impl SettingsApp {
         let mut cfg = config::load_config();
         cfg.audio.device_pattern = self.selected_device.clone();
         cfg.hotkey.dict_shortcut = self.hotkey_dict_shortcut.clone();
+        cfg.hotkey.dict_mode = self.hotkey_dict_mode;
+        cfg.hotkey.cu_mode = self.hotkey_cu_mode;
         cfg.hotkey.cu_shortcut = if self.hotkey_cu_shortcut.is_empty() {
             None
         } else {
             Some(self.hotkey_cu_shortcut.clone())
         };
+        cfg.hotkey.undo_shortcut = if self.hotkey_undo_shortcut.is_empty() {
+            None
+        } else {
+            Some(self.hotkey_undo_shortcut.clone())
+        };
         cfg.stt.backend = self.stt_backend.clone();
         cfg.stt.whisper_model = self.whisper_model.clone();
+        cfg.postprocess.spoken_punctuation = self.spoken_punctuation;
         cfg.vad.backend = self.vad_backend.clone();
+        cfg.vad.hands_free = self.vad_hands_free;
+        cfg.action.injection = self.injection;
         cfg.action.cu_provider_type = self.cu_provider_type.clone();
         cfg.action.cu_model = if self.cu_model.is_empty() { None } else { Some(self.cu_model.clone()) };
         cfg.action.cu_api_base_url = if self.cu_api_base_url.is_empty() { None } else { Some(self.cu_api_base_url.clone()) };
@@ -1189,6 +1405,11 @@ impl SettingsApp {
 
         let cfg = config::load_config();
 
+        #[cfg(feature = "vad-energy")]
+        if self.test.calibrating_until.is_some_and(|t| Instant::now() >= t) {
+            self.finish_calibration(&cfg);
+        }
+
         // ── Consume hotkey toggle set by update() ────────────────────
         if self.dict_hotkey_toggled && !self.test.hotkey_bypass {
             self.dict_hotkey_toggled = false;
@@ -1341,8 +1562,13 @@ impl SettingsApp {
                 ui.colored_label(egui::Color32::YELLOW, "Bypassed — all audio treated as speech");
             } else {
                 ui.label(format!("Backend: {}", cfg.vad.backend));
+                #[cfg(feature = "vad-energy")]
+                if cfg.vad.backend == "energy" {
+                    self.draw_calibration(ui, &cfg);
+                }
+                let threshold = if cfg.vad.backend == "energy" { cfg.vad.energy_threshold as f32 } else { 0.03 };
                 if self.test.mic_active {
-                    if self.test.mic_level > 0.03 {
+                    if self.test.mic_level > threshold {
                         self.test.vad_detecting = true;
                         ui.colored_label(egui::Color32::GREEN, "Speech detected");
                     } else {
@@ -1582,6 +1808,69 @@ impl SettingsApp {
         }
     }
 
+    #[cfg(feature = "vad-energy")]
+    fn draw_calibration(&mut self, ui: &mut egui::Ui, cfg: &config::Config) {
+        ui.horizontal(|ui| {
+            let mode = if cfg.vad.energy_adaptive { "adaptive, seeded at" } else { "fixed" };
+            ui.label(format!("Threshold: {:.4} ({mode})", cfg.vad.energy_threshold));
+            if let Some(until) = self.test.calibrating_until {
+                let left = until.saturating_duration_since(Instant::now()).as_secs_f32();
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!("Measuring ambient noise — stay quiet ({left:.0}s)"),
+                );
+            } else {
+                let recording = self.test.recording.load(std::sync::atomic::Ordering::Relaxed);
+                if ui.add_enabled(!recording, egui::Button::new("Calibrate")).clicked() {
+                    self.start_calibration(cfg);
+                }
+            }
+        });
+        if let Some(cal) = self.test.calibration {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Ambient noise {:.1} dBFS — proposed threshold {:.4}",
+                    20.0 * cal.noise_rms.log10(),
+                    cal.threshold,
+                ));
+                if ui.button("Apply").clicked() {
+                    let mut cfg = config::load_config();
+                    cfg.vad.energy_threshold = cal.threshold;
+                    config::save_config(&cfg);
+                    log::info!("VAD energy threshold calibrated to {:.4}", cal.threshold);
+                    self.test.calibration = None;
+                }
+            });
+        }
+    }
+
+    /// Record a few seconds of ambient noise through the test capture.
+    #[cfg(feature = "vad-energy")]
+    fn start_calibration(&mut self, cfg: &config::Config) {
+        if !self.test.mic_active {
+            self.start_mic_test(cfg);
+        }
+        self.test.test_chunks.lock().unwrap().clear();
+        self.test.recording.store(true, std::sync::atomic::Ordering::Relaxed);
+        self.test.calibrating_until = Some(Instant::now() + CALIBRATION_DURATION);
+        self.test.calibration = None;
+    }
+
+    #[cfg(feature = "vad-energy")]
+    fn finish_calibration(&mut self, cfg: &config::Config) {
+        self.test.calibrating_until = None;
+        self.test.recording.store(false, std::sync::atomic::Ordering::Relaxed);
+        let ambient: Vec<f32> = self.test.test_chunks.lock().unwrap().drain(..).collect();
+        self.test.calibration = voxctrl_core::vad::energy::calibrate(
+            &ambient,
+            self.test.mic_sample_rate,
+            cfg.vad.onset_margin_db,
+        );
+        if self.test.calibration.is_none() {
+            self.test.stt_status = "Calibration captured no audio".into();
+        }
+    }
+
     fn start_test_recording(&mut self, cfg: &config::Config) {
         if !self.test.mic_active {
             self.start_mic_test(cfg);
@@ -1907,7 +2196,8 @@ fn transcribe_pcm_via_server_or_direct(
         Err(server_err) => {
             log::warn!("[testbed] Server unavailable ({server_err:#}), trying direct transcriber...");
             let transcriber = voxctrl_core::stt::create_transcriber(stt_cfg, None, Some(&voxctrl_stt::stt_factory))?;
-            let result = transcriber.transcribe_pcm(samples, sample_rate);
+            let samples = voxctrl_core::audio::resample::to_16k(samples, sample_rate);
+            let result = transcriber.transcribe_pcm(&samples, voxctrl_core::audio::resample::STT_SAMPLE_RATE);
             match &result {
                 Ok(text) => log::info!("[testbed] Direct transcription OK: {:?}", text),
                 Err(e) => log::error!("[testbed] Direct transcription failed: {e:#}"),
@@ -1917,6 +2207,151 @@ fn transcribe_pcm_via_server_or_direct(
     }
 }
 
+// ── History tab ───────────────────────────────────────────────────────────
+
+impl SettingsApp {
+    fn draw_history_tab(&mut self, ui: &mut egui::Ui) {
+        let state = &mut self.history;
+
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            let search = ui.add(egui::TextEdit::singleline(&mut state.query).desired_width(300.0));
+            if search.changed() {
+                state.select(None);
+            }
+            if ui.button("Refresh").clicked() {
+                state.reload();
+            }
+        });
+        ui.separator();
+
+        let matches = history::search(&state.entries, &state.query);
+        if matches.is_empty() {
+            ui.label(if state.entries.is_empty() { "No transcripts recorded yet." } else { "No matches." });
+            return;
+        }
+
+        egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+            for (i, entry) in matches.iter().enumerate() {
+                let first_line = entry.text.lines().next().unwrap_or_default();
+                let mut label = egui::RichText::new(format!("{:>10}   {first_line}", entry.age()));
+                if entry.error.is_some() {
+                    label = label.color(egui::Color32::RED);
+                }
+                if ui.selectable_label(state.selected == Some(i), label).clicked() {
+                    // Field-wise: `matches` still borrows the entries
+                    state.selected = Some(i);
+                    state.notice = None;
+                    state.retranscribed = None;
+                }
+            }
+        });
+
+        let Some(entry) = state.selected.and_then(|i| matches.get(i)).map(|e| (*e).clone()) else {
+            return;
+        };
+        let audio = history::audio_path(&state.path, &entry).filter(|p| p.exists());
+        ui.separator();
+        egui::Grid::new("history_entry").num_columns(2).spacing([12.0, 6.0]).show(ui, |ui| {
+            ui.label("Recorded");
+            ui.label(entry.age());
+            ui.end_row();
+
+            ui.label("Backend");
+            ui.label(format!(
+                "{} — STT {:.1}s, total {:.1}s",
+                entry.stt_backend, entry.stt_secs, entry.total_secs
+            ));
+            ui.end_row();
+
+            if let Some(language) = &entry.language {
+                ui.label("Language");
+                ui.label(language);
+                ui.end_row();
+            }
+
+            ui.label("Intent");
+            ui.label(match &entry.intent {
+                Some(voxctrl_core::router::Intent::Dictate(_)) => "Dictate".to_string(),
+                Some(voxctrl_core::router::Intent::Command { action, .. }) => format!("Command: {action}"),
+                None => "—".to_string(),
+            });
+            ui.end_row();
+
+            ui.label("Outcome");
+            match &entry.error {
+                Some(e) => ui.colored_label(egui::Color32::RED, e),
+                None => ui.label("OK"),
+            };
+            ui.end_row();
+
+            if entry.raw_text != entry.text {
+                ui.label("Raw transcript");
+                ui.label(&entry.raw_text);
+                ui.end_row();
+            }
+
+            ui.label("Text");
+            ui.label(&entry.text);
+            ui.end_row();
+
+            if entry.audio.is_some() {
+                ui.label("Audio");
+                match &audio {
+                    Some(path) => ui.label(path.display().to_string()),
+                    None => ui.weak("Deleted by retention limits"),
+                };
+                ui.end_row();
+            }
+
+            if let Some(slot) = &state.retranscribed {
+                ui.label("Re-transcribed");
+                match slot.lock().unwrap().as_deref() {
+                    Some(text) if text.starts_with("Error:") => ui.colored_label(egui::Color32::RED, text),
+                    Some(text) => ui.label(text),
+                    None => {
+                        ui.ctx().request_repaint_after(Duration::from_millis(200));
+                        ui.spinner()
+                    }
+                };
+                ui.end_row();
+            }
+        });
+
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            if ui.button("Re-type").on_hover_text("Type this text again at the cursor").clicked() {
+                let action_cfg = config::load_config().action;
+                let action_factory = crate::build_action_factory();
+                state.notice = Some(match history::retype(entry.text.clone(), &action_cfg, action_factory.as_deref()) {
+                    Ok(_) => format!("Typing in {}s — focus the target window", RETYPE_DELAY.as_secs()),
+                    Err(e) => format!("Re-type failed: {e}"),
+                });
+            }
+            if let Some(notice) = &state.notice {
+                ui.label(notice);
+            }
+        });
+
+        if audio.is_some() {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("retranscribe_backend")
+                    .selected_text(lookup_label(STT_BACKENDS, &state.retranscribe_backend))
+                    .show_ui(ui, |ui| {
+                        for &(value, label) in STT_BACKENDS {
+                            ui.selectable_value(&mut state.retranscribe_backend, value.to_string(), label);
+                        }
+                    });
+                let running = state.retranscribed.as_ref().is_some_and(|s| s.lock().unwrap().is_none());
+                let button = ui.add_enabled(!running, egui::Button::new("Re-transcribe"));
+                if button.on_hover_text("Run the kept audio through the selected backend").clicked() {
+                    state.start_retranscribe(&entry);
+                }
+            });
+        }
+    }
+}
+
 // ── Models tab ────────────────────────────────────────────────────────────
 
 impl SettingsApp {
@@ -2614,10 +3049,16 @@ mod tests {
         SettingsSnapshot {
             selected_device: "DJI".into(),
             hotkey_dict_shortcut: "Ctrl+Super+Space".into(),
+            hotkey_dict_mode: HotkeyMode::Toggle,
             hotkey_cu_shortcut: String::new(),
+            hotkey_cu_mode: HotkeyMode::Toggle,
+            hotkey_undo_shortcut: String::new(),
             stt_backend: "voxtral-http".into(),
             whisper_model: "small".into(),
+            spoken_punctuation: false,
             vad_backend: "energy".into(),
+            vad_hands_free: false,
+            injection: InjectionMode::Keystrokes,
             gpu_backend: GpuBackend::Auto,
             cu_provider_type: "anthropic".into(),
             cu_model: String::new(),
@@ -2665,6 +3106,30 @@ mod tests {
         assert_eq!(a.changed_sections(&b), vec!["Hotkeys"]);
     }
 
+    #[test]
+    fn hotkey_mode_change_returns_hotkeys_section() {
+        let a = default_snapshot();
+        let mut b = default_snapshot();
+        b.hotkey_dict_mode = HotkeyMode::Hold;
+        assert_eq!(a.changed_sections(&b), vec!["Hotkeys"]);
+    }
+
+    #[test]
+    fn undo_hotkey_change_returns_hotkeys_section() {
+        let a = default_snapshot();
+        let mut b = default_snapshot();
+        b.hotkey_undo_shortcut = "Ctrl+Super+Z".into();
+        assert_eq!(a.changed_sections(&b), vec!["Hotkeys"]);
+    }
+
+    #[test]
+    fn injection_change_returns_text_entry_section() {
+        let a = default_snapshot();
+        let mut b = default_snapshot();
+        b.injection = InjectionMode::ClipboardPaste;
+        assert_eq!(a.changed_sections(&b), vec!["Text Entry"]);
+    }
+
     #[test]
     fn cu_field_change_returns_computer_use_section() {
         let a = default_snapshot();